@@ -0,0 +1,51 @@
+use std::sync::{Arc, Mutex};
+
+use crate::model::types::{Ack, AckSource, Command, CommandSink};
+
+// Hands out commands to the workers of this process, one driver at a time.
+#[derive(Clone)]
+pub struct Controller {
+    command_sinks: Vec<CommandSink>,
+    ack_source: AckSource,
+    // Held by whichever clone is broadcasting, so that the acks of one command do not go to the
+    // driver of another
+    broadcasting: Arc<Mutex<()>>,
+}
+
+impl Controller {
+    pub(crate) fn new(command_sinks: Vec<CommandSink>, ack_source: AckSource) -> Self {
+        Self {
+            command_sinks,
+            ack_source,
+            broadcasting: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.command_sinks.len()
+    }
+
+    // Sends the command to every worker, and waits for all of them to be through with it. The
+    // acknowledgements are ordered by worker.
+    pub fn broadcast(&self, command: Command) -> Vec<Ack> {
+        self.try_broadcast(command)
+            .expect("the workers have stopped, having shut down or panicked")
+    }
+
+    // The same, or None if the workers are gone.
+    pub(crate) fn try_broadcast(&self, command: Command) -> Option<Vec<Ack>> {
+        // Another driver panicking while broadcasting leaves nothing to clean up.
+        let _broadcasting = self
+            .broadcasting
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for command_sink in &self.command_sinks {
+            command_sink.send(command).ok()?;
+        }
+        let mut acks: Vec<Ack> = (0..self.workers())
+            .map(|_| self.ack_source.recv().ok())
+            .collect::<Option<Vec<Ack>>>()?;
+        acks.sort_by_key(|ack| ack.worker);
+        Some(acks)
+    }
+}
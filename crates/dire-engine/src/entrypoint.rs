@@ -1,104 +1,301 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
+use crate::control::Controller;
+use crate::explain::Explainer;
 use crate::materialization::common::{
-    dummy_first_stage_materialization, dummy_second_stage_materialization,
-    tbox_spo_sco_materialization,
+    dummy_consistency_check, dummy_first_stage_materialization, dummy_provenance_tracking,
+    dummy_second_stage_materialization, dummy_tbox_provenance_tracking, dummy_why_not_candidates,
+    tbox_spo_sco_materialization, tbox_spo_sco_provenance,
 };
-use crate::materialization::owl2rl::{owl2rl_abox, owl2rl_tbox};
-use crate::materialization::rdfs::rdfs;
-use crate::materialization::rdfspp::rdfspp;
+use crate::materialization::owl2rl::{
+    owl2rl_abox, owl2rl_abox_representatives, owl2rl_inconsistencies, owl2rl_provenance,
+    owl2rl_tbox, owl2rl_tbox_provenance, owl2rl_why_not,
+};
+use crate::materialization::rdfs::{
+    rdfs, rdfs_full, rdfs_full_provenance, rdfs_full_why_not, rdfs_provenance, rdfs_why_not,
+};
+use crate::materialization::rdfspp::{rdfspp, rdfspp_provenance, rdfspp_why_not};
 use crate::model::types::{
-    DoneSink, DoneSource, LogSource, MasterSink, TripleInputSink, TripleOutputSource,
+    CommandSink, CommandSource, ConsistencyCheck, ContinuousAnswerSinks, ExplainRequestCollection,
+    IllTypedInputSink, InconsistencyOutputSource, ListCollection, LogSource, ProvenanceCollection,
+    ProvenanceOutputSource, ProvenanceTracking, RdfsEntailment, SecondStageMaterialization,
+    TboxProvenanceTracking, TimedTripleInputSink, TripleCollection, TripleInputSink,
+    TripleOutputSource, WhyNotCandidates, WhyNotCollection,
 };
-use crate::reason::reason;
+use crate::query::Querier;
+use crate::reason::{reason, Logic, Options, Sinks, Sources};
+use timely::CommunicationConfig;
 
 pub enum Engine {
     RDFS,
+    RDFSFull(RdfsEntailment),
     RDFSpp,
     OWL2RL,
+    // OWL 2 RL with a representative per sameAs clique, instead of eq-rep-s, eq-rep-p and eq-rep-o
+    OWL2RLRepresentatives,
     Dummy,
 }
 
-pub fn entrypoint(
+// The amount of workers in this process, each of which reports on its own when done.
+pub(crate) fn local_workers(cfg: &timely::Config) -> usize {
+    match cfg.communication {
+        CommunicationConfig::Thread => 1,
+        CommunicationConfig::Process(threads) => threads,
+        CommunicationConfig::Cluster { threads, .. } => threads,
+    }
+}
+
+// Pins the signature of the closure, which would otherwise not be inferred as being generic over
+// the lifetime of the scope.
+fn second_stage<F>(materialization: F) -> SecondStageMaterialization
+where
+    F: for<'a> Fn(
+            &TripleCollection<'a>,
+            &ListCollection<'a>,
+            &TripleCollection<'a>,
+        ) -> TripleCollection<'a>
+        + Send
+        + Sync
+        + 'static,
+{
+    Arc::new(materialization)
+}
+
+// The same, for the provenance tracking that goes along with it.
+fn provenance_of<F>(provenance_tracking: F) -> ProvenanceTracking
+where
+    F: for<'a> Fn(
+            &TripleCollection<'a>,
+            &ListCollection<'a>,
+            &TripleCollection<'a>,
+        ) -> ProvenanceCollection<'a>
+        + Send
+        + Sync
+        + 'static,
+{
+    Arc::new(provenance_tracking)
+}
+
+// The same, for the why-not candidates.
+fn candidates_of<F>(why_not_candidates: F) -> WhyNotCandidates
+where
+    F: for<'a> Fn(
+            &TripleCollection<'a>,
+            &ListCollection<'a>,
+            &ExplainRequestCollection<'a>,
+        ) -> WhyNotCollection<'a>
+        + Send
+        + Sync
+        + 'static,
+{
+    Arc::new(why_not_candidates)
+}
+
+// The ends of the channels of a running engine that a reasoner drives it and reads it through.
+pub(crate) struct Handles {
+    pub(crate) tbox_input_sink: TripleInputSink,
+    pub(crate) abox_input_sink: TripleInputSink,
+    pub(crate) tbox_timed_input_sink: TimedTripleInputSink,
+    pub(crate) abox_timed_input_sink: TimedTripleInputSink,
+    pub(crate) ill_typed_input_sink: IllTypedInputSink,
+    pub(crate) tbox_output_source: TripleOutputSource,
+    pub(crate) abox_output_source: TripleOutputSource,
+    pub(crate) inconsistency_output_source: InconsistencyOutputSource,
+    pub(crate) provenance_output_source: ProvenanceOutputSource,
+    pub(crate) explainer: Explainer,
+    pub(crate) querier: Querier,
+    pub(crate) controller: Controller,
+    pub(crate) log_source: LogSource,
+    pub(crate) join_handle: thread::JoinHandle<()>,
+}
+
+// Starts the workers of this process on their own thread, with input channels of the given
+// capacity.
+pub(crate) fn entrypoint(
     cfg: timely::Config,
     batch_size: usize,
     logic: Engine,
-) -> (
-    TripleInputSink,
-    TripleInputSink,
-    TripleOutputSource,
-    TripleOutputSource,
-    DoneSource,
-    MasterSink,
-    LogSource,
-    std::thread::JoinHandle<()>,
-) {
+    options: Options,
+) -> Handles {
     let (tbox_output_sink, tbox_output_source) = flume::unbounded();
     let (tbox_input_sink, tbox_input_source) = flume::bounded(batch_size);
     let (abox_output_sink, abox_output_source) = flume::unbounded();
     let (abox_input_sink, abox_input_source) = flume::bounded(batch_size);
-    let (done_sink, done_source) = flume::bounded(0);
-    let (terminate_sink, terminate_source) = flume::bounded(0);
+    let (tbox_timed_input_sink, tbox_timed_input_source) = flume::bounded(batch_size);
+    let (abox_timed_input_sink, abox_timed_input_source) = flume::bounded(batch_size);
+    let (ill_typed_input_sink, ill_typed_input_source) = flume::unbounded();
+    let (inconsistency_output_sink, inconsistency_output_source) = flume::unbounded();
+    let (provenance_output_sink, provenance_output_source) = flume::unbounded();
+    let (explain_input_sink, explain_input_source) = flume::unbounded();
+    let (explanation_output_sink, explanation_output_source) = flume::unbounded();
+    let (why_not_output_sink, why_not_output_source) = flume::unbounded();
+    let (query_input_sink, query_input_source) = flume::unbounded();
+    let (answer_output_sink, answer_output_source) = flume::unbounded();
+    let (continuous_query_input_sink, continuous_query_input_source) = flume::unbounded();
+    let continuous_answer_sinks: ContinuousAnswerSinks = Arc::new(Mutex::new(HashMap::new()));
+    let (command_sinks, command_sources): (Vec<CommandSink>, Vec<CommandSource>) =
+        (0..local_workers(&cfg)).map(|_| flume::unbounded()).unzip();
+    let (ack_sink, ack_source) = flume::unbounded();
+    let controller = Controller::new(command_sinks, ack_source);
     let (log_sink, log_source) = flume::unbounded();
 
+    let explainer = Explainer::new(
+        explain_input_sink,
+        explanation_output_source,
+        why_not_output_source,
+        controller.clone(),
+    );
+    let querier = Querier::new(
+        query_input_sink,
+        answer_output_source,
+        continuous_query_input_sink,
+        continuous_answer_sinks.clone(),
+        controller.clone(),
+        options.explain || options.index,
+    );
+
     let join_handle = thread::spawn(move || {
+        let Options {
+            provenance,
+            explain,
+            ..
+        } = options;
         let tbox_materialization = match logic {
             Engine::Dummy => dummy_first_stage_materialization,
-            Engine::OWL2RL => owl2rl_tbox,
+            Engine::OWL2RL | Engine::OWL2RLRepresentatives => owl2rl_tbox,
             _ => tbox_spo_sco_materialization,
         };
-        let abox_materialization = match logic {
-            Engine::RDFS => rdfs,
-            Engine::RDFSpp => rdfspp,
-            Engine::OWL2RL => owl2rl_abox,
-            Engine::Dummy => dummy_second_stage_materialization,
+        let abox_materialization: SecondStageMaterialization = match logic {
+            Engine::RDFS => Arc::new(rdfs),
+            Engine::RDFSFull(entailment) => {
+                second_stage(move |tbox, lists, abox| rdfs_full(tbox, lists, abox, entailment))
+            }
+            Engine::RDFSpp => Arc::new(rdfspp),
+            Engine::OWL2RL => Arc::new(owl2rl_abox),
+            Engine::OWL2RLRepresentatives => Arc::new(owl2rl_abox_representatives),
+            Engine::Dummy => Arc::new(dummy_second_stage_materialization),
+        };
+        let consistency_check: ConsistencyCheck = match logic {
+            Engine::OWL2RL | Engine::OWL2RLRepresentatives => owl2rl_inconsistencies,
+            _ => dummy_consistency_check,
+        };
+        let provenance_tracking: ProvenanceTracking = match logic {
+            Engine::RDFS if provenance => Arc::new(rdfs_provenance),
+            Engine::RDFSFull(entailment) if provenance => {
+                provenance_of(move |tbox, lists, abox| {
+                    rdfs_full_provenance(tbox, lists, abox, entailment)
+                })
+            }
+            Engine::RDFSpp if provenance => Arc::new(rdfspp_provenance),
+            Engine::OWL2RL | Engine::OWL2RLRepresentatives if provenance => {
+                Arc::new(owl2rl_provenance)
+            }
+            _ => Arc::new(dummy_provenance_tracking),
+        };
+        let tbox_provenance_tracking: TboxProvenanceTracking = match logic {
+            Engine::RDFS | Engine::RDFSFull(_) | Engine::RDFSpp if explain => {
+                tbox_spo_sco_provenance
+            }
+            Engine::OWL2RL | Engine::OWL2RLRepresentatives if explain => owl2rl_tbox_provenance,
+            _ => dummy_tbox_provenance_tracking,
+        };
+        let why_not_candidates: WhyNotCandidates = match logic {
+            Engine::RDFS if explain => Arc::new(rdfs_why_not),
+            Engine::RDFSFull(entailment) if explain => {
+                candidates_of(move |tbox, lists, requests| {
+                    rdfs_full_why_not(tbox, lists, requests, entailment)
+                })
+            }
+            Engine::RDFSpp if explain => Arc::new(rdfspp_why_not),
+            Engine::OWL2RL | Engine::OWL2RLRepresentatives if explain => Arc::new(owl2rl_why_not),
+            _ => Arc::new(dummy_why_not_candidates),
         };
         reason(
             cfg,
-            tbox_materialization,
-            abox_materialization,
-            tbox_input_source,
-            abox_input_source,
-            tbox_output_sink,
-            abox_output_sink,
-            done_sink,
-            terminate_source,
-            log_sink,
+            Logic {
+                tbox_materialization,
+                abox_materialization,
+                consistency_check,
+                provenance_tracking,
+                tbox_provenance_tracking,
+                why_not_candidates,
+            },
+            options,
+            Sources {
+                tbox_input_source,
+                abox_input_source,
+                tbox_timed_input_source,
+                abox_timed_input_source,
+                ill_typed_input_source,
+                explain_input_source,
+                query_input_source,
+                continuous_query_input_source,
+                commands: command_sources,
+            },
+            Sinks {
+                tbox_output_sink,
+                abox_output_sink,
+                inconsistency_output_sink,
+                provenance_output_sink: provenance.then_some(provenance_output_sink),
+                explanation_output_sink,
+                why_not_output_sink,
+                answer_output_sink,
+                continuous_answer_sinks,
+                acks: ack_sink,
+                logger: log_sink,
+            },
         );
     });
-    (
+    Handles {
         tbox_input_sink,
         abox_input_sink,
+        tbox_timed_input_sink,
+        abox_timed_input_sink,
+        ill_typed_input_sink,
         tbox_output_source,
         abox_output_source,
-        done_source,
-        terminate_sink,
+        inconsistency_output_source,
+        provenance_output_source,
+        explainer,
+        querier,
+        controller,
         log_source,
         join_handle,
-    )
+    }
 }
 
+#[cfg(test)]
 mod tests {
-    use crate::entrypoint::{entrypoint, Engine};
+    use crate::entrypoint::Engine;
+    use crate::explain::{Candidate, ProofTree, Unexplained};
     use crate::model::consts::constants::owl::{
-        inverseOf, Class, ObjectProperty, TransitiveProperty,
+        assertionProperty, cardinalityOne, cardinalityZero, complementOf, differentFrom,
+        disjointWith, distinctMembers, hasKey, hasValue, intersectionOf, inverseOf, maxCardinality,
+        maxQualifiedCardinality, members, onClass, onProperty, propertyChainAxiom,
+        propertyDisjointWith, sameAs, someValuesFrom, sourceIndividual, targetIndividual,
+        targetValue, unionOf, AllDIfferent, AllDisjointClasses, AllDisjointProperties,
+        AsymmetricProperty, Class, FunctionalProperty, InverseFunctionalProperty,
+        IrreflexiveProperty, Nothing, ObjectProperty, SymmetricProperty, Thing, TransitiveProperty,
+    };
+    use crate::model::consts::constants::rdfs::{
+        comment, domain, first, nil, r#type, range, rest, subClassOf, subPropertyOf, Alt,
+        Container, ContainerMembershipProperty, Datatype, Literal, Property, Resource,
     };
-    use crate::model::consts::constants::rdfs::{domain, r#type, range, subClassOf, subPropertyOf};
     use crate::model::consts::constants::MAX_CONST;
+    use crate::model::types::{RdfsEntailment, Triple};
+    use crate::query::Term::{Constant, Variable};
+    use crate::query::{ContinuousQuery, QueryError};
+    use crate::reasoner::{Reasoner, ReasonerBuilder};
+    use dire_parser::dictionary::Dictionary;
+    use dire_parser::literals::datatype_entailments;
+    use std::collections::HashMap;
+    use std::hash::Hash;
     use std::time::Duration;
     #[test]
     fn entrypoint_works() {
-        let single_threaded = timely::Config::process(1);
-        let (
-            tbox_input_sink,
-            abox_input_sink,
-            tbox_output_source,
-            abox_output_source,
-            done_source,
-            terminator_sink,
-            logs,
-            _joinhandle,
-        ) = entrypoint(single_threaded, 1, Engine::Dummy);
+        let mut reasoner = reasoner(Engine::Dummy).batch_size(1).build();
         // Filling the tbox
         let employee = MAX_CONST + 1;
         let faculty = MAX_CONST + 2;
@@ -110,58 +307,24 @@ mod tests {
         let employs = MAX_CONST + 8;
         let teacher_of = MAX_CONST + 9;
         let course = MAX_CONST + 10;
-        tbox_input_sink
-            .send(((employee, r#type, Class), 1))
-            .unwrap();
-        tbox_input_sink.send(((faculty, r#type, Class), 1)).unwrap();
-        tbox_input_sink
-            .send(((faculty, subClassOf, employee), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((full_professor, r#type, Class), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((full_professor, subClassOf, professor), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((professor, r#type, Class), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((professor, subClassOf, faculty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((head_of, r#type, ObjectProperty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((head_of, subPropertyOf, works_for), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((member_of, r#type, ObjectProperty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((works_for, r#type, ObjectProperty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((works_for, subPropertyOf, member_of), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((works_for, r#type, TransitiveProperty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((employs, r#type, ObjectProperty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((employs, inverseOf, works_for), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((teacher_of, r#type, ObjectProperty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((teacher_of, domain, faculty), 1))
-            .unwrap();
-        tbox_input_sink
-            .send(((teacher_of, range, course), 1))
-            .unwrap();
+        reasoner.insert_tbox((employee, r#type, Class));
+        reasoner.insert_tbox((faculty, r#type, Class));
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_tbox((full_professor, r#type, Class));
+        reasoner.insert_tbox((full_professor, subClassOf, professor));
+        reasoner.insert_tbox((professor, r#type, Class));
+        reasoner.insert_tbox((professor, subClassOf, faculty));
+        reasoner.insert_tbox((head_of, r#type, ObjectProperty));
+        reasoner.insert_tbox((head_of, subPropertyOf, works_for));
+        reasoner.insert_tbox((member_of, r#type, ObjectProperty));
+        reasoner.insert_tbox((works_for, r#type, ObjectProperty));
+        reasoner.insert_tbox((works_for, subPropertyOf, member_of));
+        reasoner.insert_tbox((works_for, r#type, TransitiveProperty));
+        reasoner.insert_tbox((employs, r#type, ObjectProperty));
+        reasoner.insert_tbox((employs, inverseOf, works_for));
+        reasoner.insert_tbox((teacher_of, r#type, ObjectProperty));
+        reasoner.insert_tbox((teacher_of, domain, faculty));
+        reasoner.insert_tbox((teacher_of, range, course));
         // Filling the abox
         let full_professor_7 = MAX_CONST + 11;
         let full_professor_8 = MAX_CONST + 12;
@@ -169,35 +332,30 @@ mod tests {
         let full_professor_10 = MAX_CONST + 14;
         let department_0 = MAX_CONST + 15;
         let course_10 = MAX_CONST + 16;
-        abox_input_sink
-            .send(((full_professor_7, head_of, department_0), 1))
-            .unwrap();
-        abox_input_sink
-            .send(((full_professor_7, r#type, full_professor), 1))
-            .unwrap();
-        abox_input_sink
-            .send(((full_professor_7, teacher_of, course_10), 1))
-            .unwrap();
-        abox_input_sink
-            .send(((full_professor_7, works_for, full_professor_8), 1))
-            .unwrap();
-        abox_input_sink
-            .send(((full_professor_8, works_for, full_professor_9), 1))
-            .unwrap();
-        abox_input_sink
-            .send(((full_professor_9, works_for, full_professor_10), 1))
-            .unwrap();
+        reasoner.insert_abox((full_professor_7, head_of, department_0));
+        reasoner.insert_abox((full_professor_7, r#type, full_professor));
+        reasoner.insert_abox((full_professor_7, teacher_of, course_10));
+        reasoner.insert_abox((full_professor_7, works_for, full_professor_8));
+        reasoner.insert_abox((full_professor_8, works_for, full_professor_9));
+        reasoner.insert_abox((full_professor_9, works_for, full_professor_10));
 
-        terminator_sink.send("STOP".to_string()).unwrap();
+        reasoner.commit();
+        reasoner.shutdown();
 
         let mut actual_tbox_diffs: Vec<(u32, u32, u32)> = vec![];
         let mut actual_abox_diffs: Vec<(u32, u32, u32)> = vec![];
 
-        while let Ok(diff) = tbox_output_source.recv_timeout(Duration::from_millis(50)) {
+        while let Ok(diff) = reasoner
+            .tbox_changes()
+            .recv_timeout(Duration::from_millis(50))
+        {
             actual_tbox_diffs.push(diff.0)
         }
 
-        while let Ok(diff) = abox_output_source.recv_timeout(Duration::from_millis(50)) {
+        while let Ok(diff) = reasoner
+            .abox_changes()
+            .recv_timeout(Duration::from_millis(50))
+        {
             actual_abox_diffs.push(diff.0)
         }
 
@@ -248,4 +406,1431 @@ mod tests {
         assert_eq!(expected_tbox_diffs, actual_tbox_diffs);
         assert_eq!(expected_abox_diffs, actual_abox_diffs)
     }
+
+    #[test]
+    fn entrypoint_reports_and_retracts_inconsistencies() {
+        let mut reasoner = reasoner(Engine::OWL2RL).build();
+        let faculty = MAX_CONST + 1;
+        let course = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        reasoner.insert_tbox((faculty, disjointWith, course));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        reasoner.insert_abox((full_professor_7, r#type, course));
+
+        let expected_inconsistency = (
+            "cax-dw".to_string(),
+            vec![
+                (faculty, disjointWith, course),
+                (full_professor_7, r#type, faculty),
+                (full_professor_7, r#type, course),
+            ],
+        );
+
+        // Everything the commit reports is sent by the time it is acknowledged.
+        reasoner.commit();
+        let reported: Vec<_> = reasoner
+            .inconsistencies()
+            .try_iter()
+            .map(|(inconsistency, _time, diff)| (inconsistency, diff))
+            .collect();
+        assert_eq!(vec![(expected_inconsistency.clone(), 1)], reported);
+
+        reasoner.retract_abox((full_professor_7, r#type, course));
+        reasoner.commit();
+        reasoner.shutdown();
+
+        let retracted: Vec<_> = reasoner
+            .inconsistencies()
+            .try_iter()
+            .map(|(inconsistency, _time, diff)| (inconsistency, diff))
+            .collect();
+        assert_eq!(vec![(expected_inconsistency, -1)], retracted);
+    }
+
+    #[test]
+    fn entrypoint_reports_and_retracts_provenance() {
+        let mut reasoner = reasoner(Engine::RDFS).provenance(true).build();
+        let professor = MAX_CONST + 1;
+        let faculty = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        reasoner.insert_tbox((professor, subClassOf, faculty));
+        reasoner.insert_abox((full_professor_7, r#type, professor));
+
+        let expected_provenance = (
+            (full_professor_7, r#type, faculty),
+            "rdfs9".to_string(),
+            vec![
+                (professor, subClassOf, faculty),
+                (full_professor_7, r#type, professor),
+            ],
+        );
+
+        reasoner.commit();
+        let mut reported = vec![];
+        while let Ok((provenance, _time, diff)) = reasoner
+            .provenance()
+            .recv_timeout(Duration::from_millis(50))
+        {
+            reported.push((provenance, diff))
+        }
+        assert_eq!(vec![(expected_provenance.clone(), 1)], reported);
+
+        reasoner.retract_abox((full_professor_7, r#type, professor));
+        reasoner.commit();
+        reasoner.shutdown();
+
+        let retracted: Vec<_> = reasoner
+            .provenance()
+            .try_iter()
+            .map(|(provenance, _time, diff)| (provenance, diff))
+            .collect();
+        assert_eq!(vec![(expected_provenance, -1)], retracted);
+    }
+
+    #[test]
+    fn entrypoint_explains_across_both_stages() {
+        let mut reasoner = reasoner(Engine::OWL2RL).explain(true).build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let teacher_of = MAX_CONST + 3;
+        let full_professor_7 = MAX_CONST + 4;
+        let course_10 = MAX_CONST + 5;
+        reasoner.insert_tbox((teacher_of, domain, faculty));
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, teacher_of, course_10));
+        reasoner.commit();
+
+        let given = |triple| ProofTree {
+            triple,
+            rule: None,
+            premises: vec![],
+        };
+        let derived = |triple, rule: &str, premises| ProofTree {
+            triple,
+            rule: Some(rule.to_string()),
+            premises,
+        };
+        // Both cax-sco over prp-dom and prp-dom over scm-dom1 are as shallow, and the former is
+        // the least of the two.
+        assert_eq!(
+            Ok(derived(
+                (full_professor_7, r#type, employee),
+                "cax-sco",
+                vec![
+                    given((faculty, subClassOf, employee)),
+                    derived(
+                        (full_professor_7, r#type, faculty),
+                        "prp-dom",
+                        vec![
+                            given((teacher_of, domain, faculty)),
+                            given((full_professor_7, teacher_of, course_10)),
+                        ],
+                    ),
+                ],
+            )),
+            reasoner
+                .explainer()
+                .explain((full_professor_7, r#type, employee))
+        );
+        assert_eq!(
+            Ok(derived(
+                (teacher_of, domain, employee),
+                "scm-dom1",
+                vec![
+                    given((teacher_of, domain, faculty)),
+                    given((faculty, subClassOf, employee)),
+                ],
+            )),
+            reasoner.explainer().explain((teacher_of, domain, employee))
+        );
+        assert_eq!(
+            Err(Unexplained::NotEntailed),
+            reasoner.explainer().explain((course_10, r#type, employee))
+        );
+
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_explains_the_full_rdfs_entailment() {
+        let mut reasoner = reasoner(Engine::RDFSFull(RdfsEntailment::default()))
+            .explain(true)
+            .build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        reasoner.commit();
+
+        let given = |triple| ProofTree {
+            triple,
+            rule: None,
+            premises: vec![],
+        };
+        assert_eq!(
+            Ok(ProofTree {
+                triple: (full_professor_7, r#type, employee),
+                rule: Some("rdfs9".to_string()),
+                premises: vec![
+                    given((faculty, subClassOf, employee)),
+                    given((full_professor_7, r#type, faculty)),
+                ],
+            }),
+            reasoner
+                .explainer()
+                .explain((full_professor_7, r#type, employee))
+        );
+        // Down to the axiomatic triples, which have no premises.
+        assert_eq!(
+            Ok(ProofTree {
+                triple: (subClassOf, r#type, Property),
+                rule: Some("rdf1".to_string()),
+                premises: vec![given((faculty, subClassOf, employee))],
+            }),
+            reasoner.explainer().explain((subClassOf, r#type, Property))
+        );
+        assert_eq!(
+            Ok(ProofTree {
+                triple: (r#type, r#type, Property),
+                rule: Some("axiomatic".to_string()),
+                premises: vec![],
+            }),
+            reasoner.explainer().explain((r#type, r#type, Property))
+        );
+        assert_eq!(
+            Err(Unexplained::NotEntailed),
+            reasoner
+                .explainer()
+                .explain((employee, r#type, full_professor_7))
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_tells_untracked_derivations_from_missing_ones() {
+        let mut reasoner = reasoner(Engine::OWL2RL).explain(true).build();
+        let works_for = MAX_CONST + 1;
+        let department_0 = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let full_professor_8 = MAX_CONST + 4;
+        reasoner.insert_abox((full_professor_7, works_for, department_0));
+        reasoner.insert_abox((full_professor_7, sameAs, full_professor_8));
+        reasoner.commit();
+
+        // The eq-rep rules have no candidates to walk back through.
+        assert_eq!(
+            Err(Unexplained::Untracked),
+            reasoner
+                .explainer()
+                .explain((full_professor_8, works_for, department_0))
+        );
+        assert_eq!(
+            Err(Unexplained::NotEntailed),
+            reasoner
+                .explainer()
+                .explain((department_0, works_for, full_professor_8))
+        );
+        reasoner.shutdown();
+
+        // Nor has the dummy engine, which derives nothing to begin with.
+        let mut reasoner = self::reasoner(Engine::Dummy).explain(true).build();
+        reasoner.insert_abox((full_professor_7, works_for, department_0));
+        reasoner.commit();
+        assert_eq!(
+            Ok(ProofTree {
+                triple: (full_professor_7, works_for, department_0),
+                rule: None,
+                premises: vec![],
+            }),
+            reasoner
+                .explainer()
+                .explain((full_professor_7, works_for, department_0))
+        );
+        assert_eq!(
+            Err(Unexplained::NotEntailed),
+            reasoner
+                .explainer()
+                .explain((full_professor_8, works_for, department_0))
+        );
+        assert!(reasoner
+            .explainer()
+            .why_not((full_professor_8, works_for, department_0))
+            .is_empty());
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_explains_missing_inferences() {
+        let mut reasoner = reasoner(Engine::OWL2RL).explain(true).build();
+        let professor = MAX_CONST + 1;
+        let faculty = MAX_CONST + 2;
+        let teacher_of = MAX_CONST + 3;
+        let advisor = MAX_CONST + 4;
+        let full_professor_7 = MAX_CONST + 5;
+        let full_professor_8 = MAX_CONST + 6;
+        reasoner.insert_tbox((professor, subClassOf, faculty));
+        reasoner.insert_tbox((teacher_of, domain, faculty));
+        reasoner.insert_abox((full_professor_7, advisor, full_professor_8));
+        reasoner.commit();
+
+        assert_eq!(
+            vec![
+                Candidate {
+                    rule: "cax-sco".to_string(),
+                    given: vec![(professor, subClassOf, faculty)],
+                    missing: vec![(
+                        Constant(full_professor_7),
+                        Constant(r#type),
+                        Constant(professor)
+                    )],
+                },
+                Candidate {
+                    rule: "prp-dom".to_string(),
+                    given: vec![(teacher_of, domain, faculty)],
+                    missing: vec![(
+                        Constant(full_professor_7),
+                        Constant(teacher_of),
+                        Variable(0)
+                    )],
+                },
+            ],
+            reasoner
+                .explainer()
+                .why_not((full_professor_7, r#type, faculty))
+        );
+
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_explains_missing_inferences_of_the_full_rdfs_entailment() {
+        let mut reasoner = reasoner(Engine::RDFSFull(RdfsEntailment {
+            property_typing: false,
+            ..Default::default()
+        }))
+        .explain(true)
+        .build();
+        let professor = MAX_CONST + 1;
+        let faculty = MAX_CONST + 2;
+        let teacher_of = MAX_CONST + 3;
+        let advisor = MAX_CONST + 4;
+        let full_professor_7 = MAX_CONST + 5;
+        let full_professor_8 = MAX_CONST + 6;
+        reasoner.insert_tbox((professor, subClassOf, faculty));
+        reasoner.insert_tbox((teacher_of, domain, faculty));
+        reasoner.insert_abox((full_professor_7, advisor, full_professor_8));
+        reasoner.commit();
+
+        assert_eq!(
+            vec![
+                Candidate {
+                    rule: "rdfs2".to_string(),
+                    given: vec![(teacher_of, domain, faculty)],
+                    missing: vec![(
+                        Constant(full_professor_7),
+                        Constant(teacher_of),
+                        Variable(0)
+                    )],
+                },
+                Candidate {
+                    rule: "rdfs9".to_string(),
+                    given: vec![(professor, subClassOf, faculty)],
+                    missing: vec![(
+                        Constant(full_professor_7),
+                        Constant(r#type),
+                        Constant(professor)
+                    )],
+                },
+            ],
+            reasoner
+                .explainer()
+                .why_not((full_professor_7, r#type, faculty))
+        );
+        // Without rdf1, nothing types the property that rdfs6 would need.
+        assert_eq!(
+            vec![Candidate {
+                rule: "rdfs6".to_string(),
+                given: vec![],
+                missing: vec![(Constant(advisor), Constant(r#type), Constant(Property))],
+            }],
+            reasoner
+                .explainer()
+                .why_not((advisor, subPropertyOf, advisor))
+        );
+
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_joins_the_premises_of_missing_inferences() {
+        let mut reasoner = reasoner(Engine::OWL2RL).explain(true).build();
+        let ancestor_of = MAX_CONST + 1;
+        let person_1 = MAX_CONST + 2;
+        let person_2 = MAX_CONST + 3;
+        let person_3 = MAX_CONST + 4;
+        let person_4 = MAX_CONST + 5;
+        reasoner.insert_tbox((ancestor_of, r#type, TransitiveProperty));
+        reasoner.insert_abox((person_1, ancestor_of, person_2));
+        reasoner.insert_abox((person_3, ancestor_of, person_4));
+        reasoner.commit();
+
+        // Both premises of prp-trp are matched on their own, but not along the same person.
+        assert_eq!(
+            vec![Candidate {
+                rule: "prp-trp".to_string(),
+                given: vec![(ancestor_of, r#type, TransitiveProperty)],
+                missing: vec![(
+                    Constant(person_2),
+                    Constant(ancestor_of),
+                    Constant(person_4)
+                )],
+            }],
+            reasoner
+                .explainer()
+                .why_not((person_1, ancestor_of, person_4))
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_answers_queries_at_any_epoch() {
+        let mut reasoner = reasoner(Engine::RDFS).index(true).history(16).build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let teacher_of = MAX_CONST + 3;
+        let full_professor_7 = MAX_CONST + 4;
+        let full_professor_8 = MAX_CONST + 5;
+        let course_10 = MAX_CONST + 6;
+        let course_11 = MAX_CONST + 7;
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        reasoner.insert_abox((full_professor_7, teacher_of, course_10));
+        reasoner.commit();
+        reasoner.insert_abox((full_professor_8, r#type, faculty));
+        reasoner.insert_abox((full_professor_8, teacher_of, course_11));
+        reasoner.commit();
+
+        let employees_teaching = [
+            (Variable(0), Constant(teacher_of), Variable(1)),
+            (Variable(0), Constant(r#type), Constant(employee)),
+        ];
+        let querier = reasoner.querier();
+        assert_eq!(
+            Ok(vec![
+                vec![full_professor_7, course_10],
+                vec![full_professor_8, course_11]
+            ]),
+            querier.query(&employees_teaching)
+        );
+        assert_eq!(
+            Ok(vec![vec![full_professor_7, course_10]]),
+            querier.query_at(&employees_teaching, 0)
+        );
+        assert_eq!(
+            Ok(vec![vec![employee]]),
+            querier.query(&[(Constant(faculty), Constant(subClassOf), Variable(0))])
+        );
+        assert_eq!(
+            Ok(Vec::<Vec<u32>>::new()),
+            querier.query(&[(Variable(0), Constant(r#type), Constant(course_10))])
+        );
+
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_refuses_queries_it_can_not_answer() {
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let employees = [(Variable(0), Constant(r#type), Constant(employee))];
+
+        let mut unindexed = reasoner(Engine::RDFS).build();
+        unindexed.insert_tbox((faculty, subClassOf, employee));
+        unindexed.commit();
+        assert_eq!(
+            Err(QueryError::NotIndexed),
+            unindexed.querier().query(&employees)
+        );
+        assert_eq!(
+            Some(QueryError::NotIndexed),
+            unindexed.querier().register(&employees).err()
+        );
+        unindexed.shutdown();
+
+        let mut reasoner = reasoner(Engine::RDFS).index(true).build();
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        assert_eq!(
+            Err(QueryError::NotCommitted),
+            reasoner.querier().query(&employees)
+        );
+        reasoner.commit();
+        // Variable 0 occurs nowhere, so it would be left unbound.
+        assert_eq!(
+            Err(QueryError::UnboundVariable(0)),
+            reasoner
+                .querier()
+                .query(&[(Variable(1), Constant(r#type), Constant(employee))])
+        );
+        assert_eq!(
+            Some(QueryError::UnboundVariable(0)),
+            reasoner
+                .querier()
+                .register(&[(Variable(1), Constant(r#type), Constant(employee))])
+                .err()
+        );
+        assert_eq!(
+            Ok(vec![vec![full_professor_7]]),
+            reasoner.querier().query(&employees)
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn entrypoint_maintains_continuous_query_answers() {
+        let mut reasoner = reasoner(Engine::RDFS).index(true).build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let full_professor_8 = MAX_CONST + 4;
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        reasoner.commit();
+
+        let employees = reasoner
+            .querier()
+            .register(&[(Variable(0), Constant(r#type), Constant(employee))])
+            .unwrap();
+        reasoner.commit();
+        let changes = |employees: &ContinuousQuery| {
+            let mut changes: Vec<(Vec<u32>, isize)> = employees
+                .answers()
+                .try_iter()
+                .map(|(answer, _time, diff)| (answer, diff))
+                .collect();
+            changes.sort();
+            changes
+        };
+        assert_eq!(vec![(vec![full_professor_7], 1)], changes(&employees));
+
+        reasoner.insert_abox((full_professor_8, r#type, faculty));
+        reasoner.retract_abox((full_professor_7, r#type, faculty));
+        reasoner.commit();
+        assert_eq!(
+            vec![(vec![full_professor_7], -1), (vec![full_professor_8], 1)],
+            changes(&employees)
+        );
+
+        reasoner.querier().unregister(employees);
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        reasoner.commit();
+        assert_eq!(
+            Ok(vec![vec![full_professor_7], vec![full_professor_8]]),
+            reasoner
+                .querier()
+                .query(&[(Variable(0), Constant(r#type), Constant(employee))])
+        );
+        reasoner.shutdown();
+    }
+
+    // What is left of a stream of changes, in order.
+    fn consolidated<T: Hash + Eq + Ord>(changes: impl Iterator<Item = (T, u64, isize)>) -> Vec<T> {
+        let mut counts: HashMap<T, isize> = HashMap::new();
+        changes.for_each(|(item, _time, diff)| *counts.entry(item).or_default() += diff);
+        let mut consolidated: Vec<T> = counts
+            .into_iter()
+            .filter(|(_item, count)| *count > 0)
+            .map(|(item, _count)| item)
+            .collect();
+        consolidated.sort();
+        consolidated
+    }
+
+    // A single-threaded reasoner running the given engine, to be set up further.
+    fn reasoner(logic: Engine) -> ReasonerBuilder {
+        Reasoner::builder()
+            .config(timely::Config::process(1))
+            .engine(logic)
+            .batch_size(16)
+    }
+
+    // Takes in the tbox and abox in one commit, and shuts down.
+    fn owl2rl_reasoner(logic: Engine, tbox: &[Triple], abox: &[Triple]) -> Reasoner {
+        let mut reasoner = reasoner(logic).build();
+        tbox.iter().for_each(|triple| reasoner.insert_tbox(*triple));
+        abox.iter().for_each(|triple| reasoner.insert_abox(*triple));
+        reasoner.shutdown();
+        reasoner
+    }
+
+    fn owl2rl_materialization(logic: Engine, tbox: &[Triple], abox: &[Triple]) -> Vec<Triple> {
+        consolidated(owl2rl_reasoner(logic, tbox, abox).abox_changes().try_iter())
+    }
+
+    // The rules that report an inconsistency.
+    fn reported_inconsistencies(tbox: &[Triple], abox: &[Triple]) -> Vec<String> {
+        let mut rules: Vec<String> = consolidated(
+            owl2rl_reasoner(Engine::OWL2RL, tbox, abox)
+                .inconsistencies()
+                .try_iter(),
+        )
+        .into_iter()
+        .map(|(rule, _witnesses)| rule)
+        .collect();
+        rules.dedup();
+        rules
+    }
+
+    // The triples of a list of the given elements, starting at the given node and going on with the
+    // ones that follow it.
+    fn list(node: u32, elements: &[u32]) -> Vec<Triple> {
+        let mut triples = vec![];
+        for (i, element) in elements.iter().enumerate() {
+            let rest_node = if i + 1 == elements.len() {
+                nil
+            } else {
+                node + i as u32 + 1
+            };
+            triples.push((node + i as u32, first, *element));
+            triples.push((node + i as u32, rest, rest_node));
+        }
+        triples
+    }
+
+    #[test]
+    fn owl2rl_reports_every_kind_of_inconsistency() {
+        let faculty = MAX_CONST + 1;
+        let course = MAX_CONST + 2;
+        let advisor = MAX_CONST + 3;
+        let teacher_of = MAX_CONST + 4;
+        let age = MAX_CONST + 5;
+        let full_professor_7 = MAX_CONST + 6;
+        let full_professor_8 = MAX_CONST + 7;
+        let graduate_student_1 = MAX_CONST + 8;
+        let axiom = MAX_CONST + 9;
+        let forty = MAX_CONST + 10;
+        let members_list = MAX_CONST + 11;
+        let disjoint_classes = list(members_list, &[faculty, course]);
+        let disjoint_properties = list(members_list, &[advisor, teacher_of]);
+        let different_individuals = list(members_list, &[full_professor_7, full_professor_8]);
+        let cases: Vec<(&str, Vec<Triple>, Vec<Triple>)> = vec![
+            (
+                "cax-dw",
+                vec![(faculty, disjointWith, course)],
+                vec![
+                    (full_professor_7, r#type, faculty),
+                    (full_professor_7, r#type, course),
+                ],
+            ),
+            (
+                "cax-adc",
+                [
+                    vec![
+                        (axiom, r#type, AllDisjointClasses),
+                        (axiom, members, members_list),
+                    ],
+                    disjoint_classes,
+                ]
+                .concat(),
+                vec![
+                    (full_professor_7, r#type, faculty),
+                    (full_professor_7, r#type, course),
+                ],
+            ),
+            (
+                "cls-com",
+                vec![(faculty, complementOf, course)],
+                vec![
+                    (full_professor_7, r#type, faculty),
+                    (full_professor_7, r#type, course),
+                ],
+            ),
+            (
+                "cls-nothing2",
+                vec![],
+                vec![(full_professor_7, r#type, Nothing)],
+            ),
+            (
+                "prp-irp",
+                vec![(advisor, r#type, IrreflexiveProperty)],
+                vec![(full_professor_7, advisor, full_professor_7)],
+            ),
+            (
+                "prp-asyp",
+                vec![(advisor, r#type, AsymmetricProperty)],
+                vec![
+                    (full_professor_7, advisor, full_professor_8),
+                    (full_professor_8, advisor, full_professor_7),
+                ],
+            ),
+            (
+                "prp-pdw",
+                vec![(advisor, propertyDisjointWith, teacher_of)],
+                vec![
+                    (graduate_student_1, advisor, full_professor_7),
+                    (graduate_student_1, teacher_of, full_professor_7),
+                ],
+            ),
+            (
+                "prp-adp",
+                [
+                    vec![
+                        (axiom, r#type, AllDisjointProperties),
+                        (axiom, members, members_list),
+                    ],
+                    disjoint_properties,
+                ]
+                .concat(),
+                vec![
+                    (graduate_student_1, advisor, full_professor_7),
+                    (graduate_student_1, teacher_of, full_professor_7),
+                ],
+            ),
+            (
+                "prp-npa1",
+                vec![],
+                vec![
+                    (axiom, sourceIndividual, graduate_student_1),
+                    (axiom, assertionProperty, advisor),
+                    (axiom, targetIndividual, full_professor_7),
+                    (graduate_student_1, advisor, full_professor_7),
+                ],
+            ),
+            (
+                "prp-npa2",
+                vec![],
+                vec![
+                    (axiom, sourceIndividual, full_professor_7),
+                    (axiom, assertionProperty, age),
+                    (axiom, targetValue, forty),
+                    (full_professor_7, age, forty),
+                ],
+            ),
+            (
+                "eq-diff1",
+                vec![],
+                vec![
+                    (full_professor_7, sameAs, full_professor_8),
+                    (full_professor_7, differentFrom, full_professor_8),
+                ],
+            ),
+            // sameAs derived in the abox stage is checked as well as the stated one.
+            (
+                "eq-diff1",
+                vec![(advisor, r#type, FunctionalProperty)],
+                vec![
+                    (graduate_student_1, advisor, full_professor_7),
+                    (graduate_student_1, advisor, full_professor_8),
+                    (full_professor_7, differentFrom, full_professor_8),
+                ],
+            ),
+            (
+                "eq-diff2",
+                vec![],
+                [
+                    vec![
+                        (axiom, r#type, AllDIfferent),
+                        (axiom, members, members_list),
+                        (full_professor_7, sameAs, full_professor_8),
+                    ],
+                    different_individuals.clone(),
+                ]
+                .concat(),
+            ),
+            (
+                "eq-diff3",
+                vec![],
+                [
+                    vec![
+                        (axiom, r#type, AllDIfferent),
+                        (axiom, distinctMembers, members_list),
+                        (full_professor_7, sameAs, full_professor_8),
+                    ],
+                    different_individuals,
+                ]
+                .concat(),
+            ),
+        ];
+        for (rule, tbox, abox) in cases {
+            assert_eq!(
+                vec![rule.to_string()],
+                reported_inconsistencies(&tbox, &abox)
+            );
+        }
+    }
+
+    #[test]
+    fn owl2rl_outputs_the_same_as_links_of_the_abox_stage() {
+        let advisor = MAX_CONST + 1;
+        let full_professor_7 = MAX_CONST + 2;
+        let full_professor_8 = MAX_CONST + 3;
+        let full_professor_9 = MAX_CONST + 4;
+        let graduate_student_1 = MAX_CONST + 5;
+        let department_0 = MAX_CONST + 6;
+        let university_0 = MAX_CONST + 7;
+        let tbox = vec![
+            (advisor, r#type, FunctionalProperty),
+            (department_0, sameAs, university_0),
+        ];
+        let abox = vec![
+            (graduate_student_1, advisor, full_professor_7),
+            (graduate_student_1, advisor, full_professor_8),
+            (full_professor_8, sameAs, full_professor_9),
+        ];
+
+        let reasoner = owl2rl_reasoner(Engine::OWL2RL, &tbox, &abox);
+        let changes: Vec<_> = reasoner.abox_changes().try_iter().collect();
+        let materialization = consolidated(changes.iter().cloned());
+        // Both the stated links and the derived ones are put out, each of them once.
+        for link in [
+            (full_professor_8, sameAs, full_professor_9),
+            (full_professor_9, sameAs, full_professor_8),
+            (full_professor_7, sameAs, full_professor_8),
+            (full_professor_7, sameAs, full_professor_9),
+        ] {
+            assert!(materialization.contains(&link));
+            assert_eq!(
+                1,
+                changes
+                    .iter()
+                    .filter(|(triple, _time, _diff)| *triple == link)
+                    .count()
+            );
+        }
+        // Those that the tbox states stay in the tbox.
+        assert!(!materialization.contains(&(department_0, sameAs, university_0)));
+    }
+
+    #[test]
+    fn owl2rl_representatives_agree_with_expansion() {
+        let head_of = MAX_CONST + 1;
+        let chair = MAX_CONST + 2;
+        let department_0 = MAX_CONST + 3;
+        let full_professor_7 = MAX_CONST + 4;
+        let full_professor_8 = MAX_CONST + 5;
+        let full_professor_9 = MAX_CONST + 6;
+        let tbox = vec![
+            (head_of, r#type, InverseFunctionalProperty),
+            (head_of, domain, chair),
+        ];
+        let abox = vec![
+            (full_professor_7, head_of, department_0),
+            (full_professor_8, head_of, department_0),
+            (full_professor_9, sameAs, full_professor_8),
+        ];
+
+        let expanded = owl2rl_materialization(Engine::OWL2RL, &tbox, &abox);
+        let represented = owl2rl_materialization(Engine::OWL2RLRepresentatives, &tbox, &abox);
+
+        assert!(expanded.contains(&(full_professor_9, r#type, chair)));
+        assert!(expanded.contains(&(full_professor_7, sameAs, full_professor_9)));
+        assert_eq!(expanded, represented);
+    }
+
+    #[test]
+    fn owl2rl_representatives_rewrite_the_individuals_of_the_tbox() {
+        let works_for = MAX_CONST + 1;
+        let restriction = MAX_CONST + 2;
+        let department_a = MAX_CONST + 3;
+        let department_b = MAX_CONST + 4;
+        let member = MAX_CONST + 5;
+        // The tbox names the department that does not stand for its clique.
+        let tbox = vec![
+            (restriction, hasValue, department_b),
+            (restriction, onProperty, works_for),
+        ];
+        let abox = vec![
+            (member, works_for, department_a),
+            (department_a, sameAs, department_b),
+        ];
+
+        let expanded = owl2rl_materialization(Engine::OWL2RL, &tbox, &abox);
+        let represented = owl2rl_materialization(Engine::OWL2RLRepresentatives, &tbox, &abox);
+
+        assert!(expanded.contains(&(member, r#type, restriction)));
+        assert_eq!(expanded, represented);
+    }
+
+    #[test]
+    fn owl2rl_tells_apart_the_keys_of_each_axiom() {
+        let student = MAX_CONST + 1;
+        let student_id = MAX_CONST + 2;
+        let name = MAX_CONST + 3;
+        let email_address = MAX_CONST + 4;
+        let key_list = MAX_CONST + 5;
+        let other_key_list = MAX_CONST + 7;
+        let undergraduate_student_1 = MAX_CONST + 10;
+        let undergraduate_student_2 = MAX_CONST + 11;
+        let undergraduate_student_3 = MAX_CONST + 12;
+        let undergraduate_student_4 = MAX_CONST + 13;
+        let undergraduate_student_5 = MAX_CONST + 14;
+        let undergraduate_student_6 = MAX_CONST + 15;
+        let id_1 = MAX_CONST + 16;
+        let name_1 = MAX_CONST + 17;
+        let name_2 = MAX_CONST + 18;
+        let email_address_1 = MAX_CONST + 19;
+        let mut reasoner = reasoner(Engine::OWL2RL).build();
+        // Students are told apart either by their id alone, or by their name and email address.
+        reasoner.insert_tbox((student, hasKey, key_list));
+        reasoner.insert_tbox((student, hasKey, other_key_list));
+        list(key_list, &[student_id])
+            .into_iter()
+            .chain(list(other_key_list, &[name, email_address]))
+            .for_each(|triple| reasoner.insert_tbox(triple));
+        for undergraduate_student in [
+            undergraduate_student_1,
+            undergraduate_student_2,
+            undergraduate_student_3,
+            undergraduate_student_4,
+            undergraduate_student_5,
+            undergraduate_student_6,
+        ] {
+            reasoner.insert_abox((undergraduate_student, r#type, student));
+        }
+        reasoner.insert_abox((undergraduate_student_1, student_id, id_1));
+        reasoner.insert_abox((undergraduate_student_2, student_id, id_1));
+        // Sharing the first key of the second axiom is not enough.
+        reasoner.insert_abox((undergraduate_student_3, name, name_1));
+        reasoner.insert_abox((undergraduate_student_4, name, name_1));
+        reasoner.insert_abox((undergraduate_student_5, name, name_2));
+        reasoner.insert_abox((undergraduate_student_6, name, name_2));
+        reasoner.insert_abox((undergraduate_student_5, email_address, email_address_1));
+        reasoner.insert_abox((undergraduate_student_6, email_address, email_address_1));
+        reasoner.commit();
+        let materialization = consolidated(reasoner.abox_changes().try_iter());
+        assert!(materialization.contains(&(
+            undergraduate_student_1,
+            sameAs,
+            undergraduate_student_2
+        )));
+        assert!(!materialization.contains(&(
+            undergraduate_student_3,
+            sameAs,
+            undergraduate_student_4
+        )));
+        assert!(materialization.contains(&(
+            undergraduate_student_5,
+            sameAs,
+            undergraduate_student_6
+        )));
+
+        // Taking back a key value takes back the agreement, and the link along with it.
+        reasoner.retract_abox((undergraduate_student_6, email_address, email_address_1));
+        let epoch = reasoner.commit();
+        assert!(reasoner.abox_changes().try_iter().any(|change| change
+            == (
+                (undergraduate_student_5, sameAs, undergraduate_student_6),
+                epoch,
+                -1
+            )));
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn owl2rl_applies_max_cardinalities() {
+        let restriction = MAX_CONST + 1;
+        let qualified_restriction = MAX_CONST + 2;
+        let unqualified_restriction = MAX_CONST + 3;
+        let head_of = MAX_CONST + 4;
+        let advisor = MAX_CONST + 5;
+        let professor = MAX_CONST + 6;
+        let chair_0 = MAX_CONST + 7;
+        let graduate_student_1 = MAX_CONST + 8;
+        let graduate_student_2 = MAX_CONST + 9;
+        let department_0 = MAX_CONST + 10;
+        let department_1 = MAX_CONST + 11;
+        let full_professor_7 = MAX_CONST + 12;
+        let full_professor_8 = MAX_CONST + 13;
+        let lecturer_9 = MAX_CONST + 14;
+        let tbox = vec![
+            (restriction, maxCardinality, cardinalityOne),
+            (restriction, onProperty, head_of),
+            (
+                qualified_restriction,
+                maxQualifiedCardinality,
+                cardinalityOne,
+            ),
+            (qualified_restriction, onProperty, advisor),
+            (qualified_restriction, onClass, professor),
+            (
+                unqualified_restriction,
+                maxQualifiedCardinality,
+                cardinalityOne,
+            ),
+            (unqualified_restriction, onProperty, advisor),
+            (unqualified_restriction, onClass, Thing),
+        ];
+        let abox = vec![
+            (chair_0, r#type, restriction),
+            (chair_0, head_of, department_0),
+            (chair_0, head_of, department_1),
+            (graduate_student_1, r#type, qualified_restriction),
+            (graduate_student_1, advisor, full_professor_7),
+            (graduate_student_1, advisor, full_professor_8),
+            (graduate_student_1, advisor, lecturer_9),
+            (full_professor_7, r#type, professor),
+            (full_professor_8, r#type, professor),
+            (graduate_student_2, r#type, unqualified_restriction),
+            (graduate_student_2, advisor, department_0),
+            (graduate_student_2, advisor, lecturer_9),
+        ];
+
+        let materialization = owl2rl_materialization(Engine::OWL2RL, &tbox, &abox);
+        // cls-maxc2
+        assert!(materialization.contains(&(department_0, sameAs, department_1)));
+        // cls-maxqc3, which leaves out the advisors that are not of the class
+        assert!(materialization.contains(&(full_professor_7, sameAs, full_professor_8)));
+        assert!(!materialization.contains(&(full_professor_7, sameAs, lecturer_9)));
+        // cls-maxqc4
+        assert!(materialization.contains(&(department_0, sameAs, lecturer_9)));
+
+        let tbox = vec![
+            (restriction, maxCardinality, cardinalityZero),
+            (restriction, onProperty, head_of),
+            (
+                qualified_restriction,
+                maxQualifiedCardinality,
+                cardinalityZero,
+            ),
+            (qualified_restriction, onProperty, advisor),
+            (qualified_restriction, onClass, professor),
+            (
+                unqualified_restriction,
+                maxQualifiedCardinality,
+                cardinalityZero,
+            ),
+            (unqualified_restriction, onProperty, advisor),
+            (unqualified_restriction, onClass, Thing),
+        ];
+        assert_eq!(
+            vec!["cls-maxc1".to_string()],
+            reported_inconsistencies(
+                &tbox,
+                &[
+                    (chair_0, r#type, restriction),
+                    (chair_0, head_of, department_0)
+                ]
+            )
+        );
+        assert_eq!(
+            vec!["cls-maxqc1".to_string()],
+            reported_inconsistencies(
+                &tbox,
+                &[
+                    (graduate_student_1, r#type, qualified_restriction),
+                    (graduate_student_1, advisor, full_professor_7),
+                    (graduate_student_1, advisor, lecturer_9),
+                    (full_professor_7, r#type, professor),
+                ]
+            )
+        );
+        assert_eq!(
+            vec!["cls-maxqc2".to_string()],
+            reported_inconsistencies(
+                &tbox,
+                &[
+                    (graduate_student_2, r#type, unqualified_restriction),
+                    (graduate_student_2, advisor, lecturer_9),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn owl2rl_reasons_with_the_datatype_entailments_of_a_dictionary() {
+        let xsd = |name: &str| format!("<http://www.w3.org/2001/XMLSchema#{}>", name);
+        let literal = |lexical: &str, name: &str| format!("\"{}\"^^{}", lexical, xsd(name));
+        let mut dictionary = Dictionary::new();
+        let age = dictionary.encode("<http://example.org/age>");
+        let full_professor_7 = dictionary.encode("<http://example.org/FullProfessor7>");
+        let full_professor_8 = dictionary.encode("<http://example.org/FullProfessor8>");
+        let forty = dictionary.encode(&literal("40", "integer"));
+        let forty_again = dictionary.encode(&literal("40.0", "decimal"));
+        let fifty = dictionary.encode(&literal("50", "integer"));
+        let huge = dictionary.encode(&literal(&"1".repeat(45), "positiveInteger"));
+        let ill_typed = dictionary.encode(&literal("forty", "integer"));
+        let entailments = datatype_entailments(&mut dictionary);
+        let integer = dictionary.encode(&xsd("integer"));
+        let unsigned_byte = dictionary.encode(&xsd("unsignedByte"));
+        let positive_integer = dictionary.encode(&xsd("positiveInteger"));
+
+        let mut reasoner = reasoner(Engine::OWL2RL).build();
+        for triple in &entailments.triples {
+            reasoner.insert_tbox(*triple);
+        }
+        reasoner.insert_abox((full_professor_7, age, forty));
+        reasoner.insert_abox((full_professor_8, age, huge));
+        for literal in &entailments.ill_typed {
+            reasoner.insert_ill_typed(*literal);
+        }
+        reasoner.shutdown();
+        let tbox_materialization = consolidated(reasoner.tbox_changes().try_iter());
+        let materialization = consolidated(reasoner.abox_changes().try_iter());
+        let inconsistencies = consolidated(reasoner.inconsistencies().try_iter());
+        // dt-type2, whatever the size of the value
+        assert!(tbox_materialization.contains(&(forty, r#type, unsigned_byte)));
+        assert!(tbox_materialization.contains(&(huge, r#type, positive_integer)));
+        // dt-eq, by which a value is found under each of its lexical forms
+        assert!(materialization.contains(&(full_professor_7, age, forty_again)));
+        assert!(!materialization.contains(&(full_professor_7, age, fifty)));
+        // dt-not-type, for the ill-typed literal alone, which is not typed with its datatype
+        assert!(!tbox_materialization.contains(&(ill_typed, r#type, integer)));
+        assert!(!materialization.contains(&(ill_typed, r#type, integer)));
+        assert_eq!(
+            vec![(
+                "dt-not-type".to_string(),
+                vec![(ill_typed, r#type, integer)]
+            )],
+            inconsistencies
+        );
+
+        // dt-diff
+        assert_eq!(
+            vec!["dt-diff".to_string()],
+            reported_inconsistencies(&entailments.triples, &[(forty, sameAs, fifty)])
+        );
+    }
+
+    #[test]
+    fn owl2rl_applies_intersections_and_unions() {
+        let teaching_professor = MAX_CONST + 1;
+        let professor = MAX_CONST + 2;
+        let teacher = MAX_CONST + 3;
+        let member = MAX_CONST + 4;
+        let student = MAX_CONST + 5;
+        let intersection_list = MAX_CONST + 6;
+        let union_list = MAX_CONST + 8;
+        let full_professor_7 = MAX_CONST + 10;
+        let graduate_student_1 = MAX_CONST + 11;
+        let mut reasoner = reasoner(Engine::OWL2RL).build();
+        reasoner.insert_tbox((teaching_professor, intersectionOf, intersection_list));
+        reasoner.insert_tbox((member, unionOf, union_list));
+        list(intersection_list, &[professor, teacher])
+            .into_iter()
+            .chain(list(union_list, &[professor, student]))
+            .for_each(|triple| reasoner.insert_tbox(triple));
+        reasoner.insert_abox((full_professor_7, r#type, teaching_professor));
+        reasoner.insert_abox((graduate_student_1, r#type, student));
+        reasoner.commit();
+        let materialization = consolidated(reasoner.abox_changes().try_iter());
+        // cls-int2
+        assert!(materialization.contains(&(full_professor_7, r#type, professor)));
+        assert!(materialization.contains(&(full_professor_7, r#type, teacher)));
+        // cls-uni, over either member of the union
+        assert!(materialization.contains(&(full_professor_7, r#type, member)));
+        assert!(materialization.contains(&(graduate_student_1, r#type, member)));
+
+        // Retracting the member of the union that an individual is of takes back the union.
+        reasoner.retract_abox((graduate_student_1, r#type, student));
+        let epoch = reasoner.commit();
+        let mut changes: Vec<_> = reasoner.abox_changes().try_iter().collect();
+        changes.sort();
+        assert_eq!(
+            vec![
+                ((graduate_student_1, r#type, member), epoch, -1),
+                ((graduate_student_1, r#type, student), epoch, -1),
+            ],
+            changes
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn owl2rl_tracks_the_provenance_of_the_rules_over_lists() {
+        let has_uncle = MAX_CONST + 1;
+        let has_parent = MAX_CONST + 2;
+        let has_brother = MAX_CONST + 3;
+        let chain_list = MAX_CONST + 4;
+        let person = MAX_CONST + 6;
+        let ssn = MAX_CONST + 7;
+        let key_list = MAX_CONST + 8;
+        let teaching_professor = MAX_CONST + 9;
+        let professor = MAX_CONST + 10;
+        let teacher = MAX_CONST + 11;
+        let intersection_list = MAX_CONST + 12;
+        let student_1 = MAX_CONST + 14;
+        let student_2 = MAX_CONST + 15;
+        let full_professor_7 = MAX_CONST + 16;
+        let ssn_1 = MAX_CONST + 17;
+        let mut reasoner = reasoner(Engine::OWL2RL).provenance(true).build();
+        reasoner.insert_tbox((has_uncle, propertyChainAxiom, chain_list));
+        reasoner.insert_tbox((person, hasKey, key_list));
+        reasoner.insert_tbox((teaching_professor, intersectionOf, intersection_list));
+        list(chain_list, &[has_parent, has_brother])
+            .into_iter()
+            .chain(list(key_list, &[ssn]))
+            .chain(list(intersection_list, &[professor, teacher]))
+            .for_each(|triple| reasoner.insert_tbox(triple));
+        reasoner.insert_abox((student_1, has_parent, full_professor_7));
+        reasoner.insert_abox((full_professor_7, has_brother, student_2));
+        reasoner.insert_abox((student_1, r#type, person));
+        reasoner.insert_abox((student_2, r#type, person));
+        reasoner.insert_abox((student_1, ssn, ssn_1));
+        reasoner.insert_abox((student_2, ssn, ssn_1));
+        reasoner.insert_abox((full_professor_7, r#type, professor));
+        reasoner.insert_abox((full_professor_7, r#type, teacher));
+        reasoner.shutdown();
+        let provenance = consolidated(reasoner.provenance().try_iter());
+        assert!(provenance.contains(&(
+            (student_1, has_uncle, student_2),
+            "prp-spo2".to_string(),
+            vec![
+                (has_uncle, propertyChainAxiom, chain_list),
+                (student_1, has_parent, full_professor_7),
+                (full_professor_7, has_brother, student_2),
+            ]
+        )));
+        assert!(provenance.contains(&(
+            (student_1, sameAs, student_2),
+            "prp-key".to_string(),
+            vec![
+                (person, hasKey, key_list),
+                (student_1, r#type, person),
+                (student_2, r#type, person),
+                (student_1, ssn, ssn_1),
+                (student_2, ssn, ssn_1),
+            ]
+        )));
+        assert!(provenance.contains(&(
+            (full_professor_7, r#type, teaching_professor),
+            "cls-int1".to_string(),
+            vec![
+                (teaching_professor, intersectionOf, intersection_list),
+                (full_professor_7, r#type, professor),
+                (full_professor_7, r#type, teacher),
+            ]
+        )));
+        // Such as eq-rep-s over (student_1 sameAs student_1), which eq-trans derives.
+        assert!(provenance
+            .iter()
+            .all(|(triple, _rule, premises)| !premises.contains(triple)));
+    }
+
+    #[test]
+    fn rdfs_full_tracks_provenance() {
+        let works_for = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let university_0 = MAX_CONST + 4;
+        let mut reasoner = reasoner(Engine::RDFSFull(RdfsEntailment::default()))
+            .provenance(true)
+            .build();
+        reasoner.insert_tbox((works_for, domain, employee));
+        reasoner.insert_abox((full_professor_7, works_for, university_0));
+        reasoner.shutdown();
+        let provenance = consolidated(reasoner.provenance().try_iter());
+        assert!(provenance.contains(&(
+            (full_professor_7, r#type, employee),
+            "rdfs2".to_string(),
+            vec![
+                (works_for, domain, employee),
+                (full_professor_7, works_for, university_0),
+            ]
+        )));
+    }
+
+    // The abox triples that an engine derives out of a graph that most of the rules of every engine
+    // apply to, without a single provenance record to tell how.
+    fn untracked_derivations(logic: Engine) -> Vec<Triple> {
+        let professor = MAX_CONST + 1;
+        let faculty = MAX_CONST + 2;
+        let employee = MAX_CONST + 3;
+        let teacher_of = MAX_CONST + 4;
+        let course = MAX_CONST + 5;
+        let head_of = MAX_CONST + 6;
+        let works_for = MAX_CONST + 7;
+        let advisor = MAX_CONST + 8;
+        let advisee = MAX_CONST + 9;
+        let part_of = MAX_CONST + 10;
+        let colleague = MAX_CONST + 11;
+        let chair = MAX_CONST + 12;
+        let department = MAX_CONST + 13;
+        let member_of_b = MAX_CONST + 14;
+        let full_professor_7 = MAX_CONST + 15;
+        let full_professor_8 = MAX_CONST + 16;
+        let professor_7 = MAX_CONST + 17;
+        let course_0 = MAX_CONST + 18;
+        let department_0 = MAX_CONST + 19;
+        let department_b = MAX_CONST + 20;
+        let university_0 = MAX_CONST + 21;
+        let country_0 = MAX_CONST + 22;
+        let student_1 = MAX_CONST + 23;
+        let tbox = vec![
+            (professor, subClassOf, faculty),
+            (faculty, subClassOf, employee),
+            (teacher_of, domain, faculty),
+            (teacher_of, range, course),
+            (head_of, subPropertyOf, works_for),
+            (advisor, inverseOf, advisee),
+            (part_of, r#type, TransitiveProperty),
+            (colleague, r#type, SymmetricProperty),
+            (chair, onProperty, head_of),
+            (chair, someValuesFrom, department),
+            (member_of_b, onProperty, works_for),
+            (member_of_b, hasValue, department_b),
+        ];
+        let abox = vec![
+            (full_professor_7, r#type, professor),
+            (full_professor_7, teacher_of, course_0),
+            (full_professor_7, head_of, department_0),
+            (department_0, r#type, department),
+            (department_0, part_of, university_0),
+            (university_0, part_of, country_0),
+            (full_professor_7, colleague, full_professor_8),
+            (full_professor_8, advisor, student_1),
+            (full_professor_8, works_for, department_b),
+            (full_professor_7, sameAs, professor_7),
+        ];
+
+        let mut reasoner = reasoner(logic).provenance(true).build();
+        tbox.iter().for_each(|triple| reasoner.insert_tbox(*triple));
+        abox.iter().for_each(|triple| reasoner.insert_abox(*triple));
+        reasoner.shutdown();
+        let tracked: Vec<Triple> = consolidated(reasoner.provenance().try_iter())
+            .into_iter()
+            .map(|(triple, _rule, _premises)| triple)
+            .collect();
+        consolidated(reasoner.abox_changes().try_iter())
+            .into_iter()
+            .filter(|triple| !abox.contains(triple) && !tracked.contains(triple))
+            .collect()
+    }
+
+    #[test]
+    fn rdfs_tracks_the_provenance_of_every_derived_triple() {
+        assert_eq!(Vec::<Triple>::new(), untracked_derivations(Engine::RDFS));
+    }
+
+    #[test]
+    fn rdfs_full_tracks_the_provenance_of_every_derived_triple() {
+        assert_eq!(
+            Vec::<Triple>::new(),
+            untracked_derivations(Engine::RDFSFull(RdfsEntailment::default()))
+        );
+    }
+
+    #[test]
+    fn rdfspp_tracks_the_provenance_of_every_derived_triple() {
+        assert_eq!(Vec::<Triple>::new(), untracked_derivations(Engine::RDFSpp));
+    }
+
+    #[test]
+    fn owl2rl_tracks_the_provenance_of_every_derived_triple() {
+        assert_eq!(Vec::<Triple>::new(), untracked_derivations(Engine::OWL2RL));
+        assert_eq!(
+            Vec::<Triple>::new(),
+            untracked_derivations(Engine::OWL2RLRepresentatives)
+        );
+    }
+
+    // Asserts that the full RDFS entailment derives the given triples, and that it no longer does
+    // once the part of it that derives them is switched off.
+    fn assert_rdfs_full_switches(
+        tbox: &[Triple],
+        abox: &[Triple],
+        entailed: &[Triple],
+        switch_off: fn(&mut RdfsEntailment),
+    ) {
+        let mut entailment = RdfsEntailment::default();
+        let materialization = owl2rl_materialization(Engine::RDFSFull(entailment), tbox, abox);
+        assert!(entailed
+            .iter()
+            .all(|triple| materialization.contains(triple)));
+        switch_off(&mut entailment);
+        let materialization = owl2rl_materialization(Engine::RDFSFull(entailment), tbox, abox);
+        assert!(entailed
+            .iter()
+            .all(|triple| !materialization.contains(triple)));
+    }
+
+    #[test]
+    fn rdfs_full_adds_the_axiomatic_triples() {
+        let advisor = MAX_CONST + 1;
+        let student_1 = MAX_CONST + 2;
+        let full_professor_8 = MAX_CONST + 3;
+        assert_rdfs_full_switches(
+            &[],
+            &[(student_1, advisor, full_professor_8)],
+            &[(Alt, subClassOf, Container), (comment, range, Literal)],
+            |entailment| entailment.axiomatic_triples = false,
+        );
+    }
+
+    #[test]
+    fn rdfs_full_types_properties() {
+        let advisor = MAX_CONST + 1;
+        let student_1 = MAX_CONST + 2;
+        let full_professor_8 = MAX_CONST + 3;
+        // rdf1
+        assert_rdfs_full_switches(
+            &[],
+            &[(student_1, advisor, full_professor_8)],
+            &[(advisor, r#type, Property)],
+            |entailment| entailment.property_typing = false,
+        );
+    }
+
+    #[test]
+    fn rdfs_full_types_resources() {
+        let advisor = MAX_CONST + 1;
+        let student_1 = MAX_CONST + 2;
+        let full_professor_8 = MAX_CONST + 3;
+        // rdfs4a and rdfs4b
+        assert_rdfs_full_switches(
+            &[],
+            &[(student_1, advisor, full_professor_8)],
+            &[
+                (student_1, r#type, Resource),
+                (full_professor_8, r#type, Resource),
+            ],
+            |entailment| entailment.resource_typing = false,
+        );
+    }
+
+    #[test]
+    fn rdfs_full_relates_properties_and_classes_to_themselves() {
+        let works_for = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let advisor = MAX_CONST + 3;
+        let student_1 = MAX_CONST + 4;
+        let full_professor_8 = MAX_CONST + 5;
+        // rdfs6, rdfs8 and rdfs10, with the class typed through the range of rdfs:domain
+        assert_rdfs_full_switches(
+            &[(works_for, domain, employee)],
+            &[(student_1, advisor, full_professor_8)],
+            &[
+                (advisor, subPropertyOf, advisor),
+                (employee, subClassOf, Resource),
+                (employee, subClassOf, employee),
+            ],
+            |entailment| entailment.reflexivity = false,
+        );
+    }
+
+    #[test]
+    fn rdfs_full_relates_container_membership_properties_to_member() {
+        use crate::model::consts::constants::rdfs::member;
+        let member_1 = MAX_CONST + 1;
+        // rdfs12
+        assert_rdfs_full_switches(
+            &[],
+            &[(member_1, r#type, ContainerMembershipProperty)],
+            &[(member_1, subPropertyOf, member)],
+            |entailment| entailment.container_membership = false,
+        );
+    }
+
+    #[test]
+    fn rdfs_full_relates_datatypes_to_literal() {
+        let integer = MAX_CONST + 1;
+        // rdfs13
+        assert_rdfs_full_switches(
+            &[],
+            &[(integer, r#type, Datatype)],
+            &[(integer, subClassOf, Literal)],
+            |entailment| entailment.datatypes = false,
+        );
+    }
 }
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+
+use differential_dataflow::operators::arrange::{ArrangeByKey, Arranged, TraceAgent};
+use differential_dataflow::operators::iterate::Variable;
+use differential_dataflow::operators::{Join, JoinCore, Reduce, Threshold};
+use differential_dataflow::trace::implementations::ord::OrdKeySpine;
+use timely::communication::allocator::Generic;
+use timely::dataflow::scopes::Child;
+use timely::worker::Worker;
+
+use crate::control::Controller;
+use crate::model::types::{
+    Command, ExplainInputSink, ExplainRequestCollection, ExplanationCollection,
+    ExplanationOutputSource, ProvenanceCollection, Triple, WhyNotCollection, WhyNotOutputSource,
+};
+use crate::query::{decode, instantiate, matches, Index, TriplePattern};
+
+// The input triples, as arranged by the running dataflow.
+pub(crate) type TripleArrangement<'a> =
+    Arranged<Child<'a, Worker<Generic>, u64>, TraceAgent<OrdKeySpine<Triple, u64, isize>>>;
+
+// A derivation of a triple, down to triples that were given as input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofTree {
+    pub triple: Triple,
+    // None for triples that were given as input
+    pub rule: Option<String>,
+    pub premises: Vec<ProofTree>,
+}
+
+impl ProofTree {
+    pub fn depth(&self) -> usize {
+        self.premises
+            .iter()
+            .map(|premise| premise.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+// Every triple that the proofs of the requested ones may go through, found by walking the one-step
+// derivations backwards from them until reaching input triples. The walk only goes through
+// entailed triples, matching the premises of the candidates for each of them against the index,
+// and taking the derivations of the tbox stage from the tbox rules. Meant for a dataflow of its own
+// over the traces of the running one, as every step of the walk takes an epoch.
+pub(crate) fn explanations<'a>(
+    requests: &ExplainRequestCollection<'a>,
+    tbox_inputs: &TripleArrangement<'a>,
+    abox_inputs: &TripleArrangement<'a>,
+    index: &Index<'a>,
+    tbox_derivations: &ProvenanceCollection<'a>,
+    candidates: impl Fn(&ExplainRequestCollection<'a>) -> WhyNotCollection<'a>,
+) -> ExplanationCollection<'a> {
+    let tbox_derivations_by_triple = tbox_derivations
+        .map(|(triple, rule, premises)| (triple, (rule, premises)))
+        .arrange_by_key();
+
+    let mut scope = requests.scope();
+    let walked = Variable::new(&mut scope, 1);
+
+    let relevant = requests.concat(&walked).distinct();
+    let relevant_by_triple = relevant.map(|(request, triple)| (triple, request));
+    let given = relevant_by_triple
+        .join_core(tbox_inputs, |&triple, &request, &()| {
+            Some((request, triple))
+        })
+        .concat(
+            &relevant_by_triple.join_core(abox_inputs, |&triple, &request, &()| {
+                Some((request, triple))
+            }),
+        )
+        .distinct();
+    // Input triples end the walk, even if they could have been derived as well.
+    let derived = index
+        .semijoin(&relevant)
+        .map(|derived| (derived, ()))
+        .antijoin(&given)
+        .map(|(derived, ())| derived);
+
+    let tbox_derivations = derived
+        .map(|(request, triple)| (triple, request))
+        .join_core(
+            &tbox_derivations_by_triple,
+            |&triple, &request, (rule, premises)| {
+                Some((request, triple, rule.clone(), premises.clone()))
+            },
+        );
+    let abox_derivations = matches(
+        &candidates(&derived).map(|(request, triple, rule, given, premises)| {
+            ((request, triple, rule, given, premises.clone()), premises)
+        }),
+        index,
+        false,
+    )
+    .flat_map(
+        |((request, triple, rule, given, premises), bindings, _missing)| {
+            premises
+                .iter()
+                .map(|premise| instantiate(*premise, &bindings))
+                .collect::<Option<Vec<Triple>>>()
+                .map(|premises| (request, triple, rule, [given, premises].concat()))
+        },
+    );
+    // Those with their own conclusion among their premises justify nothing.
+    let derivations = tbox_derivations
+        .concat(&abox_derivations)
+        .filter(|(_request, triple, _rule, premises)| !premises.contains(triple));
+
+    walked.set(
+        &derivations.flat_map(|(request, _triple, _rule, premises)| {
+            premises.into_iter().map(move |premise| (request, premise))
+        }),
+    );
+
+    given
+        .map(|(request, triple)| (request, triple, true, None))
+        .concat(&derived.map(|(request, triple)| (request, triple, false, None)))
+        .concat(&derivations.map(|(request, triple, rule, premises)| {
+            (request, triple, false, Some((rule, premises)))
+        }))
+}
+
+// A rule that could derive a triple which is not entailed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    pub rule: String,
+    // The tbox triples the rule is instantiated with
+    pub given: Vec<Triple>,
+    // The premises without a single matching triple in the materialization
+    pub missing: Vec<TriplePattern>,
+}
+
+// Narrows down the premises of every candidate to those that the materialization misses. They are
+// matched in order, joining on the variables they share, and one is missing once no triple matches
+// it along with those before. Of the ways they match, the one that misses the fewest is kept, with
+// the variables bound by the others filled in.
+pub(crate) fn missing_premises<'a>(
+    candidates: &WhyNotCollection<'a>,
+    index: &Index<'a>,
+) -> WhyNotCollection<'a> {
+    let candidates = candidates
+        .distinct()
+        .map(|candidate| (candidate.clone(), candidate.4));
+
+    matches(&candidates, index, true)
+        .map(|(candidate, _bindings, missing)| (candidate, missing))
+        .reduce(|_candidate, matched, output| {
+            // The matches are sorted, so the first of the fewest is the least of them
+            let fewest = matched
+                .iter()
+                .map(|(missing, _count)| *missing)
+                .min_by_key(|missing| missing.len())
+                .unwrap();
+            output.push((fewest.clone(), 1))
+        })
+        .map(|((request, triple, rule, given, _premises), missing)| {
+            (request, triple, rule, given, missing)
+        })
+}
+
+// A triple, with the rule and premises of one of its derivations, or None if it was given.
+pub type Explanation = (Triple, Option<(String, Vec<Triple>)>);
+
+// The shallowest derivation of the triple out of the explanations of a request, taking the least
+// of the derivations in case of a tie. None if the triple is neither given nor derived.
+pub fn proof_tree(triple: Triple, explanations: &[Explanation]) -> Option<ProofTree> {
+    let mut explanations = explanations.to_vec();
+    explanations.sort();
+    explanations.dedup();
+
+    // The depth of the shallowest derivation of each triple, given triples being of depth zero.
+    let mut depths: HashMap<Triple, usize> = HashMap::new();
+    explanations
+        .iter()
+        .filter(|(_triple, derivation)| derivation.is_none())
+        .for_each(|(triple, _derivation)| {
+            depths.insert(*triple, 0);
+        });
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (triple, derivation) in &explanations {
+            if let Some((_rule, premises)) = derivation {
+                let depth = premises
+                    .iter()
+                    .map(|premise| depths.get(premise).map(|depth| depth + 1))
+                    .try_fold(1, |deepest, depth| depth.map(|depth| depth.max(deepest)));
+                if let Some(depth) = depth {
+                    if depths.get(triple).is_none_or(|current| depth < *current) {
+                        depths.insert(*triple, depth);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // Every premise of a shallowest derivation is shallower than its conclusion, so this ends.
+    fn build(
+        triple: Triple,
+        explanations: &[Explanation],
+        depths: &HashMap<Triple, usize>,
+    ) -> Option<ProofTree> {
+        let depth = *depths.get(&triple)?;
+        if depth == 0 {
+            return Some(ProofTree {
+                triple,
+                rule: None,
+                premises: vec![],
+            });
+        }
+        let (rule, premises) = explanations
+            .iter()
+            .filter(|(conclusion, _derivation)| *conclusion == triple)
+            .filter_map(|(_conclusion, derivation)| derivation.as_ref())
+            .find(|(_rule, premises)| {
+                premises
+                    .iter()
+                    .all(|premise| depths.get(premise).is_some_and(|d| *d < depth))
+            })?;
+        Some(ProofTree {
+            triple,
+            rule: Some(rule.clone()),
+            premises: premises
+                .iter()
+                .map(|premise| build(*premise, explanations, depths))
+                .collect::<Option<Vec<ProofTree>>>()?,
+        })
+    }
+
+    build(triple, &explanations, &depths)
+}
+
+// Why a triple has no proof tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unexplained {
+    NotEntailed,
+    // Entailed, but only through rules that the explainer has no candidates for, such as eq-rep-s
+    Untracked,
+}
+
+// An explanation without the request it answers.
+type ExplainedTriple = (Triple, bool, Option<(String, Vec<Triple>)>);
+
+// Sends explain requests to a running engine, and puts together the answers.
+pub struct Explainer {
+    explain_input_sink: ExplainInputSink,
+    explanation_output_source: ExplanationOutputSource,
+    why_not_output_source: WhyNotOutputSource,
+    controller: Controller,
+    next_request: usize,
+}
+
+impl Explainer {
+    pub(crate) fn new(
+        explain_input_sink: ExplainInputSink,
+        explanation_output_source: ExplanationOutputSource,
+        why_not_output_source: WhyNotOutputSource,
+        controller: Controller,
+    ) -> Self {
+        Self {
+            explain_input_sink,
+            explanation_output_source,
+            why_not_output_source,
+            controller,
+            next_request: 0,
+        }
+    }
+
+    // The shallowest derivation of the triple from the input triples, as of the last round, or why
+    // there is none.
+    pub fn explain(&mut self, triple: Triple) -> Result<ProofTree, Unexplained> {
+        let (explanations, _candidates) = self.request(triple);
+        let entailed = explanations
+            .iter()
+            .any(|(explained, _given, derivation)| *explained == triple && derivation.is_none());
+        let explanations: Vec<Explanation> = explanations
+            .into_iter()
+            .filter(|(_triple, given, derivation)| *given || derivation.is_some())
+            .map(|(triple, _given, derivation)| (triple, derivation))
+            .collect();
+        match proof_tree(triple, &explanations) {
+            Some(proof_tree) => Ok(proof_tree),
+            None if entailed => Err(Unexplained::Untracked),
+            None => Err(Unexplained::NotEntailed),
+        }
+    }
+
+    // The rules that could derive the triple, with the premises each of them misses, as of the last
+    // round. Meant for triples that are not entailed.
+    pub fn why_not(&mut self, triple: Triple) -> Vec<Candidate> {
+        let (_explanations, candidates) = self.request(triple);
+        candidates
+    }
+
+    // Both kinds of answers are worked out for every request, so that one round does for either.
+    fn request(&mut self, triple: Triple) -> (Vec<ExplainedTriple>, Vec<Candidate>) {
+        let request = self.next_request;
+        self.next_request += 1;
+        self.explain_input_sink.send((request, triple)).unwrap();
+        self.controller.broadcast(Command::Query);
+
+        let explanations = consolidate(
+            self.explanation_output_source
+                .try_iter()
+                .filter(|((answered, _triple, _given, _derivation), _time, _diff)| {
+                    *answered == request
+                })
+                .map(|((_answered, triple, given, derivation), _time, diff)| {
+                    ((triple, given, derivation), diff)
+                }),
+        );
+        let mut candidates: Vec<Candidate> = consolidate(
+            self.why_not_output_source
+                .try_iter()
+                .filter(
+                    |((answered, _triple, _rule, _given, _missing), _time, _diff)| {
+                        *answered == request
+                    },
+                )
+                .map(
+                    |((_answered, _triple, rule, given, missing), _time, diff)| {
+                        ((rule, given, missing), diff)
+                    },
+                ),
+        )
+        .into_iter()
+        .map(|(rule, given, missing)| Candidate {
+            rule,
+            given,
+            missing: missing
+                .into_iter()
+                .map(|(s, p, o)| (decode(s), decode(p), decode(o)))
+                .collect(),
+        })
+        .collect();
+        candidates.sort_by(|one, other| (&one.rule, &one.given).cmp(&(&other.rule, &other.given)));
+        (explanations, candidates)
+    }
+}
+
+fn consolidate<T: std::hash::Hash + Eq>(diffs: impl Iterator<Item = (T, isize)>) -> Vec<T> {
+    let mut multiplicities: HashMap<T, isize> = HashMap::new();
+    for (item, diff) in diffs {
+        *multiplicities.entry(item).or_insert(0) += diff;
+    }
+    multiplicities
+        .into_iter()
+        .filter(|(_item, multiplicity)| *multiplicity > 0)
+        .map(|(item, _multiplicity)| item)
+        .collect()
+}
@@ -1,4 +1,8 @@
+pub mod control;
 pub mod entrypoint;
+pub mod explain;
 mod materialization;
 pub mod model;
+pub mod query;
 mod reason;
+pub mod reasoner;
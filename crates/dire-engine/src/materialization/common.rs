@@ -7,9 +7,32 @@ use timely::order::Product;
 
 use crate::model::consts::constants::rdfs::{subClassOf, subPropertyOf};
 use crate::model::types::{
-    KeyedTripleCollection, ListCollection, TripleCollection, TupleCollection,
+    ExplainRequestCollection, InconsistencyCollection, KeyedTripleCollection, ListCollection,
+    Provenance, ProvenanceCollection, Triple, TripleCollection, TupleCollection, WhyNotCollection,
 };
 
+// The derivation of a triple by a rule, from its premises.
+pub fn derive(triple: Triple, rule: &'static str, premises: &[Triple]) -> Provenance {
+    (triple, rule.to_string(), premises.to_vec())
+}
+
+// The same as derive, with the premises gathered along a list, such as down the property chain of
+// prp-spo2, following the given ones.
+pub fn derive_chained(
+    triple: Triple,
+    rule: &'static str,
+    premises: &[Triple],
+    chained: &[Triple],
+) -> Provenance {
+    (triple, rule.to_string(), [premises, chained].concat())
+}
+
+// Leaves out the derivations that have their own conclusion among their premises, such as eq-rep-s
+// over (s sameAs s), as they justify nothing.
+pub fn justified<'a>(derivations: &ProvenanceCollection<'a>) -> ProvenanceCollection<'a> {
+    derivations.filter(|(triple, _rule, premises)| !premises.contains(triple))
+}
+
 pub fn dummy_first_stage_materialization<'a>(
     collection: &TripleCollection<'a>,
 ) -> (TripleCollection<'a>, ListCollection<'a>) {
@@ -26,6 +49,41 @@ pub fn dummy_second_stage_materialization<'a>(
     collection_two.clone()
 }
 
+pub fn dummy_consistency_check<'a>(
+    _collection_one: &TripleCollection<'a>,
+    _list_collection_one: &ListCollection<'a>,
+    collection_two: &TripleCollection<'a>,
+    _ill_typed: &TupleCollection<'a>,
+) -> InconsistencyCollection<'a> {
+    let mut scope = collection_two.scope();
+    scope.new_collection_from(vec![]).1
+}
+
+pub fn dummy_provenance_tracking<'a>(
+    _collection_one: &TripleCollection<'a>,
+    _list_collection_one: &ListCollection<'a>,
+    collection_two: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    let mut scope = collection_two.scope();
+    scope.new_collection_from(vec![]).1
+}
+
+pub fn dummy_tbox_provenance_tracking<'a>(
+    collection: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    let mut scope = collection.scope();
+    scope.new_collection_from(vec![]).1
+}
+
+pub fn dummy_why_not_candidates<'a>(
+    _collection_one: &TripleCollection<'a>,
+    _list_collection_one: &ListCollection<'a>,
+    requests: &ExplainRequestCollection<'a>,
+) -> WhyNotCollection<'a> {
+    let mut scope = requests.scope();
+    scope.new_collection_from(vec![]).1
+}
+
 pub fn tbox_spo_sco_materialization<'a>(
     tbox: &TripleCollection<'a>,
 ) -> (TripleCollection<'a>, ListCollection<'a>) {
@@ -86,24 +144,23 @@ pub fn tbox_spo_sco_materialization<'a>(
     (tbox, outer.new_collection_from(vec![(0, vec![0])]).1)
 }
 
-pub fn abox_sco_type_materialization<'a>(
-    tbox_sco_assertions: &KeyedTripleCollection<'a>,
-    abox_class_assertions: &KeyedTripleCollection<'a>,
-) -> KeyedTripleCollection<'a> {
-    let mut outer = tbox_sco_assertions.scope();
-    outer.region_named("CAX-SCO", |inn| {
-        let sco_assertions = tbox_sco_assertions.enter(inn);
-        let class_assertions = abox_class_assertions.enter(inn);
-
-        let class_assertions_arranged = class_assertions.arrange_by_key();
-
-        sco_assertions
-            .join_core(
-                &class_assertions_arranged,
-                |_key, &(_sco, y), &(z, type_)| Some((y, (z, type_))),
-            )
-            .leave()
-    })
+// Every single-step derivation of a triple of the transitive closure of subClassOf and
+// subPropertyOf, over the closed tbox.
+pub fn tbox_spo_sco_provenance<'a>(tbox: &TripleCollection<'a>) -> ProvenanceCollection<'a> {
+    let transitive_assertions = tbox.filter(|(_s, p, _o)| *p == subClassOf || *p == subPropertyOf);
+    let transitive_assertions_by_sp = transitive_assertions
+        .map(|(s, p, o)| ((s, p), o))
+        .arrange_by_key();
+
+    // rdfs5 and rdfs11
+    let derivations = transitive_assertions
+        .map(|(x, p, y)| ((y, p), x))
+        .join_core(&transitive_assertions_by_sp, |&(y, p), &x, &z| {
+            let rule = if p == subClassOf { "rdfs11" } else { "rdfs5" };
+            Some(derive((x, p, z), rule, &[(x, p, y), (y, p, z)]))
+        });
+
+    justified(&derivations)
 }
 
 pub fn abox_domain_and_range_type_materialization<'a>(
@@ -1,22 +1,36 @@
 use differential_dataflow::input::Input;
-use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::operators::arrange::{ArrangeByKey, Arranged, TraceAgent};
 
+use differential_dataflow::lattice::Lattice;
 use differential_dataflow::operators::iterate::Variable;
-use differential_dataflow::operators::{iterate, Consolidate, JoinCore, Threshold};
+use differential_dataflow::operators::{
+    iterate, Consolidate, Count, Join, JoinCore, Reduce, Threshold,
+};
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+use differential_dataflow::Collection;
 
-use timely::dataflow::Scope;
+use timely::dataflow::{Scope, ScopeParent};
 use timely::order::Product;
 
+use crate::materialization::common::{derive, derive_chained, justified};
 use crate::model::consts::constants::owl::{
-    allValuesFrom, equivalentClass, equivalentProperty, hasValue, intersectionOf, inverseOf,
-    onProperty, oneOf, propertyChainAxiom, sameAs, someValuesFrom, unionOf, Class,
-    FunctionalProperty, InverseFunctionalProperty, Nothing, ObjectProperty, SymmetricProperty,
+    allValuesFrom, assertionProperty, cardinalityOne, cardinalityZero, complementOf, differentFrom,
+    disjointWith, distinctMembers, equivalentClass, equivalentProperty, hasKey, hasValue,
+    intersectionOf, inverseOf, maxCardinality, maxQualifiedCardinality, members, onClass,
+    onProperty, oneOf, propertyChainAxiom, propertyDisjointWith, sameAs, someValuesFrom,
+    sourceIndividual, targetIndividual, targetValue, unionOf, AllDIfferent, AllDisjointClasses,
+    AllDisjointProperties, AsymmetricProperty, Class, FunctionalProperty,
+    InverseFunctionalProperty, IrreflexiveProperty, Nothing, ObjectProperty, SymmetricProperty,
     Thing, TransitiveProperty,
 };
 use crate::model::consts::constants::rdfs::{
-    domain, first, nil, r#type, range, rest, subClassOf, subPropertyOf,
+    domain, first, nil, r#type, range, rest, subClassOf, subPropertyOf, Datatype, Literal,
+};
+use crate::model::types::{
+    ExplainRequestCollection, InconsistencyCollection, KeyedTripleCollection, ListCollection,
+    ProvenanceCollection, Triple, TripleCollection, TupleCollection, WhyNotCollection,
 };
-use crate::model::types::{ListCollection, Triple, TripleCollection};
+use crate::query::{constant, variable};
 
 pub fn expand_lists<'a>(tbox: &TripleCollection<'a>) -> ListCollection<'a> {
     // "First" indicates the content of the rule
@@ -73,7 +87,7 @@ pub fn expand_lists<'a>(tbox: &TripleCollection<'a>) -> ListCollection<'a> {
                 },
             );
 
-            lists_var.set(&core_lists.enter(&inner).concat(&searching_for_rest));
+            lists_var.set(&core_lists.enter(inner).concat(&searching_for_rest));
 
             lists_new.leave()
         })
@@ -90,7 +104,7 @@ pub fn expand_lists<'a>(tbox: &TripleCollection<'a>) -> ListCollection<'a> {
 pub fn owl2rl_tbox<'a>(tbox: &TripleCollection<'a>) -> (TripleCollection<'a>, ListCollection<'a>) {
     let mut outer = tbox.scope();
 
-    let lists = expand_lists(&tbox);
+    let lists = expand_lists(tbox);
 
     let iof_assertions = tbox
         .filter(|(_s, p, _o)| *p == intersectionOf)
@@ -371,7 +385,7 @@ pub fn owl2rl_tbox<'a>(tbox: &TripleCollection<'a>) -> (TripleCollection<'a>, Li
                 scm_dom2, scm_rng1, scm_rng2, scm_hv, scm_svf1, scm_svf2, scm_avf1, scm_avf2,
             ]);
 
-            tbox_var.set(&tbox.enter(&inner).concat(&scm));
+            tbox_var.set(&tbox.enter(inner).concat(&scm));
 
             tbox_new.leave()
         })
@@ -380,10 +394,158 @@ pub fn owl2rl_tbox<'a>(tbox: &TripleCollection<'a>) -> (TripleCollection<'a>, Li
     (materialization, lists)
 }
 
+// How the ABox stage handles sameAs.
+#[derive(Clone, Copy)]
+enum Equality {
+    // eq-rep-s, eq-rep-p and eq-rep-o copy every triple of an individual onto each of its peers.
+    Expansion,
+    // Triples are rewritten to a representative per sameAs clique.
+    Representatives,
+}
+
+// The representative of every term that is sameAs some other one, which is the smallest id of its
+// clique, found by propagating the smallest label along the edges.
+fn representatives<G: Scope>(edges: &Collection<G, (u32, u32)>) -> Collection<G, (u32, u32)>
+where
+    G::Timestamp: Lattice + Ord,
+{
+    let edges = edges
+        .concat(&edges.map(|(x, y)| (y, x)))
+        .filter(|(x, y)| *x != *y)
+        .distinct();
+    let nodes = edges.map(|(x, _y)| (x, x)).distinct();
+
+    let mut outer = edges.scope();
+    outer.iterative::<usize, _, _>(|inner| {
+        let labels_var =
+            Variable::new_from(nodes.enter(inner), Product::new(Default::default(), 1));
+
+        let labels_new = labels_var
+            .join_core(&edges.enter(inner).arrange_by_key(), |&_x, &label, &y| {
+                Some((y, label))
+            })
+            .concat(&nodes.enter(inner))
+            .reduce(|_x, labels, output| output.push((*labels[0].0, 1)));
+
+        labels_var.set(&labels_new);
+
+        labels_new.leave()
+    })
+}
+
+// Replaces every term of the triples with its representative, if it has one.
+fn rewrite<G: Scope>(
+    triples: &Collection<G, Triple>,
+    representatives: &Collection<G, (u32, u32)>,
+) -> Collection<G, Triple>
+where
+    G::Timestamp: Lattice + Ord,
+{
+    replace_terms(triples, representatives)
+}
+
+// Replaces the second term of each pair with its representative, if it has one.
+fn rewrite_values<G: Scope>(
+    pairs: &Collection<G, (u32, u32)>,
+    representatives: &Collection<G, (u32, u32)>,
+) -> Collection<G, (u32, u32)>
+where
+    G::Timestamp: Lattice + Ord,
+{
+    let by_value = pairs.map(|(x, y)| (y, x));
+    by_value
+        .join_core(&representatives.arrange_by_key(), |_y, &x, &r| Some((x, r)))
+        .concat(
+            &by_value
+                .antijoin(&representatives.map(|(y, _r)| y).distinct())
+                .map(|(y, x)| (x, y)),
+        )
+}
+
+// The other way around, every triple over representatives for each of the terms they stand for.
+fn expand<G: Scope>(
+    triples: &Collection<G, Triple>,
+    representatives: &Collection<G, (u32, u32)>,
+) -> Collection<G, Triple>
+where
+    G::Timestamp: Lattice + Ord,
+{
+    replace_terms(triples, &representatives.map(|(x, r)| (r, x)))
+}
+
+// Replaces every term of the triples with each of those it is paired with, keeping the terms that
+// are not paired with any as they are.
+fn replace_terms<G: Scope>(
+    triples: &Collection<G, Triple>,
+    replacements: &Collection<G, (u32, u32)>,
+) -> Collection<G, Triple>
+where
+    G::Timestamp: Lattice + Ord,
+{
+    let replacements_arr = replacements.arrange_by_key();
+    let replaced = replacements.map(|(term, _replacement)| term).distinct();
+
+    let by_s = replace(
+        &triples.map(|(s, p, o)| (s, (p, o))),
+        &replacements_arr,
+        &replaced,
+    );
+    let by_p = replace(
+        &by_s.map(|(s, (p, o))| (p, (s, o))),
+        &replacements_arr,
+        &replaced,
+    );
+    let by_o = replace(
+        &by_p.map(|(p, (s, o))| (o, (s, p))),
+        &replacements_arr,
+        &replaced,
+    );
+    by_o.map(|(o, (s, p))| (s, p, o))
+}
+
+// Each replaced term with its replacement, arranged by the replaced term.
+type ReplacementArrangement<G> =
+    Arranged<G, TraceAgent<OrdValSpine<u32, u32, <G as ScopeParent>::Timestamp, isize>>>;
+
+// Replaces the key of each of the keyed triples, if it has any replacement.
+fn replace<G: Scope>(
+    keyed: &Collection<G, (u32, (u32, u32))>,
+    replacements_arr: &ReplacementArrangement<G>,
+    replaced: &Collection<G, u32>,
+) -> Collection<G, (u32, (u32, u32))>
+where
+    G::Timestamp: Lattice + Ord,
+{
+    keyed
+        .join_core(replacements_arr, |_term, &terms, &replacement| {
+            Some((replacement, terms))
+        })
+        .concat(&keyed.antijoin(replaced))
+}
+
 pub fn owl2rl_abox<'a>(
     tbox: &TripleCollection<'a>,
     lists: &ListCollection<'a>,
     abox: &TripleCollection<'a>,
+) -> TripleCollection<'a> {
+    owl2rl_abox_with(tbox, lists, abox, Equality::Expansion)
+}
+
+// Rather than copying the triples of every individual onto each of its sameAs peers, keeps a
+// single representative per sameAs clique, and only expands the triples over them on the way out.
+pub fn owl2rl_abox_representatives<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+) -> TripleCollection<'a> {
+    owl2rl_abox_with(tbox, lists, abox, Equality::Representatives)
+}
+
+fn owl2rl_abox_with<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+    equality: Equality,
 ) -> TripleCollection<'a> {
     let mut outer = abox.scope();
 
@@ -396,10 +558,10 @@ pub fn owl2rl_abox<'a>(
     });
 
     let lists_unrolled_size = lists
-        .map(|(x, list)| (((list.len() - 1) as u32, x as u32), x))
+        .map(|(x, list)| (((list.len() - 1) as u32, x), x))
         .arrange_by_key();
 
-    let lists_unrolled_by_i_c_i = lists_unrolled.map(|(x, i, c_i)| ((i as u32, c_i), x));
+    let lists_unrolled_by_i_c_i = lists_unrolled.map(|(x, i, c_i)| ((i, c_i), x));
 
     let lists_by_last_i_x = lists
         .map(|(head, list)| (((list.len() - 1) as u32, head), head))
@@ -413,6 +575,8 @@ pub fn owl2rl_abox<'a>(
         .filter(|(_s, p, _o)| *p == equivalentClass)
         .map(|(s, _p, o)| (s, o));
 
+    let tbox_sas_assertions = tbox.filter(|(_s, p, _o)| *p == sameAs).distinct();
+
     let fp_assertions = tbox
         .filter(|(_s, _p, o)| *o == FunctionalProperty)
         .map(|(s, _p, _o)| (s, s));
@@ -430,12 +594,12 @@ pub fn owl2rl_abox<'a>(
         .map(|(s, _p, o)| (s, o));
 
     let symp_assertions = tbox
-        .filter(|(_s, _p, o)| *o == SymmetricProperty)
-        .map(|(_s, p, _o)| (p, p));
+        .filter(|(_s, p, o)| *p == r#type && *o == SymmetricProperty)
+        .map(|(s, _p, _o)| (s, s));
 
     let trans_assertions = tbox
-        .filter(|(_s, _p, o)| *o == TransitiveProperty)
-        .map(|(_s, p, _o)| (p, p));
+        .filter(|(_s, p, o)| *p == r#type && *o == TransitiveProperty)
+        .map(|(s, _p, _o)| (s, s));
 
     let spo_assertions = tbox
         .filter(|(_s, p, _o)| *p == subPropertyOf)
@@ -475,12 +639,64 @@ pub fn owl2rl_abox<'a>(
         .filter(|(_s, p, _o)| *p == intersectionOf)
         .map(|(s, _p, o)| (s, o));
     let iof_assertions_by_o = iof_assertions.map(|(s, o)| (o, s)).arrange_by_key();
+    let iof_assertions_arr = iof_assertions.arrange_by_key();
+
+    let uof_assertions_by_o = tbox
+        .filter(|(_s, p, _o)| *p == unionOf)
+        .map(|(s, _p, o)| (o, s))
+        .arrange_by_key();
 
     let pca_assertions = tbox
         .filter(|(_s, p, _o)| *p == propertyChainAxiom)
         .map(|(s, _p, o)| (s, o));
     let pca_assertions_by_o = pca_assertions.map(|(s, o)| (o, s)).arrange_by_key();
 
+    let hk_assertions_by_o = tbox
+        .filter(|(_s, p, _o)| *p == hasKey)
+        .map(|(s, _p, o)| (o, s));
+
+    // Each hasKey axiom is told apart by its list, so that the keys of a class with more than one
+    // of them are not mixed up.
+    let hk_keys_by_c = hk_assertions_by_o
+        .join_core(&lists_arr, |&list, &c, keys| {
+            keys.iter()
+                .enumerate()
+                .map(|(i, p_i)| (c, (list, i as u32, *p_i)))
+                .collect::<Vec<_>>()
+        })
+        .arrange_by_key();
+
+    let hk_sizes = hk_assertions_by_o
+        .join_core(&lists_arr, |&list, &c, keys| {
+            Some(((c, list, keys.len() as isize), ()))
+        })
+        .arrange_by_key();
+
+    let oc_assertions = tbox
+        .filter(|(_s, p, _o)| *p == onClass)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+
+    let maxc_one_assertions = tbox
+        .filter(|(_s, p, o)| *p == maxCardinality && *o == cardinalityOne)
+        .map(|(s, _p, _o)| (s, ()))
+        .join_core(&op_assertions, |&x, &(), &p| Some((x, p)));
+
+    let maxqc_one_assertions = tbox
+        .filter(|(_s, p, o)| *p == maxQualifiedCardinality && *o == cardinalityOne)
+        .map(|(s, _p, _o)| (s, ()))
+        .join_core(&op_assertions, |&x, &(), &p| Some((x, p)))
+        .join_core(&oc_assertions, |&x, &p, &c| Some((x, (p, c))));
+
+    // Membership in owl:Thing is not materialized, so restrictions qualified by it are handled as
+    // unqualified ones.
+    let maxc_one_assertions = maxc_one_assertions.concat(
+        &maxqc_one_assertions
+            .filter(|(_x, (_p, c))| *c == Thing)
+            .map(|(x, (p, _c))| (x, p)),
+    );
+    let maxqc_one_assertions = maxqc_one_assertions.filter(|(_x, (_p, c))| *c != Thing);
+
     // cls-thing
     let cls_thing = outer.new_collection_from(vec![(Thing, r#type, Class)]).1;
 
@@ -489,354 +705,2445 @@ pub fn owl2rl_abox<'a>(
 
     let abox = abox.concatenate(vec![cls_thing, cls_nothing1]);
 
-    outer
-        .iterative::<usize, _, _>(|inner| {
-            let abox_var = iterate::Variable::new(inner, Product::new(Default::default(), 1));
-            let tbox_var = iterate::Variable::new(inner, Product::new(Default::default(), 1));
+    let (abox_new, representatives) = outer.iterative::<usize, _, _>(|inner| {
+        let abox_var = iterate::Variable::new(inner, Product::new(Default::default(), 1));
+        let tbox_var = iterate::Variable::new(inner, Product::new(Default::default(), 1));
 
-            let abox_new = abox_var.distinct();
-            let tbox_new = tbox_var.distinct();
+        let abox_new = abox_var.distinct();
+        let tbox_new = tbox_var.distinct();
 
-            let lists_unrolled_by_i_and_o = lists_unrolled_by_i_c_i.enter(&inner);
+        let lists_unrolled_by_i_and_o = lists_unrolled_by_i_c_i.enter(inner);
 
-            let abox_by_s = abox_new.map(|(s, p, o)| (s, (p, o))).arrange_by_key();
-            let abox_by_p = abox_new.map(|(s, p, o)| (p, (s, o))).arrange_by_key();
-            let abox_by_o = abox_new.map(|(s, p, o)| (o, (s, p))).arrange_by_key();
-            let abox_by_sp = abox_new.map(|(s, p, o)| ((s, p), o)).arrange_by_key();
-            let abox_by_so = abox_new.map(|(s, p, o)| ((s, o), p)).arrange_by_key();
-            let abox_by_po = abox_new.map(|(s, p, o)| ((p, o), s)).arrange_by_key();
+        let abox_by_s = abox_new.map(|(s, p, o)| (s, (p, o))).arrange_by_key();
+        let abox_by_p = abox_new.map(|(s, p, o)| (p, (s, o))).arrange_by_key();
+        let abox_by_sp = abox_new.map(|(s, p, o)| ((s, p), o)).arrange_by_key();
+        let abox_by_so = abox_new.map(|(s, p, o)| ((s, o), p)).arrange_by_key();
+        let abox_by_po = abox_new.map(|(s, p, o)| ((p, o), s)).arrange_by_key();
 
-            let type_assertions = abox_new.filter(|(_s, p, _o)| *p == r#type);
-            let type_assertions_by_o = type_assertions.map(|(s, _p, o)| (o, s));
-            let type_assertions_by_o_arr = type_assertions_by_o.arrange_by_key();
-            let type_assertions_by_so = type_assertions.map(|(s, _p, o)| ((s, o), s));
+        let type_assertions = abox_new.filter(|(_s, p, _o)| *p == r#type);
+        let type_assertions_by_o = type_assertions.map(|(s, _p, o)| (o, s));
+        let type_assertions_by_o_arr = type_assertions_by_o.arrange_by_key();
+        let type_assertions_by_so = type_assertions.map(|(s, _p, o)| ((s, o), s));
 
-            let property_assertions = abox_new.filter(|(_s, p, _o)| *p != r#type);
-            let property_assertions_by_sp =
-                property_assertions.map(|(u_i, p_i, u_j)| ((u_i, p_i), u_j));
+        let property_assertions = abox_new.filter(|(_s, p, _o)| *p != r#type);
+        let property_assertions_by_sp =
+            property_assertions.map(|(u_i, p_i, u_j)| ((u_i, p_i), u_j));
 
-            let sco_assertions = sco_assertions.enter(&inner);
+        let sco_assertions = sco_assertions.enter(inner);
 
-            let eqc_assertions = eqc_assertions.enter(&inner);
+        let eqc_assertions = eqc_assertions.enter(inner);
 
-            let fp_assertions = fp_assertions.enter(&inner);
+        let fp_assertions = fp_assertions.enter(inner);
 
-            let ifp_assertions = ifp_assertions.enter(&inner);
+        let ifp_assertions = ifp_assertions.enter(inner);
 
-            let sas_assertions = tbox_new
-                .filter(|(_s, p, _o)| *p == sameAs)
-                .map(|(s, _p, o)| (s, o));
+        // sameAs assertions may come from either box, whereas the derived ones are kept apart
+        // from the abox, and are only merged back into it on the way out.
+        let sas_assertions = tbox_new
+            .concat(&abox_new)
+            .filter(|(_s, p, _o)| *p == sameAs)
+            .map(|(s, _p, o)| (s, o));
+
+        let dom_assertions = dom_assertions.enter(inner);
+
+        let rng_assertions = rng_assertions.enter(inner);
+
+        let symp_assertions = symp_assertions.enter(inner);
+
+        let trans_assertions = trans_assertions.enter(inner);
+
+        let spo_assertions = spo_assertions.enter(inner);
 
-            let sas_assertions_arr = sas_assertions.arrange_by_key();
-            let sas_assertions_by_o = sas_assertions.map(|(s, o)| (o, s));
+        let eqp_assertions = eqp_assertions.enter(inner);
 
-            let dom_assertions = dom_assertions.enter(&inner);
+        let inv_assertions = inv_assertions.enter(inner);
 
-            let rng_assertions = rng_assertions.enter(&inner);
+        let svf_assertions = svf_assertions.enter(inner);
 
-            let symp_assertions = symp_assertions.enter(&inner);
+        let avf_assertions = avf_assertions.enter(inner);
 
-            let trans_assertions = trans_assertions.enter(&inner);
+        let op_assertions = op_assertions.enter(inner);
 
-            let spo_assertions = spo_assertions.enter(&inner);
+        // With representatives, the individuals that the tbox names are rewritten as well, so
+        // that they still meet the rewritten abox. The triples that the rules build out of
+        // them are rewritten along with the rest of the derived abox.
+        let representatives = match equality {
+            Equality::Expansion => None,
+            Equality::Representatives => Some(representatives(&sas_assertions)),
+        };
 
-            let eqp_assertions = eqp_assertions.enter(&inner);
+        let hv_assertions = match &representatives {
+            Some(representatives) => rewrite_values(&hv_assertions.enter(inner), representatives),
+            None => hv_assertions.enter(inner),
+        };
 
-            let inv_assertions = inv_assertions.enter(&inner);
+        let oof_assertions_by_o = oof_assertions_by_o.enter(inner);
 
-            let svf_assertions = svf_assertions.enter(&inner);
+        let hk_keys_by_c = hk_keys_by_c.enter(inner);
 
-            let avf_assertions = avf_assertions.enter(&inner);
+        let hk_sizes = hk_sizes.enter(inner);
 
-            let op_assertions = op_assertions.enter(&inner);
+        let maxc_one_assertions = maxc_one_assertions.enter(inner);
 
-            let hv_assertions = hv_assertions.enter(&inner);
+        let maxqc_one_assertions = maxqc_one_assertions.enter(inner);
 
-            let oof_assertions_by_o = oof_assertions_by_o.enter(&inner);
+        // cax-sco
+        let cax_sco = sco_assertions.join_core(&type_assertions_by_o_arr, |&_c1, &c2, &x| {
+            Some((x, r#type, c2))
+        });
+
+        // cax-eqc1
+        let cax_eqc1 = eqc_assertions.join_core(&type_assertions_by_o_arr, |&_c1, &c2, &x| {
+            Some((x, r#type, c2))
+        });
 
-            // cax-sco
-            let cax_sco = sco_assertions.join_core(&type_assertions_by_o_arr, |&_c1, &c2, &x| {
-                Some((x, r#type, c2))
+        // cax-eqc2
+        let cax_eqc2 = eqc_assertions
+            .map(|(s, o)| (o, s))
+            .join_core(&type_assertions_by_o_arr, |&_c2, &c1, &x| {
+                Some((x, r#type, c1))
             });
 
-            // cax-eqc1
-            let cax_eqc1 = eqc_assertions.join_core(&type_assertions_by_o_arr, |&_c1, &c2, &x| {
-                Some((x, r#type, c2))
+        let cax = cax_sco.concatenate(vec![cax_eqc1, cax_eqc2]);
+
+        // prp-dom
+
+        let prp_dom =
+            dom_assertions.join_core(&abox_by_p, |&_p, &c, &(x, _y)| Some((x, r#type, c)));
+
+        // prp-rng
+
+        let prp_rng =
+            rng_assertions.join_core(&abox_by_p, |&_p, &c, &(_x, y)| Some((y, r#type, c)));
+
+        // prp-fp
+
+        let prp_fp_step_one =
+            fp_assertions.join_core(&abox_by_p, |&p, _, &(x, y1)| Some(((x, p), y1)));
+
+        let prp_fp = prp_fp_step_one
+            .join_core(&abox_by_sp, |&(_x, _p), &y1, &y2| Some((y1, sameAs, y2)))
+            .filter(|(y1, _, y2)| *y1 != *y2);
+
+        // prp-ifp
+
+        let prp_ifp_step_one =
+            ifp_assertions.join_core(&abox_by_p, |&p, _, &(x1, y)| Some(((p, y), x1)));
+
+        let prp_ifp = prp_ifp_step_one
+            .join_core(&abox_by_po, |&(_p, _y), &x1, &x2| Some((x1, sameAs, x2)))
+            .filter(|(x1, _, x2)| *x1 != *x2);
+
+        // prp-symp
+
+        let prp_symp = symp_assertions.join_core(&abox_by_p, |&p, _, &(x, y)| Some((y, p, x)));
+
+        // prp-trp
+
+        let prp_trp_step_one =
+            trans_assertions.join_core(&abox_by_p, |&p, _, &(x, y)| Some(((p, y), x)));
+
+        let prp_trp = prp_trp_step_one.join_core(&abox_by_sp, |&(p, _y), &x, &z| Some((x, p, z)));
+
+        // prp-spo1
+
+        let prp_spo1 = spo_assertions.join_core(&abox_by_p, |&_p1, &p2, &(x, y)| Some((x, p2, y)));
+
+        // prp-spo2
+
+        let prp_spo2_step_one = property_assertions
+            .map(|(u_i, p_i, u_j)| ((0u32, p_i), (u_i, u_j)))
+            .join_core(
+                &lists_unrolled_by_i_and_o.arrange_by_key(),
+                |&(i, _p_i), &(u_i, u_j), &x| Some(((i, x), (u_i, u_j))),
+            );
+
+        let prp_spo2 = inner
+            .iterative::<usize, _, _>(|inner_squared| {
+                let property_assertions_var = Variable::new_from(
+                    prp_spo2_step_one.enter(inner_squared),
+                    Product::new(Default::default(), 1),
+                );
+
+                let lists_by_i_x = lists_unrolled_by_i_and_o
+                    .enter(inner_squared)
+                    .map(|((i, p_i), x)| ((i, x), p_i))
+                    .arrange_by_key();
+
+                let property_assertions_new = property_assertions_var.distinct();
+
+                let property_assertions_by_sp = property_assertions_by_sp
+                    .enter(inner_squared)
+                    .arrange_by_key();
+
+                let property_assertions_new = property_assertions_new
+                    .map(|((i, x), (u_i, u_j))| ((i + 1, x), (u_i, u_j)))
+                    .join_core(&lists_by_i_x, |&(i, x), &(u_i, u_j), &p_i| {
+                        Some(((u_j, p_i), (i, x, u_i)))
+                    })
+                    .join_core(
+                        &property_assertions_by_sp,
+                        |&(_u_j, _p_i), &(i, x, u_i), &u_k| Some(((i, x), (u_i, u_k))),
+                    )
+                    .concat(&property_assertions_new)
+                    .distinct();
+
+                property_assertions_var.set(&property_assertions_new);
+
+                property_assertions_new.leave()
+            })
+            .join_core(
+                &lists_by_last_i_x.enter(inner),
+                |&(_i, x), &(u_i, u_k), &_| Some((x, (u_i, u_k))),
+            )
+            .join_core(&pca_assertions_by_o.enter(inner), |&_x, &(u_i, u_k), &p| {
+                Some((u_i, p, u_k))
             });
 
-            // cax-eqc2
-            let cax_eqc2 = eqc_assertions
-                .map(|(s, o)| (o, s))
-                .join_core(&type_assertions_by_o_arr, |&_c2, &c1, &x| {
-                    Some((x, r#type, c1))
-                });
+        // prp-eqp1
 
-            let cax = cax_sco.concatenate(vec![cax_eqc1, cax_eqc2]);
+        let prp_eqp1 = eqp_assertions.join_core(&abox_by_p, |&_p1, &p2, &(x, y)| Some((x, p2, y)));
 
-            // eq-rep-s
-            let eq_rep_s = sas_assertions
-                .join_core(&abox_by_s, |&_s, &s_prime, &(p, o)| Some((s_prime, p, o)));
+        // prp-eqp2
 
-            // eq-rep-p
-            let eq_rep_p = sas_assertions
-                .join_core(&abox_by_p, |&_p, &p_prime, &(s, o)| Some((s, p_prime, o)));
+        let prp_eqp2 = eqp_assertions
+            .map(|(p1, p2)| (p2, p1))
+            .join_core(&abox_by_p, |&_p2, &p1, &(x, y)| Some((x, p1, y)));
 
-            // eq-rep-o
-            let eq_rep_o = sas_assertions
-                .join_core(&abox_by_o, |&_o, &o_prime, &(s, p)| Some((s, p, o_prime)));
+        // prp-inv1
 
-            // eq-sym
+        let prp_inv1 = inv_assertions.join_core(&abox_by_p, |&_p1, &p2, &(x, y)| Some((y, p2, x)));
 
-            let eq_sym = sas_assertions.map(|(s, o)| (o, sameAs, s));
+        // prp-inv2
 
-            // eq-trans
+        let prp_inv2 = inv_assertions
+            .map(|(p1, p2)| (p2, p1))
+            .join_core(&abox_by_p, |&_p2, &p1, &(x, y)| Some((y, p1, x)));
 
-            let eq_trans = sas_assertions_by_o
-                .join_core(&sas_assertions_arr, |&_y, &x, &z| Some((x, sameAs, z)));
+        let prp_abox = prp_dom.concatenate(vec![
+            prp_rng, prp_symp, prp_trp, prp_spo1, prp_spo2, prp_eqp1, prp_eqp2, prp_inv1, prp_inv2,
+        ]);
 
-            let eq_abox = eq_rep_s.concatenate(vec![eq_rep_p, eq_rep_o]);
-            let eq_tbox = eq_sym.concat(&eq_trans);
+        // prp-key
 
-            // prp-dom
+        let prp_key_step_one = type_assertions_by_o
+            .join_core(&hk_keys_by_c, |&c, &x, &(list, i, p_i)| {
+                Some(((x, p_i), (c, list, i)))
+            })
+            .join_core(&abox_by_sp, |&(x, _p_i), &(c, list, i), &z| {
+                Some(((c, list, i, z), x))
+            });
+
+        // Two individuals are the same once they agree on every key of one of the axioms of
+        // their class, which stays incremental, as a changed key value takes back its
+        // agreement.
+        let prp_key = prp_key_step_one
+            .join_core(
+                &prp_key_step_one.arrange_by_key(),
+                |&(c, list, i, _z), &x, &y| Some((c, list, i, x, y)),
+            )
+            .filter(|(_c, _list, _i, x, y)| *x != *y)
+            .distinct()
+            .map(|(c, list, _i, x, y)| (c, list, x, y))
+            .count()
+            .map(|((c, list, x, y), agreements)| ((c, list, agreements), (x, y)))
+            .join_core(&hk_sizes, |&(_c, _list, _n), &(x, y), &()| {
+                Some((x, sameAs, y))
+            });
+
+        let prp_tbox = prp_fp.concatenate(vec![prp_ifp, prp_key]);
+
+        // cls-int1
+
+        let cls_int1_step_one = type_assertions_by_o.map(|(o, s)| ((0, o), s)).join_core(
+            &lists_unrolled_by_i_and_o.arrange_by_key(),
+            |&(i, _c_i), &y, &x| Some(((i, x), y)),
+        );
+
+        let cls_int1 = inner
+            .iterative::<usize, _, _>(|inner_squared| {
+                let type_assertions_var = Variable::new_from(
+                    cls_int1_step_one.enter(inner_squared),
+                    Product::new(Default::default(), 1),
+                );
 
-            let prp_dom =
-                dom_assertions.join_core(&abox_by_p, |&_p, &c, &(x, _y)| Some((x, r#type, c)));
+                let lists_by_i_x = lists_unrolled_by_i_and_o
+                    .enter(inner_squared)
+                    .map(|((i, c_i), x)| ((i, x), c_i))
+                    .arrange_by_key();
+
+                let type_assertions_new = type_assertions_var.distinct();
+
+                let type_assertions_by_so =
+                    type_assertions_by_so.enter(inner_squared).arrange_by_key();
+
+                let type_assertions_new = type_assertions_new
+                    .map(|((i, x), y)| ((i + 1, x), y))
+                    .join_core(&lists_by_i_x, |&(i, x), &y, &c_i| Some(((c_i, y), (i, x))))
+                    .join_core(&type_assertions_by_so, |&(_c_i, y), &(i, x), &_y| {
+                        Some(((i, x), y))
+                    })
+                    .concat(&type_assertions_new)
+                    .distinct();
+
+                type_assertions_var.set(&type_assertions_new);
+
+                type_assertions_new.leave()
+            })
+            .join_core(&lists_unrolled_size.enter(inner), |&(_i, x), &y, &_| {
+                Some((x, y))
+            })
+            .join_core(&iof_assertions_by_o.enter(inner), |&_x, &y, &c| {
+                Some((y, r#type, c))
+            });
 
-            // prp-rng
+        // cls-int2
 
-            let prp_rng =
-                rng_assertions.join_core(&abox_by_p, |&_p, &c, &(_x, y)| Some((y, r#type, c)));
+        let cls_int2 = type_assertions_by_o
+            .join_core(&iof_assertions_arr.enter(inner), |&_c, &y, &x| Some((x, y)))
+            .join_core(
+                &lists_unrolled_by_i_and_o
+                    .map(|((_i, c_i), x)| (x, c_i))
+                    .arrange_by_key(),
+                |&_x, &y, &c_i| Some((y, r#type, c_i)),
+            );
 
-            // prp-fp
+        // cls-uni
+
+        let cls_uni = type_assertions_by_o
+            .join_core(
+                &lists_unrolled_by_i_and_o
+                    .map(|((_i, c_i), x)| (c_i, x))
+                    .arrange_by_key(),
+                |&_c_i, &y, &x| Some((x, y)),
+            )
+            .join_core(&uof_assertions_by_o.enter(inner), |&_x, &y, &c| {
+                Some((y, r#type, c))
+            });
 
-            let prp_fp_step_one =
-                fp_assertions.join_core(&abox_by_p, |&p, _, &(x, y1)| Some(((x, p), y1)));
+        // cls-svf1
+        let cls_svf1_step_one =
+            svf_assertions.join_core(&op_assertions, |&x, &y, &p| Some((p, (y, x))));
 
-            let prp_fp = prp_fp_step_one
-                .join_core(&abox_by_sp, |&(_x, _p), &y1, &y2| Some((y1, sameAs, y2)))
-                .filter(|(y1, _, y2)| *y1 != *y2);
+        let cls_svf1_step_two =
+            cls_svf1_step_one.join_core(&abox_by_p, |&_p, &(y, x), &(u, v)| Some(((v, y), (u, x))));
 
-            // prp-ifp
+        let cls_svf1 =
+            cls_svf1_step_two.join_core(&abox_by_so, |&(_v, _y), &(u, x), &_| Some((u, r#type, x)));
 
-            let prp_ifp_step_one =
-                ifp_assertions.join_core(&abox_by_p, |&p, _, &(x1, y)| Some(((p, y), x1)));
+        // cls-svf2
+        let cls_svf2_step_one = svf_assertions
+            .filter(|(_s, o)| *o == Thing)
+            .join_core(&op_assertions, |&x, _, &p| Some((p, x)));
 
-            let prp_ifp = prp_ifp_step_one
-                .join_core(&abox_by_po, |&(_p, _y), &x1, &x2| Some((x1, sameAs, x2)))
-                .filter(|(x1, _, x2)| *x1 != *x2);
+        let cls_svf2 =
+            cls_svf2_step_one.join_core(&abox_by_p, |&_p, &x, &(u, _v)| Some((u, r#type, x)));
 
-            // prp-symp
+        // cls-avf
+        let cls_avf_step_one =
+            avf_assertions.join_core(&op_assertions, |&x, &y, &p| Some((x, (p, y))));
 
-            let prp_symp = symp_assertions.join_core(&abox_by_p, |&p, _, &(x, y)| Some((y, p, x)));
+        let cls_avf_step_two = cls_avf_step_one
+            .join_core(&type_assertions_by_o_arr, |&_x, &(p, y), &u| {
+                Some(((u, p), y))
+            });
 
-            // prp-trp
+        let cls_avf =
+            cls_avf_step_two.join_core(&abox_by_sp, |&(_u, _p), &y, &v| Some((v, r#type, y)));
 
-            let prp_trp_step_one =
-                trans_assertions.join_core(&abox_by_p, |&p, _, &(x, y)| Some(((p, y), x)));
+        // cls-hv1
+        let cls_hv1_step_one =
+            hv_assertions.join_core(&op_assertions, |&x, &y, &p| Some((x, (p, y))));
 
-            let prp_trp =
-                prp_trp_step_one.join_core(&abox_by_sp, |&(p, _y), &x, &z| Some((x, p, z)));
+        let cls_hv1 = cls_hv1_step_one.join_core(&type_assertions_by_o_arr, |&_x, &(p, y), &u| {
+            Some((u, p, y))
+        });
 
-            // prp-spo1
+        // cls-hv2
+        let cls_hv2_step_one =
+            hv_assertions.join_core(&op_assertions, |&x, &y, &p| Some(((p, y), x)));
 
-            let prp_spo1 =
-                spo_assertions.join_core(&abox_by_p, |&_p1, &p2, &(x, y)| Some((x, p2, y)));
+        let cls_hv2 =
+            cls_hv2_step_one.join_core(&abox_by_po, |&(_p, _y), &x, &u| Some((u, r#type, x)));
 
-            // prp-spo2
+        // cls-oo
 
-            let prp_spo2_step_one = property_assertions
-                .map(|(u_i, p_i, u_j)| ((0u32, p_i), (u_i, u_j)))
-                .join_core(
-                    &lists_unrolled_by_i_and_o.arrange_by_key(),
-                    |&(i, _p_i), &(u_i, u_j), &x| Some(((i, x), (u_i, u_j))),
+        let cls_oo = oof_assertions_by_o
+            .join_core(&lists_arr.enter(inner), |&_x, &c, list| {
+                Some((c, list.clone()))
+            })
+            .flat_map(|(c, list)| {
+                list.iter()
+                    .map(|y_x| (*y_x, r#type, c))
+                    .collect::<Vec<Triple>>()
+            });
+
+        // cls-maxc2 and cls-maxqc4
+        let cls_maxc2 = maxc_one_assertions
+            .join_core(&type_assertions_by_o_arr, |&_x, &p, &u| Some(((u, p), ())))
+            .join_core(&abox_by_sp, |&(u, p), &(), &y1| Some(((u, p), y1)))
+            .join_core(&abox_by_sp, |&(_u, _p), &y1, &y2| Some((y1, sameAs, y2)))
+            .filter(|(y1, _, y2)| *y1 != *y2);
+
+        // cls-maxqc3
+        let cls_maxqc3_step_one = maxqc_one_assertions
+            .join_core(&type_assertions_by_o_arr, |&_x, &(p, c), &u| {
+                Some(((u, p), c))
+            })
+            .join_core(&abox_by_sp, |&(u, p), &c, &y| Some(((y, c), (u, p))))
+            .join_core(
+                &type_assertions_by_so.arrange_by_key(),
+                |&(y, c), &(u, p), &_y| Some(((u, p, c), y)),
+            );
+
+        let cls_maxqc3 = cls_maxqc3_step_one
+            .join_core(
+                &cls_maxqc3_step_one.arrange_by_key(),
+                |&(_u, _p, _c), &y1, &y2| Some((y1, sameAs, y2)),
+            )
+            .filter(|(y1, _, y2)| *y1 != *y2);
+
+        let cls_tbox = cls_maxc2.concat(&cls_maxqc3);
+
+        let cls = cls_svf1.concatenate(vec![
+            cls_int1, cls_int2, cls_uni, cls_svf2, cls_avf, cls_hv1, cls_hv2, cls_oo,
+        ]);
+
+        match representatives {
+            None => {
+                let abox_by_o = abox_new.map(|(s, p, o)| (o, (s, p))).arrange_by_key();
+                let sas_assertions_arr = sas_assertions.arrange_by_key();
+                let sas_assertions_by_o = sas_assertions.map(|(s, o)| (o, s));
+
+                // eq-rep-s
+                let eq_rep_s = sas_assertions
+                    .join_core(&abox_by_s, |&_s, &s_prime, &(p, o)| Some((s_prime, p, o)));
+
+                // eq-rep-p
+                let eq_rep_p = sas_assertions
+                    .join_core(&abox_by_p, |&_p, &p_prime, &(s, o)| Some((s, p_prime, o)));
+
+                // eq-rep-o
+                let eq_rep_o = sas_assertions
+                    .join_core(&abox_by_o, |&_o, &o_prime, &(s, p)| Some((s, p, o_prime)));
+
+                // eq-sym
+
+                let eq_sym = sas_assertions.map(|(s, o)| (o, sameAs, s));
+
+                // eq-trans
+
+                let eq_trans = sas_assertions_by_o
+                    .join_core(&sas_assertions_arr, |&_y, &x, &z| Some((x, sameAs, z)));
+
+                let eq_abox = eq_rep_s.concatenate(vec![eq_rep_p, eq_rep_o]);
+                let eq_tbox = eq_sym.concat(&eq_trans);
+
+                abox_var.set(
+                    &abox
+                        .enter(inner)
+                        .concatenate(vec![cax, eq_abox, prp_abox, cls]),
+                );
+                tbox_var.set(
+                    &tbox
+                        .enter(inner)
+                        .concatenate(vec![eq_tbox, prp_tbox, cls_tbox]),
                 );
 
-            let prp_spo2 = inner
-                .iterative::<usize, _, _>(|inner_squared| {
-                    let property_assertions_var = Variable::new_from(
-                        prp_spo2_step_one.enter(inner_squared),
-                        Product::new(Default::default(), 1),
-                    );
-
-                    let lists_by_i_x = lists_unrolled_by_i_and_o
-                        .enter(inner_squared)
-                        .map(|((i, p_i), x)| ((i, x), p_i))
-                        .arrange_by_key();
-
-                    let property_assertions_new = property_assertions_var.distinct();
-
-                    let property_assertions_by_sp = property_assertions_by_sp
-                        .enter(inner_squared)
-                        .arrange_by_key();
-
-                    let property_assertions_new = property_assertions_new
-                        .map(|((i, x), (u_i, u_j))| ((i + 1, x), (u_i, u_j)))
-                        .join_core(&lists_by_i_x, |&(i, x), &(u_i, u_j), &p_i| {
-                            Some(((u_j, p_i), (i, x, u_i)))
-                        })
-                        .join_core(
-                            &property_assertions_by_sp,
-                            |&(_u_j, _p_i), &(i, x, u_i), &u_k| Some(((i, x), (u_i, u_k))),
-                        )
-                        .concat(&property_assertions_new)
-                        .distinct();
-
-                    property_assertions_var.set(&property_assertions_new);
-
-                    property_assertions_new.leave()
-                })
-                .join_core(
-                    &lists_by_last_i_x.enter(&inner),
-                    |&(_i, x), &(u_i, u_k), &_| Some((x, (u_i, u_k))),
+                // Only the sameAs links that the rules derive are merged into the abox, as
+                // those it states are in it already, and those the tbox states stay there.
+                let derived_sas = tbox_new
+                    .filter(|(_s, p, _o)| *p == sameAs)
+                    .concat(&tbox_sas_assertions.enter(inner).negate());
+
+                (abox_new.concat(&derived_sas).distinct().leave(), None)
+            }
+            Some(representatives) => {
+                let abox_derived = abox.enter(inner).concatenate(vec![cax, prp_abox, cls]);
+                // sameAs is kept apart before rewriting, as it would otherwise collapse into
+                // the reflexive sameAs of the representative. Links are also carried over
+                // from the previous round, as the triples they were derived from may have
+                // collapsed into one since.
+                let abox_sas_assertions = abox_derived.filter(|(_s, p, _o)| *p == sameAs);
+
+                abox_var.set(&rewrite(&abox_derived, &representatives));
+                tbox_var.set(&tbox.enter(inner).concatenate(vec![
+                    prp_tbox,
+                    cls_tbox,
+                    abox_sas_assertions,
+                    tbox_new.filter(|(_s, p, _o)| *p == sameAs),
+                ]));
+
+                let representative_sas = representatives.map(|(_x, r)| (r, sameAs, r)).distinct();
+
+                (
+                    abox_new.concat(&representative_sas).leave(),
+                    Some(representatives.leave()),
                 )
+            }
+        }
+    });
+
+    // The triples over representatives are only expanded once, after the loop has settled.
+    let abox_new = match representatives {
+        Some(representatives) => expand(&abox_new, &representatives),
+        None => abox_new,
+    };
+
+    abox_new.concat(&abox).consolidate()
+}
+
+pub fn owl2rl_inconsistencies<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+    ill_typed: &TupleCollection<'a>,
+) -> InconsistencyCollection<'a> {
+    let dw_assertions = tbox
+        .filter(|(_s, p, _o)| *p == disjointWith)
+        .map(|(s, _p, o)| (s, o));
+
+    let com_assertions = tbox
+        .filter(|(_s, p, _o)| *p == complementOf)
+        .map(|(s, _p, o)| (s, o));
+
+    let adc_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == AllDisjointClasses)
+        .map(|(s, _p, _o)| (s, ()));
+
+    let members_assertions = tbox
+        .filter(|(_s, p, _o)| *p == members)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+
+    let irp_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == IrreflexiveProperty)
+        .map(|(s, _p, _o)| (s, ()))
+        .arrange_by_key();
+
+    let asyp_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == AsymmetricProperty)
+        .map(|(s, _p, _o)| (s, ()));
+
+    let pdw_assertions = tbox
+        .filter(|(_s, p, _o)| *p == propertyDisjointWith)
+        .map(|(s, _p, o)| (s, o));
+
+    let adp_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == AllDisjointProperties)
+        .map(|(s, _p, _o)| (s, ()));
+
+    // Negative property assertions and differences between individuals are rather stated in the
+    // abox, alongside the lists of the latter.
+    let assertions = tbox.concat(abox);
+
+    let lists_arr = lists
+        .concat(&expand_lists(abox))
+        .distinct()
+        .arrange_by_key();
+
+    let npa_source_assertions = assertions
+        .filter(|(_s, p, _o)| *p == sourceIndividual)
+        .map(|(s, _p, o)| (s, o));
+    let npa_property_assertions = assertions
+        .filter(|(_s, p, _o)| *p == assertionProperty)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+    let npa_target_individual_assertions = assertions
+        .filter(|(_s, p, _o)| *p == targetIndividual)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+    let npa_target_value_assertions = assertions
+        .filter(|(_s, p, _o)| *p == targetValue)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+
+    let dif_assertions = assertions
+        .filter(|(_s, p, _o)| *p == differentFrom)
+        .map(|(s, _p, o)| ((s, sameAs, o), ()));
+
+    let ad_assertions = assertions
+        .filter(|(_s, p, o)| *p == r#type && *o == AllDIfferent)
+        .map(|(s, _p, _o)| (s, ()));
+    let ad_members_assertions = assertions
+        .filter(|(_s, p, _o)| *p == members || *p == distinctMembers)
+        .map(|(s, p, o)| (s, (p, o)))
+        .arrange_by_key();
+
+    let op_assertions = tbox
+        .filter(|(_s, p, _o)| *p == onProperty)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+    let oc_assertions = tbox
+        .filter(|(_s, p, _o)| *p == onClass)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+    let maxc_zero_assertions = tbox
+        .filter(|(_s, p, o)| *p == maxCardinality && *o == cardinalityZero)
+        .map(|(s, _p, _o)| (s, ()));
+    let maxqc_zero_assertions = tbox
+        .filter(|(_s, p, o)| *p == maxQualifiedCardinality && *o == cardinalityZero)
+        .map(|(s, _p, _o)| (s, ()));
+
+    // The literals of a supported datatype, the datatypes whose value space holds each of them, and
+    // which of them are the same value, are all entailed up front from the dictionary.
+    let literal_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == Literal)
+        .map(|(s, _p, _o)| (s, ()))
+        .arrange_by_key();
+    let datatype_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == Datatype)
+        .map(|(s, _p, _o)| (s, ()))
+        .arrange_by_key();
+    let literal_types = tbox
+        .filter(|(_s, p, _o)| *p == r#type)
+        .map(|(s, _p, o)| (s, o))
+        .distinct();
+    let literal_equalities = tbox
+        .filter(|(_s, p, _o)| *p == sameAs)
+        .map(|(s, _p, o)| (s, o))
+        .distinct();
+
+    let abox_by_p = abox.map(|(s, p, o)| (p, (s, o))).arrange_by_key();
+    let abox_by_sp = abox.map(|(s, p, o)| ((s, p), o)).arrange_by_key();
+    let abox_by_spo = abox.map(|triple| (triple, ())).arrange_by_key();
+
+    let type_assertions = abox.filter(|(_s, p, _o)| *p == r#type);
+    let type_assertions_by_o_arr = type_assertions.map(|(s, _p, o)| (o, s)).arrange_by_key();
+    let type_assertions_by_so = type_assertions
+        .map(|(s, _p, o)| ((s, o), ()))
+        .arrange_by_key();
+
+    // cax-dw
+    let cax_dw = dw_assertions
+        .join_core(&type_assertions_by_o_arr, |&c1, &c2, &x| {
+            Some(((x, c2), c1))
+        })
+        .join_core(&type_assertions_by_so, |&(x, c2), &c1, &()| {
+            Some((
+                "cax-dw".to_string(),
+                vec![(c1, disjointWith, c2), (x, r#type, c1), (x, r#type, c2)],
+            ))
+        });
+
+    // cax-adc
+    let cax_adc = adc_assertions
+        .join_core(&members_assertions, |&x, &(), &list| Some((list, x)))
+        .join_core(&lists_arr, |&list, &x, classes| {
+            distinct_pairs(classes)
+                .into_iter()
+                .map(move |(c_i, c_j)| (c_i, (c_j, x, list)))
+        })
+        .join_core(&type_assertions_by_o_arr, |&c_i, &(c_j, x, list), &z| {
+            Some(((z, c_j), (c_i, x, list)))
+        })
+        .join_core(&type_assertions_by_so, |&(z, c_j), &(c_i, x, list), &()| {
+            Some((
+                "cax-adc".to_string(),
+                vec![
+                    (x, r#type, AllDisjointClasses),
+                    (x, members, list),
+                    (z, r#type, c_i),
+                    (z, r#type, c_j),
+                ],
+            ))
+        });
+
+    // cls-com
+    let cls_com = com_assertions
+        .join_core(&type_assertions_by_o_arr, |&c1, &c2, &x| {
+            Some(((x, c2), c1))
+        })
+        .join_core(&type_assertions_by_so, |&(x, c2), &c1, &()| {
+            Some((
+                "cls-com".to_string(),
+                vec![(c1, complementOf, c2), (x, r#type, c1), (x, r#type, c2)],
+            ))
+        });
+
+    // cls-nothing2
+    let cls_nothing2 = type_assertions
+        .filter(|(_x, _p, o)| *o == Nothing)
+        .map(|triple| ("cls-nothing2".to_string(), vec![triple]));
+
+    // prp-irp
+    let prp_irp = abox
+        .filter(|(x, _p, y)| *x == *y)
+        .map(|(x, p, _x)| (p, x))
+        .join_core(&irp_assertions, |&p, &x, &()| {
+            Some((
+                "prp-irp".to_string(),
+                vec![(p, r#type, IrreflexiveProperty), (x, p, x)],
+            ))
+        });
+
+    // prp-asyp
+    let prp_asyp = asyp_assertions
+        .join_core(&abox_by_p, |&p, &(), &(x, y)| {
+            // Each violation is witnessed by both triples, so only one of them looks for the other
+            if x <= y {
+                Some(((y, p, x), (x, y)))
+            } else {
+                None
+            }
+        })
+        .join_core(&abox_by_spo, |&(_y, p, _x), &(x, y), &()| {
+            Some((
+                "prp-asyp".to_string(),
+                vec![(p, r#type, AsymmetricProperty), (x, p, y), (y, p, x)],
+            ))
+        });
+
+    // prp-pdw
+    let prp_pdw = pdw_assertions
+        .join_core(&abox_by_p, |&p1, &p2, &(x, y)| Some(((x, p2, y), p1)))
+        .join_core(&abox_by_spo, |&(x, p2, y), &p1, &()| {
+            Some((
+                "prp-pdw".to_string(),
+                vec![(p1, propertyDisjointWith, p2), (x, p1, y), (x, p2, y)],
+            ))
+        });
+
+    // prp-adp
+    let prp_adp = adp_assertions
+        .join_core(&members_assertions, |&x, &(), &list| Some((list, x)))
+        .join_core(&lists_arr, |&list, &x, properties| {
+            distinct_pairs(properties)
+                .into_iter()
+                .map(move |(p_i, p_j)| (p_i, (p_j, x, list)))
+        })
+        .join_core(&abox_by_p, |&p_i, &(p_j, x, list), &(u, y)| {
+            Some(((u, p_j, y), (p_i, x, list)))
+        })
+        .join_core(&abox_by_spo, |&(u, p_j, y), &(p_i, x, list), &()| {
+            Some((
+                "prp-adp".to_string(),
+                vec![
+                    (x, r#type, AllDisjointProperties),
+                    (x, members, list),
+                    (u, p_i, y),
+                    (u, p_j, y),
+                ],
+            ))
+        });
+
+    let npa_assertions =
+        npa_source_assertions.join_core(&npa_property_assertions, |&x, &i1, &p| Some((x, (i1, p))));
+
+    // prp-npa1
+    let prp_npa1 = npa_assertions
+        .join_core(&npa_target_individual_assertions, |&x, &(i1, p), &i2| {
+            Some(((i1, p, i2), x))
+        })
+        .join_core(&abox_by_spo, |&(i1, p, i2), &x, &()| {
+            Some((
+                "prp-npa1".to_string(),
+                vec![
+                    (x, sourceIndividual, i1),
+                    (x, assertionProperty, p),
+                    (x, targetIndividual, i2),
+                    (i1, p, i2),
+                ],
+            ))
+        });
+
+    // prp-npa2
+    let prp_npa2 = npa_assertions
+        .join_core(&npa_target_value_assertions, |&x, &(i, p), &lt| {
+            Some(((i, p, lt), x))
+        })
+        .join_core(&abox_by_spo, |&(i, p, lt), &x, &()| {
+            Some((
+                "prp-npa2".to_string(),
+                vec![
+                    (x, sourceIndividual, i),
+                    (x, assertionProperty, p),
+                    (x, targetValue, lt),
+                    (i, p, lt),
+                ],
+            ))
+        });
+
+    // eq-diff1
+    let eq_diff1 = dif_assertions.join_core(&abox_by_spo, |&(x, _same_as, y), &(), &()| {
+        Some((
+            "eq-diff1".to_string(),
+            vec![(x, sameAs, y), (x, differentFrom, y)],
+        ))
+    });
+
+    // eq-diff2 and eq-diff3
+    let eq_diff2_3 = ad_assertions
+        .join_core(&ad_members_assertions, |&x, &(), &(p, list)| {
+            Some((list, (x, p)))
+        })
+        .join_core(&lists_arr, |&list, &(x, p), individuals| {
+            distinct_pairs(individuals)
+                .into_iter()
+                .map(move |(y_i, y_j)| ((y_i, sameAs, y_j), (x, p, list)))
+        })
+        .join_core(&abox_by_spo, |&(y_i, _same_as, y_j), &(x, p, list), &()| {
+            let rule = if p == members { "eq-diff2" } else { "eq-diff3" };
+            Some((
+                rule.to_string(),
+                vec![(x, r#type, AllDIfferent), (x, p, list), (y_i, sameAs, y_j)],
+            ))
+        });
+
+    // cls-maxc1
+    let cls_maxc1 = maxc_zero_assertions
+        .join_core(&op_assertions, |&x, &(), &p| Some((x, p)))
+        .join_core(&type_assertions_by_o_arr, |&x, &p, &u| Some(((u, p), x)))
+        .join_core(&abox_by_sp, |&(u, p), &x, &y| {
+            Some((
+                "cls-maxc1".to_string(),
+                vec![
+                    (x, maxCardinality, cardinalityZero),
+                    (x, onProperty, p),
+                    (u, r#type, x),
+                    (u, p, y),
+                ],
+            ))
+        });
+
+    let maxqc_zero_violations = maxqc_zero_assertions
+        .join_core(&op_assertions, |&x, &(), &p| Some((x, p)))
+        .join_core(&oc_assertions, |&x, &p, &c| Some((x, (p, c))))
+        .join_core(&type_assertions_by_o_arr, |&x, &(p, c), &u| {
+            Some(((u, p), (x, c)))
+        })
+        .join_core(&abox_by_sp, |&(u, p), &(x, c), &y| Some((x, p, c, u, y)));
+
+    // cls-maxqc1
+    let cls_maxqc1 = maxqc_zero_violations
+        .filter(|(_x, _p, c, _u, _y)| *c != Thing)
+        .map(|(x, p, c, u, y)| ((y, c), (x, p, u)))
+        .join_core(&type_assertions_by_so, |&(y, c), &(x, p, u), &()| {
+            Some((
+                "cls-maxqc1".to_string(),
+                vec![
+                    (x, maxQualifiedCardinality, cardinalityZero),
+                    (x, onProperty, p),
+                    (x, onClass, c),
+                    (u, r#type, x),
+                    (u, p, y),
+                    (y, r#type, c),
+                ],
+            ))
+        });
+
+    // cls-maxqc2
+    let cls_maxqc2 = maxqc_zero_violations
+        .filter(|(_x, _p, c, _u, _y)| *c == Thing)
+        .map(|(x, p, c, u, y)| {
+            (
+                "cls-maxqc2".to_string(),
+                vec![
+                    (x, maxQualifiedCardinality, cardinalityZero),
+                    (x, onProperty, p),
+                    (x, onClass, c),
+                    (u, r#type, x),
+                    (u, p, y),
+                ],
+            )
+        });
+
+    // dt-not-type
+    let dt_not_type = type_assertions
+        .map(|(lt, _p, dt)| (dt, lt))
+        .join_core(&datatype_assertions, |&dt, &lt, &()| Some((lt, dt)))
+        .join_core(&literal_assertions, |&lt, &dt, &()| Some(((lt, dt), ())))
+        .antijoin(&literal_types)
+        .map(|((lt, dt), ())| ("dt-not-type".to_string(), vec![(lt, r#type, dt)]))
+        // An ill-typed literal is rejected as soon as it shows up, whether or not it is typed with
+        // the datatype it claims to have.
+        .concat(&ill_typed.map(|(lt, dt)| ("dt-not-type".to_string(), vec![(lt, r#type, dt)])));
+
+    // dt-diff, only for literals that were stated or derived to be the same, rather than every pair
+    // of different values.
+    let dt_diff = abox
+        .filter(|(s, p, o)| *p == sameAs && *s != *o)
+        .map(|(lt1, _p, lt2)| (lt1, lt2))
+        .join_core(&literal_assertions, |&lt1, &lt2, &()| Some((lt2, lt1)))
+        .join_core(&literal_assertions, |&lt2, &lt1, &()| {
+            Some(((lt1, lt2), ()))
+        })
+        .antijoin(&literal_equalities)
+        .map(|((lt1, lt2), ())| ("dt-diff".to_string(), vec![(lt1, sameAs, lt2)]));
+
+    cax_dw.concatenate(vec![
+        cax_adc,
+        cls_com,
+        cls_nothing2,
+        cls_maxc1,
+        cls_maxqc1,
+        cls_maxqc2,
+        prp_irp,
+        prp_asyp,
+        prp_pdw,
+        prp_adp,
+        prp_npa1,
+        prp_npa2,
+        eq_diff1,
+        eq_diff2_3,
+        dt_not_type,
+        dt_diff,
+    ])
+}
+
+// Every pair of elements at different positions of a list.
+fn distinct_pairs(elements: &[u32]) -> Vec<(u32, u32)> {
+    let mut pairs = vec![];
+    for (i, e_i) in elements.iter().enumerate() {
+        for e_j in elements[i + 1..].iter() {
+            pairs.push((*e_i, *e_j));
+        }
+    }
+    pairs
+}
+
+// The scm rules, applied once to the tbox, given its lists.
+fn owl2rl_tbox_rules<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    let lists_arr = lists.arrange_by_key();
+
+    let cls_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == Class)
+        .map(|(s, _p, _o)| s);
+    let op_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == ObjectProperty)
+        .map(|(s, _p, _o)| s);
+
+    let sco_assertions = tbox
+        .filter(|(_s, p, _o)| *p == subClassOf)
+        .map(|(s, _p, o)| (s, o));
+    let sco_assertions_arr = sco_assertions.arrange_by_key();
+    let sco_assertions_by_o = sco_assertions.map(|(s, o)| (o, s));
+    let sco_assertions_by_so = sco_assertions.map(|(s, o)| ((s, o), ())).arrange_by_key();
+
+    let spo_assertions = tbox
+        .filter(|(_s, p, _o)| *p == subPropertyOf)
+        .map(|(s, _p, o)| (s, o));
+    let spo_assertions_arr = spo_assertions.arrange_by_key();
+    let spo_assertions_by_o = spo_assertions.map(|(s, o)| (o, s));
+    let spo_assertions_by_o_arr = spo_assertions_by_o.arrange_by_key();
+    let spo_assertions_by_so = spo_assertions.map(|(s, o)| ((s, o), ())).arrange_by_key();
+
+    let eqc_assertions = tbox
+        .filter(|(_s, p, _o)| *p == equivalentClass)
+        .map(|(s, _p, o)| (s, o));
+    let eqp_assertions = tbox
+        .filter(|(_s, p, _o)| *p == equivalentProperty)
+        .map(|(s, _p, o)| (s, o));
+    let dom_assertions = tbox
+        .filter(|(_s, p, _o)| *p == domain)
+        .map(|(s, _p, o)| (s, o));
+    let rng_assertions = tbox
+        .filter(|(_s, p, _o)| *p == range)
+        .map(|(s, _p, o)| (s, o));
+    let onp_assertions_arr = tbox
+        .filter(|(_s, p, _o)| *p == onProperty)
+        .map(|(s, _p, o)| (s, o))
+        .arrange_by_key();
+
+    // The restrictions of each kind, keyed by the class or individual they restrict to, along
+    // with their property.
+    let restrictions = |restriction: u32| {
+        tbox.filter(move |(_s, p, _o)| *p == restriction)
+            .map(|(s, _p, o)| (s, o))
+            .join_core(&onp_assertions_arr, |&c, &y, &p| Some((y, (c, p))))
+    };
+    let hv_restrictions = restrictions(hasValue);
+    let svf_restrictions = restrictions(someValuesFrom);
+    let avf_restrictions = restrictions(allValuesFrom);
+
+    // scm-cls
+    let scm_cls = cls_assertions.flat_map(|c| {
+        vec![
+            (c, subClassOf, c),
+            (c, equivalentClass, c),
+            (c, subClassOf, Thing),
+            (Nothing, subClassOf, c),
+        ]
+        .into_iter()
+        .map(move |triple| derive(triple, "scm-cls", &[(c, r#type, Class)]))
+    });
+
+    // scm-sco
+    let scm_sco = sco_assertions_by_o.join_core(&sco_assertions_arr, |&c2, &c1, &c3| {
+        Some(derive(
+            (c1, subClassOf, c3),
+            "scm-sco",
+            &[(c1, subClassOf, c2), (c2, subClassOf, c3)],
+        ))
+    });
+
+    // scm-eqc1
+    let scm_eqc1 = eqc_assertions.flat_map(|(c1, c2)| {
+        vec![(c1, subClassOf, c2), (c2, subClassOf, c1)]
+            .into_iter()
+            .map(move |triple| derive(triple, "scm-eqc1", &[(c1, equivalentClass, c2)]))
+    });
+
+    // scm-eqc2
+    let scm_eqc2 = sco_assertions.map(|(c1, c2)| ((c2, c1), ())).join_core(
+        &sco_assertions_by_so,
+        |&(c2, c1), &(), &()| {
+            Some(derive(
+                (c1, equivalentClass, c2),
+                "scm-eqc2",
+                &[(c1, subClassOf, c2), (c2, subClassOf, c1)],
+            ))
+        },
+    );
+
+    // scm-op
+    let scm_op = op_assertions.flat_map(|p| {
+        vec![(p, subPropertyOf, p), (p, equivalentProperty, p)]
+            .into_iter()
+            .map(move |triple| derive(triple, "scm-op", &[(p, r#type, ObjectProperty)]))
+    });
+
+    // scm-spo
+    let scm_spo = spo_assertions_by_o.join_core(&spo_assertions_arr, |&p2, &p1, &p3| {
+        Some(derive(
+            (p1, subPropertyOf, p3),
+            "scm-spo",
+            &[(p1, subPropertyOf, p2), (p2, subPropertyOf, p3)],
+        ))
+    });
+
+    // scm-eqp1
+    let scm_eqp1 = eqp_assertions.flat_map(|(p1, p2)| {
+        vec![(p1, subPropertyOf, p2), (p2, subPropertyOf, p1)]
+            .into_iter()
+            .map(move |triple| derive(triple, "scm-eqp1", &[(p1, equivalentProperty, p2)]))
+    });
+
+    // scm-eqp2
+    let scm_eqp2 = spo_assertions.map(|(p1, p2)| ((p2, p1), ())).join_core(
+        &spo_assertions_by_so,
+        |&(p2, p1), &(), &()| {
+            Some(derive(
+                (p1, equivalentProperty, p2),
+                "scm-eqp2",
+                &[(p1, subPropertyOf, p2), (p2, subPropertyOf, p1)],
+            ))
+        },
+    );
+
+    // scm-dom1
+    let scm_dom1 =
+        dom_assertions
+            .map(|(p, c1)| (c1, p))
+            .join_core(&sco_assertions_arr, |&c1, &p, &c2| {
+                Some(derive(
+                    (p, domain, c2),
+                    "scm-dom1",
+                    &[(p, domain, c1), (c1, subClassOf, c2)],
+                ))
+            });
+
+    // scm-dom2
+    let scm_dom2 = dom_assertions.join_core(&spo_assertions_by_o_arr, |&p2, &c, &p1| {
+        Some(derive(
+            (p1, domain, c),
+            "scm-dom2",
+            &[(p2, domain, c), (p1, subPropertyOf, p2)],
+        ))
+    });
+
+    // scm-rng1
+    let scm_rng1 =
+        rng_assertions
+            .map(|(p, c1)| (c1, p))
+            .join_core(&sco_assertions_arr, |&c1, &p, &c2| {
+                Some(derive(
+                    (p, range, c2),
+                    "scm-rng1",
+                    &[(p, range, c1), (c1, subClassOf, c2)],
+                ))
+            });
+
+    // scm-rng2
+    let scm_rng2 = rng_assertions.join_core(&spo_assertions_by_o_arr, |&p2, &c, &p1| {
+        Some(derive(
+            (p1, range, c),
+            "scm-rng2",
+            &[(p2, range, c), (p1, subPropertyOf, p2)],
+        ))
+    });
+
+    // scm-hv
+    let scm_hv = hv_restrictions
+        .join_core(
+            &hv_restrictions.arrange_by_key(),
+            |&i, &(c1, p1), &(c2, p2)| Some(((p1, p2), (i, c1, c2))),
+        )
+        .join_core(&spo_assertions_by_so, |&(p1, p2), &(i, c1, c2), &()| {
+            Some(derive(
+                (c1, subClassOf, c2),
+                "scm-hv",
+                &[
+                    (c1, hasValue, i),
+                    (c1, onProperty, p1),
+                    (c2, hasValue, i),
+                    (c2, onProperty, p2),
+                    (p1, subPropertyOf, p2),
+                ],
+            ))
+        });
+
+    // scm-svf1 and scm-avf1, where the first restriction is to a subclass of the second's class
+    let restriction_subclasses = |restrictions: &KeyedTripleCollection<'a>| {
+        restrictions
+            .join_core(&sco_assertions_arr, |&y1, &(c1, p), &y2| {
+                Some(((y2, p), (y1, c1)))
+            })
+            .join_core(
+                &restrictions
+                    .map(|(y2, (c2, p))| ((y2, p), c2))
+                    .arrange_by_key(),
+                |&(y2, p), &(y1, c1), &c2| Some((c1, c2, p, y1, y2)),
+            )
+    };
+    let scm_svf1 = restriction_subclasses(&svf_restrictions).map(|(c1, c2, p, y1, y2)| {
+        derive(
+            (c1, subClassOf, c2),
+            "scm-svf1",
+            &[
+                (c1, someValuesFrom, y1),
+                (c1, onProperty, p),
+                (c2, someValuesFrom, y2),
+                (c2, onProperty, p),
+                (y1, subClassOf, y2),
+            ],
+        )
+    });
+    let scm_avf1 = restriction_subclasses(&avf_restrictions).map(|(c1, c2, p, y1, y2)| {
+        derive(
+            (c1, subClassOf, c2),
+            "scm-avf1",
+            &[
+                (c1, allValuesFrom, y1),
+                (c1, onProperty, p),
+                (c2, allValuesFrom, y2),
+                (c2, onProperty, p),
+                (y1, subClassOf, y2),
+            ],
+        )
+    });
+
+    // scm-svf2 and scm-avf2, where the first restriction is on a subproperty of the second's
+    let restriction_subproperties = |restrictions: &KeyedTripleCollection<'a>| {
+        restrictions
+            .join_core(
+                &restrictions.arrange_by_key(),
+                |&y, &(c1, p1), &(c2, p2)| Some(((p1, p2), (y, c1, c2))),
+            )
+            .join_core(&spo_assertions_by_so, |&(p1, p2), &(y, c1, c2), &()| {
+                Some((c1, c2, p1, p2, y))
+            })
+    };
+    let scm_svf2 = restriction_subproperties(&svf_restrictions).map(|(c1, c2, p1, p2, y)| {
+        derive(
+            (c1, subClassOf, c2),
+            "scm-svf2",
+            &[
+                (c1, someValuesFrom, y),
+                (c1, onProperty, p1),
+                (c2, someValuesFrom, y),
+                (c2, onProperty, p2),
+                (p1, subPropertyOf, p2),
+            ],
+        )
+    });
+    let scm_avf2 = restriction_subproperties(&avf_restrictions).map(|(c1, c2, p1, p2, y)| {
+        derive(
+            (c2, subClassOf, c1),
+            "scm-avf2",
+            &[
+                (c1, allValuesFrom, y),
+                (c1, onProperty, p1),
+                (c2, allValuesFrom, y),
+                (c2, onProperty, p2),
+                (p1, subPropertyOf, p2),
+            ],
+        )
+    });
+
+    // scm-int
+    let scm_int = tbox
+        .filter(|(_s, p, _o)| *p == intersectionOf)
+        .map(|(c, _p, x)| (x, c))
+        .join_core(&lists_arr, |&x, &c, list| {
+            list.iter()
+                .map(|c_i| derive((c, subClassOf, *c_i), "scm-int", &[(c, intersectionOf, x)]))
+                .collect::<Vec<_>>()
+        });
+
+    // scm-uni
+    let scm_uni = tbox
+        .filter(|(_s, p, _o)| *p == unionOf)
+        .map(|(c, _p, x)| (x, c))
+        .join_core(&lists_arr, |&x, &c, list| {
+            list.iter()
+                .map(|c_i| derive((*c_i, subClassOf, c), "scm-uni", &[(c, unionOf, x)]))
+                .collect::<Vec<_>>()
+        });
+
+    scm_cls.concatenate(vec![
+        scm_sco, scm_eqc1, scm_eqc2, scm_op, scm_spo, scm_eqp1, scm_eqp2, scm_dom1, scm_dom2,
+        scm_rng1, scm_rng2, scm_hv, scm_svf1, scm_svf2, scm_avf1, scm_avf2, scm_int, scm_uni,
+    ])
+}
+
+// The ABox rules, applied once to the abox, given the tbox, its lists and the sameAs links between
+// individuals, which may come from either box. The sameAs links that the rules derive are put out
+// apart from the rest, as the materialization keeps them along with the tbox. The eq rules are
+// only applied when expanding sameAs.
+fn owl2rl_abox_rules<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+    sas_assertions: &TupleCollection<'a>,
+    equality: Equality,
+) -> (ProvenanceCollection<'a>, ProvenanceCollection<'a>) {
+    let lists_arr = lists.arrange_by_key();
+
+    // Every element of every list, keyed by its position and the head of the list.
+    let lists_unrolled_by_i_x = lists.flat_map(|(x, list)| {
+        list.into_iter()
+            .enumerate()
+            .map(move |(i, c_i)| ((i as u32, x), c_i))
+    });
+    let lists_unrolled_by_i_c_i = lists_unrolled_by_i_x
+        .map(|((i, x), c_i)| ((i, c_i), x))
+        .arrange_by_key();
+    let lists_by_last_i_x = lists
+        .map(|(x, list)| (((list.len() - 1) as u32, x), ()))
+        .arrange_by_key();
+
+    let pairs = |property: u32| {
+        tbox.filter(move |(_s, p, _o)| *p == property)
+            .map(|(s, _p, o)| (s, o))
+    };
+    let sco_assertions = pairs(subClassOf);
+    let eqc_assertions = pairs(equivalentClass);
+    let dom_assertions = pairs(domain);
+    let rng_assertions = pairs(range);
+    let spo_assertions = pairs(subPropertyOf);
+    let eqp_assertions = pairs(equivalentProperty);
+    let inv_assertions = pairs(inverseOf);
+    let svf_assertions = pairs(someValuesFrom);
+    let avf_assertions = pairs(allValuesFrom);
+    let hv_assertions = pairs(hasValue);
+    let op_assertions = pairs(onProperty).arrange_by_key();
+    let oc_assertions = pairs(onClass).arrange_by_key();
+    let iof_assertions_by_o = pairs(intersectionOf).map(|(s, o)| (o, s)).arrange_by_key();
+    let pca_assertions_by_o = pairs(propertyChainAxiom)
+        .map(|(s, o)| (o, s))
+        .arrange_by_key();
+    let hk_assertions_by_o = pairs(hasKey).map(|(s, o)| (o, s));
+
+    let characteristic_assertions = |characteristic: u32| {
+        tbox.filter(move |(_s, p, o)| *p == r#type && *o == characteristic)
+            .map(|(s, _p, _o)| (s, ()))
+    };
+    let fp_assertions = characteristic_assertions(FunctionalProperty);
+    let ifp_assertions = characteristic_assertions(InverseFunctionalProperty);
+    let symp_assertions = characteristic_assertions(SymmetricProperty);
+    let trans_assertions = characteristic_assertions(TransitiveProperty);
+
+    let maxc_one_assertions = tbox
+        .filter(|(_s, p, o)| *p == maxCardinality && *o == cardinalityOne)
+        .map(|(s, _p, _o)| (s, ()));
+    let maxqc_one_assertions = tbox
+        .filter(|(_s, p, o)| *p == maxQualifiedCardinality && *o == cardinalityOne)
+        .map(|(s, _p, _o)| (s, ()));
+
+    let list_assertions = |list_property: u32| {
+        pairs(list_property)
+            .map(|(s, o)| (o, s))
+            .join_core(&lists_arr, |&x, &c, list| Some((c, (x, list.clone()))))
+    };
+    let iof_assertions = list_assertions(intersectionOf);
+    let uof_assertions = list_assertions(unionOf);
+    let oof_assertions = list_assertions(oneOf);
+
+    let abox_by_s = abox.map(|(s, p, o)| (s, (p, o))).arrange_by_key();
+    let abox_by_p = abox.map(|(s, p, o)| (p, (s, o))).arrange_by_key();
+    let abox_by_o = abox.map(|(s, p, o)| (o, (s, p))).arrange_by_key();
+    let abox_by_sp = abox.map(|(s, p, o)| ((s, p), o)).arrange_by_key();
+    let abox_by_po = abox.map(|(s, p, o)| ((p, o), s)).arrange_by_key();
+
+    let type_assertions = abox.filter(|(_s, p, _o)| *p == r#type);
+    let type_assertions_by_o = type_assertions.map(|(s, _p, o)| (o, s));
+    let type_assertions_by_o_arr = type_assertions_by_o.arrange_by_key();
+    let type_assertions_by_so = type_assertions.map(|(s, _p, o)| ((s, o), ()));
+    let type_assertions_by_so_arr = type_assertions_by_so.arrange_by_key();
+
+    let property_assertions = abox.filter(|(_s, p, _o)| *p != r#type);
+    let property_assertions_by_sp = property_assertions.map(|(s, p, o)| ((s, p), o));
+
+    let sas_assertions_arr = sas_assertions.arrange_by_key();
+
+    // eq-sym
+    let eq_sym = sas_assertions.map(|(x, y)| derive((y, sameAs, x), "eq-sym", &[(x, sameAs, y)]));
+
+    // eq-trans
+    let eq_trans =
+        sas_assertions
+            .map(|(x, y)| (y, x))
+            .join_core(&sas_assertions_arr, |&y, &x, &z| {
+                Some(derive(
+                    (x, sameAs, z),
+                    "eq-trans",
+                    &[(x, sameAs, y), (y, sameAs, z)],
+                ))
+            });
+
+    // eq-rep-s
+    let eq_rep_s = sas_assertions.join_core(&abox_by_s, |&s, &s_prime, &(p, o)| {
+        Some(derive(
+            (s_prime, p, o),
+            "eq-rep-s",
+            &[(s, sameAs, s_prime), (s, p, o)],
+        ))
+    });
+
+    // eq-rep-p
+    let eq_rep_p = sas_assertions.join_core(&abox_by_p, |&p, &p_prime, &(s, o)| {
+        Some(derive(
+            (s, p_prime, o),
+            "eq-rep-p",
+            &[(p, sameAs, p_prime), (s, p, o)],
+        ))
+    });
+
+    // eq-rep-o
+    let eq_rep_o = sas_assertions.join_core(&abox_by_o, |&o, &o_prime, &(s, p)| {
+        Some(derive(
+            (s, p, o_prime),
+            "eq-rep-o",
+            &[(o, sameAs, o_prime), (s, p, o)],
+        ))
+    });
+
+    // prp-dom
+    let prp_dom = dom_assertions.join_core(&abox_by_p, |&p, &c, &(x, y)| {
+        Some(derive(
+            (x, r#type, c),
+            "prp-dom",
+            &[(p, domain, c), (x, p, y)],
+        ))
+    });
+
+    // prp-rng
+    let prp_rng = rng_assertions.join_core(&abox_by_p, |&p, &c, &(x, y)| {
+        Some(derive(
+            (y, r#type, c),
+            "prp-rng",
+            &[(p, range, c), (x, p, y)],
+        ))
+    });
+
+    // prp-fp
+    let prp_fp = fp_assertions
+        .join_core(&abox_by_p, |&p, &(), &(x, y1)| Some(((x, p), y1)))
+        .join_core(&abox_by_sp, |&(x, p), &y1, &y2| {
+            if y1 == y2 {
+                return None;
+            }
+            Some(derive(
+                (y1, sameAs, y2),
+                "prp-fp",
+                &[(p, r#type, FunctionalProperty), (x, p, y1), (x, p, y2)],
+            ))
+        });
+
+    // prp-ifp
+    let prp_ifp = ifp_assertions
+        .join_core(&abox_by_p, |&p, &(), &(x1, y)| Some(((p, y), x1)))
+        .join_core(&abox_by_po, |&(p, y), &x1, &x2| {
+            if x1 == x2 {
+                return None;
+            }
+            Some(derive(
+                (x1, sameAs, x2),
+                "prp-ifp",
+                &[
+                    (p, r#type, InverseFunctionalProperty),
+                    (x1, p, y),
+                    (x2, p, y),
+                ],
+            ))
+        });
+
+    // prp-symp
+    let prp_symp = symp_assertions.join_core(&abox_by_p, |&p, &(), &(x, y)| {
+        Some(derive(
+            (y, p, x),
+            "prp-symp",
+            &[(p, r#type, SymmetricProperty), (x, p, y)],
+        ))
+    });
+
+    // prp-trp
+    let prp_trp = trans_assertions
+        .join_core(&abox_by_p, |&p, &(), &(x, y)| Some(((y, p), x)))
+        .join_core(&abox_by_sp, |&(y, p), &x, &z| {
+            Some(derive(
+                (x, p, z),
+                "prp-trp",
+                &[(p, r#type, TransitiveProperty), (x, p, y), (y, p, z)],
+            ))
+        });
+
+    // prp-spo1
+    let prp_spo1 = spo_assertions.join_core(&abox_by_p, |&p1, &p2, &(x, y)| {
+        Some(derive(
+            (x, p2, y),
+            "prp-spo1",
+            &[(p1, subPropertyOf, p2), (x, p1, y)],
+        ))
+    });
+
+    // prp-spo2, walking down each property chain one link at a time, along with the assertions
+    // met on the way.
+    let prp_spo2_step_one = property_assertions
+        .map(|(u_0, p_0, u_1)| ((0u32, p_0), (u_0, u_1)))
+        .join_core(&lists_unrolled_by_i_c_i, |&(i, p_0), &(u_0, u_1), &x| {
+            Some(((i, x), (u_0, u_1, vec![(u_0, p_0, u_1)])))
+        });
+
+    let prp_spo2 = abox
+        .scope()
+        .iterative::<usize, _, _>(|inner| {
+            let chains_var = Variable::new_from(
+                prp_spo2_step_one.enter(inner),
+                Product::new(Default::default(), 1),
+            );
+
+            let chains_new = chains_var.distinct();
+
+            let lists_by_i_x = lists_unrolled_by_i_x.enter(inner).arrange_by_key();
+
+            let property_assertions_by_sp = property_assertions_by_sp.enter(inner).arrange_by_key();
+
+            let chains_next = chains_new
+                .map(|((i, x), chain)| ((i + 1, x), chain))
+                .join_core(&lists_by_i_x, |&(i, x), (u_0, u_i, premises), &p_i| {
+                    Some(((*u_i, p_i), (i, x, *u_0, premises.clone())))
+                })
                 .join_core(
-                    &pca_assertions_by_o.enter(&inner),
-                    |&_x, &(u_i, u_k), &p| Some((u_i, p, u_k)),
+                    &property_assertions_by_sp,
+                    |&(u_i, p_i), (i, x, u_0, premises), &u_j| {
+                        Some((
+                            (*i, *x),
+                            (
+                                *u_0,
+                                u_j,
+                                [premises.as_slice(), &[(u_i, p_i, u_j)]].concat(),
+                            ),
+                        ))
+                    },
                 );
 
-            // prp-eqp1
+            chains_var.set(&prp_spo2_step_one.enter(inner).concat(&chains_next));
 
-            let prp_eqp1 =
-                eqp_assertions.join_core(&abox_by_p, |&_p1, &p2, &(x, y)| Some((x, p2, y)));
+            chains_new.leave()
+        })
+        .join_core(&lists_by_last_i_x, |&(_i, x), (u_0, u_n, premises), &()| {
+            Some((x, (*u_0, *u_n, premises.clone())))
+        })
+        .join_core(&pca_assertions_by_o, |&x, (u_0, u_n, premises), &p| {
+            Some(derive_chained(
+                (*u_0, p, *u_n),
+                "prp-spo2",
+                &[(p, propertyChainAxiom, x)],
+                premises,
+            ))
+        });
 
-            // prp-eqp2
+    // prp-eqp1
+    let prp_eqp1 = eqp_assertions.join_core(&abox_by_p, |&p1, &p2, &(x, y)| {
+        Some(derive(
+            (x, p2, y),
+            "prp-eqp1",
+            &[(p1, equivalentProperty, p2), (x, p1, y)],
+        ))
+    });
 
-            let prp_eqp2 = eqp_assertions
-                .map(|(p1, p2)| (p2, p1))
-                .join_core(&abox_by_p, |&_p2, &p1, &(x, y)| Some((x, p1, y)));
+    // prp-eqp2
+    let prp_eqp2 =
+        eqp_assertions
+            .map(|(p1, p2)| (p2, p1))
+            .join_core(&abox_by_p, |&p2, &p1, &(x, y)| {
+                Some(derive(
+                    (x, p1, y),
+                    "prp-eqp2",
+                    &[(p1, equivalentProperty, p2), (x, p2, y)],
+                ))
+            });
 
-            // prp-inv1
+    // prp-inv1
+    let prp_inv1 = inv_assertions.join_core(&abox_by_p, |&p1, &p2, &(x, y)| {
+        Some(derive(
+            (y, p2, x),
+            "prp-inv1",
+            &[(p1, inverseOf, p2), (x, p1, y)],
+        ))
+    });
 
-            let prp_inv1 =
-                inv_assertions.join_core(&abox_by_p, |&_p1, &p2, &(x, y)| Some((y, p2, x)));
+    // prp-inv2
+    let prp_inv2 =
+        inv_assertions
+            .map(|(p1, p2)| (p2, p1))
+            .join_core(&abox_by_p, |&p2, &p1, &(x, y)| {
+                Some(derive(
+                    (y, p1, x),
+                    "prp-inv2",
+                    &[(p1, inverseOf, p2), (x, p2, y)],
+                ))
+            });
 
-            // prp-inv2
+    // prp-key
 
-            let prp_inv2 = inv_assertions
-                .map(|(p1, p2)| (p2, p1))
-                .join_core(&abox_by_p, |&_p2, &p1, &(x, y)| Some((y, p1, x)));
+    // Each hasKey axiom is told apart by its list, so that the keys of a class with more than one
+    // of them are not mixed up.
+    let hk_keys_by_c = hk_assertions_by_o
+        .join_core(&lists_arr, |&list, &c, keys| {
+            keys.iter()
+                .enumerate()
+                .map(|(i, p_i)| (c, (list, i as u32, *p_i)))
+                .collect::<Vec<_>>()
+        })
+        .arrange_by_key();
 
-            let prp_abox = prp_dom.concatenate(vec![
-                prp_rng, prp_symp, prp_trp, prp_spo1, prp_spo2, prp_eqp1, prp_eqp2, prp_inv1,
-                prp_inv2,
-            ]);
+    let hk_sizes = hk_assertions_by_o
+        .join_core(&lists_arr, |&list, &c, keys| {
+            Some(((c, list, keys.len()), ()))
+        })
+        .arrange_by_key();
 
-            let prp_tbox = prp_fp.concat(&prp_ifp);
+    let prp_key_step_one = type_assertions_by_o
+        .join_core(&hk_keys_by_c, |&c, &x, &(list, i, p_i)| {
+            Some(((x, p_i), (c, list, i)))
+        })
+        .join_core(&abox_by_sp, |&(x, p_i), &(c, list, i), &z| {
+            Some(((c, list, i, z), (x, p_i)))
+        });
+
+    // Two individuals are the same once they agree on every key of one of the axioms of their
+    // class, which stays incremental, as a changed key value takes back its agreement.
+    let prp_key = prp_key_step_one
+        .join_core(
+            &prp_key_step_one.arrange_by_key(),
+            |&(c, list, i, z), &(x, p_i), &(y, _p_i)| {
+                if x == y {
+                    return None;
+                }
+                Some(((c, list, x, y), (i, vec![(x, p_i, z), (y, p_i, z)])))
+            },
+        )
+        .reduce(|_individuals, agreements, output| {
+            // The agreements come sorted by key, each key with one or more values both agree on,
+            // of which the first is kept.
+            let mut keys = 0usize;
+            let mut premises = Vec::new();
+            let mut last = None;
+            for ((i, agreement), _count) in agreements {
+                if last != Some(*i) {
+                    keys += 1;
+                    premises.extend_from_slice(agreement);
+                    last = Some(*i);
+                }
+            }
+            output.push(((keys, premises), 1));
+        })
+        .map(|((c, list, x, y), (keys, premises))| ((c, list, keys), (x, y, premises)))
+        .join_core(&hk_sizes, |&(c, list, _n), (x, y, premises), &()| {
+            Some(derive_chained(
+                (*x, sameAs, *y),
+                "prp-key",
+                &[(c, hasKey, list), (*x, r#type, c), (*y, r#type, c)],
+                premises,
+            ))
+        });
 
-            // cls-int1
+    // cax-sco
+    let cax_sco = sco_assertions.join_core(&type_assertions_by_o_arr, |&c1, &c2, &x| {
+        Some(derive(
+            (x, r#type, c2),
+            "cax-sco",
+            &[(c1, subClassOf, c2), (x, r#type, c1)],
+        ))
+    });
 
-            let cls_int1_step_one = type_assertions_by_o.map(|(o, s)| ((0, o), s)).join_core(
-                &lists_unrolled_by_i_and_o.arrange_by_key(),
-                |&(i, _c_i), &y, &x| Some(((i as u32, x), y)),
-            );
+    // cax-eqc1
+    let cax_eqc1 = eqc_assertions.join_core(&type_assertions_by_o_arr, |&c1, &c2, &x| {
+        Some(derive(
+            (x, r#type, c2),
+            "cax-eqc1",
+            &[(c1, equivalentClass, c2), (x, r#type, c1)],
+        ))
+    });
 
-            let cls_int1 = inner
-                .iterative::<usize, _, _>(|inner_squared| {
-                    let type_assertions_var = Variable::new_from(
-                        cls_int1_step_one.enter(inner_squared),
-                        Product::new(Default::default(), 1),
-                    );
+    // cax-eqc2
+    let cax_eqc2 = eqc_assertions.map(|(c1, c2)| (c2, c1)).join_core(
+        &type_assertions_by_o_arr,
+        |&c2, &c1, &x| {
+            Some(derive(
+                (x, r#type, c1),
+                "cax-eqc2",
+                &[(c1, equivalentClass, c2), (x, r#type, c2)],
+            ))
+        },
+    );
+
+    // cls-svf1 and cls-svf2
+    let cls_svf = svf_assertions
+        .join_core(&op_assertions, |&x, &y, &p| Some((p, (x, y))))
+        .join_core(&abox_by_p, |&p, &(x, y), &(u, v)| Some(((v, y), (x, p, u))));
+    let cls_svf1 = cls_svf.filter(|((_v, y), _rest)| *y != Thing).join_core(
+        &type_assertions_by_so_arr,
+        |&(v, y), &(x, p, u), &()| {
+            Some(derive(
+                (u, r#type, x),
+                "cls-svf1",
+                &[
+                    (x, someValuesFrom, y),
+                    (x, onProperty, p),
+                    (u, p, v),
+                    (v, r#type, y),
+                ],
+            ))
+        },
+    );
+    let cls_svf2 = cls_svf
+        .filter(|((_v, y), _rest)| *y == Thing)
+        .map(|((v, y), (x, p, u))| {
+            derive(
+                (u, r#type, x),
+                "cls-svf2",
+                &[(x, someValuesFrom, y), (x, onProperty, p), (u, p, v)],
+            )
+        });
 
-                    let lists_by_i_x = lists_unrolled_by_i_and_o
-                        .enter(&inner_squared)
-                        .map(|((i, c_i), x)| ((i, x), c_i))
-                        .arrange_by_key();
+    // cls-avf
+    let cls_avf = avf_assertions
+        .join_core(&op_assertions, |&x, &y, &p| Some((x, (p, y))))
+        .join_core(&type_assertions_by_o_arr, |&x, &(p, y), &u| {
+            Some(((u, p), (x, y)))
+        })
+        .join_core(&abox_by_sp, |&(u, p), &(x, y), &v| {
+            Some(derive(
+                (v, r#type, y),
+                "cls-avf",
+                &[
+                    (x, allValuesFrom, y),
+                    (x, onProperty, p),
+                    (u, r#type, x),
+                    (u, p, v),
+                ],
+            ))
+        });
 
-                    let type_assertions_new = type_assertions_var.distinct();
+    let hv_restrictions = hv_assertions.join_core(&op_assertions, |&x, &y, &p| Some((x, (p, y))));
 
-                    let type_assertions_by_so =
-                        type_assertions_by_so.enter(inner_squared).arrange_by_key();
+    // cls-hv1
+    let cls_hv1 = hv_restrictions.join_core(&type_assertions_by_o_arr, |&x, &(p, y), &u| {
+        Some(derive(
+            (u, p, y),
+            "cls-hv1",
+            &[(x, hasValue, y), (x, onProperty, p), (u, r#type, x)],
+        ))
+    });
 
-                    let type_assertions_new = type_assertions_new
-                        .map(|((i, x), y)| ((i + 1, x), y))
-                        .join_core(&lists_by_i_x, |&(i, x), &y, &c_i| Some(((c_i, y), (i, x))))
-                        .join_core(&type_assertions_by_so, |&(_c_i, y), &(i, x), &_y| {
-                            Some(((i, x), y))
-                        })
-                        .concat(&type_assertions_new)
-                        .distinct();
+    // cls-hv2
+    let cls_hv2 =
+        hv_restrictions
+            .map(|(x, (p, y))| ((p, y), x))
+            .join_core(&abox_by_po, |&(p, y), &x, &u| {
+                Some(derive(
+                    (u, r#type, x),
+                    "cls-hv2",
+                    &[(x, hasValue, y), (x, onProperty, p), (u, p, y)],
+                ))
+            });
 
-                    type_assertions_var.set(&type_assertions_new);
+    // cls-int1, checking the classes of each intersection one at a time, along with the type
+    // assertions met on the way.
+    let cls_int1_step_one = type_assertions_by_o
+        .map(|(c_0, y)| ((0u32, c_0), y))
+        .join_core(&lists_unrolled_by_i_c_i, |&(i, c_0), &y, &x| {
+            Some(((i, x), (y, vec![(y, r#type, c_0)])))
+        });
 
-                    type_assertions_new.leave()
-                })
-                .join_core(&lists_unrolled_size.enter(inner), |&(_i, x), &y, &_| {
-                    Some((x, y))
+    let cls_int1 = abox
+        .scope()
+        .iterative::<usize, _, _>(|inner| {
+            let members_var = Variable::new_from(
+                cls_int1_step_one.enter(inner),
+                Product::new(Default::default(), 1),
+            );
+
+            let members_new = members_var.distinct();
+
+            let lists_by_i_x = lists_unrolled_by_i_x.enter(inner).arrange_by_key();
+
+            let type_assertions_by_so = type_assertions_by_so.enter(inner).arrange_by_key();
+
+            let members_next = members_new
+                .map(|((i, x), member)| ((i + 1, x), member))
+                .join_core(&lists_by_i_x, |&(i, x), (y, premises), &c_i| {
+                    Some(((*y, c_i), (i, x, premises.clone())))
                 })
-                .join_core(&iof_assertions_by_o.enter(&inner), |&_x, &y, &c| {
-                    Some((y, r#type, c))
-                });
+                .join_core(
+                    &type_assertions_by_so,
+                    |&(y, c_i), (i, x, premises), &()| {
+                        Some((
+                            (*i, *x),
+                            (y, [premises.as_slice(), &[(y, r#type, c_i)]].concat()),
+                        ))
+                    },
+                );
 
-            // cls-svf1
-            let cls_svf1_step_one =
-                svf_assertions.join_core(&op_assertions, |&x, &y, &p| Some((p, (y, x))));
+            members_var.set(&cls_int1_step_one.enter(inner).concat(&members_next));
 
-            let cls_svf1_step_two = cls_svf1_step_one
-                .join_core(&abox_by_p, |&_p, &(y, x), &(u, v)| Some(((v, y), (u, x))));
+            members_new.leave()
+        })
+        .join_core(&lists_by_last_i_x, |&(_i, x), (y, premises), &()| {
+            Some((x, (*y, premises.clone())))
+        })
+        .join_core(&iof_assertions_by_o, |&x, (y, premises), &c| {
+            Some(derive_chained(
+                (*y, r#type, c),
+                "cls-int1",
+                &[(c, intersectionOf, x)],
+                premises,
+            ))
+        });
 
-            let cls_svf1 = cls_svf1_step_two
-                .join_core(&abox_by_so, |&(_v, _y), &(u, x), &_| Some((u, r#type, x)));
+    // cls-int2
+    let cls_int2 = iof_assertions.join_core(&type_assertions_by_o_arr, |&c, (x, list), &y| {
+        list.iter()
+            .map(|c_i| {
+                derive(
+                    (y, r#type, *c_i),
+                    "cls-int2",
+                    &[(c, intersectionOf, *x), (y, r#type, c)],
+                )
+            })
+            .collect::<Vec<_>>()
+    });
 
-            // cls-svf2
-            let cls_svf2_step_one = svf_assertions
-                .filter(|(_s, o)| *o == Thing)
-                .join_core(&op_assertions, |&x, _, &p| Some((p, x)));
+    // cls-uni
+    let cls_uni = uof_assertions
+        .flat_map(|(c, (x, list))| list.into_iter().map(move |c_i| (c_i, (c, x))))
+        .join_core(&type_assertions_by_o_arr, |&c_i, &(c, x), &y| {
+            Some(derive(
+                (y, r#type, c),
+                "cls-uni",
+                &[(c, unionOf, x), (y, r#type, c_i)],
+            ))
+        });
 
-            let cls_svf2 =
-                cls_svf2_step_one.join_core(&abox_by_p, |&_p, &x, &(u, _v)| Some((u, r#type, x)));
+    // cls-oo
+    let cls_oo = oof_assertions.flat_map(|(c, (x, list))| {
+        list.into_iter()
+            .map(move |y| derive((y, r#type, c), "cls-oo", &[(c, oneOf, x)]))
+    });
 
-            // cls-avf
-            let cls_avf_step_one =
-                avf_assertions.join_core(&op_assertions, |&x, &y, &p| Some((x, (p, y))));
+    // cls-maxc2 and cls-maxqc4, as membership in owl:Thing is not materialized, so restrictions
+    // qualified by it are handled as unqualified ones.
+    let cls_maxc2 = maxc_one_assertions
+        .join_core(&op_assertions, |&x, &(), &p| {
+            Some((x, (p, maxCardinality, Thing)))
+        })
+        .concat(
+            &maxqc_one_assertions
+                .join_core(&op_assertions, |&x, &(), &p| Some((x, p)))
+                .join_core(&oc_assertions, |&x, &p, &c| {
+                    if c == Thing {
+                        Some((x, (p, maxQualifiedCardinality, c)))
+                    } else {
+                        None
+                    }
+                }),
+        )
+        .join_core(&type_assertions_by_o_arr, |&x, &(p, cardinality, c), &u| {
+            Some(((u, p), (x, cardinality, c)))
+        })
+        .join_core(&abox_by_sp, |&(u, p), &(x, cardinality, c), &y1| {
+            Some(((u, p), (x, cardinality, c, y1)))
+        })
+        .join_core(&abox_by_sp, |&(u, p), &(x, cardinality, c, y1), &y2| {
+            if y1 == y2 {
+                return None;
+            }
+            let mut premises = vec![(x, cardinality, cardinalityOne), (x, onProperty, p)];
+            let rule = if cardinality == maxCardinality {
+                "cls-maxc2"
+            } else {
+                premises.push((x, onClass, c));
+                "cls-maxqc4"
+            };
+            premises.extend([(u, r#type, x), (u, p, y1), (u, p, y2)]);
+            Some(derive((y1, sameAs, y2), rule, &premises))
+        });
 
-            let cls_avf_step_two = cls_avf_step_one
-                .join_core(&type_assertions_by_o_arr, |&_x, &(p, y), &u| {
-                    Some(((u, p), y))
-                });
+    // cls-maxqc3
+    let cls_maxqc3_step_one = maxqc_one_assertions
+        .join_core(&op_assertions, |&x, &(), &p| Some((x, p)))
+        .join_core(&oc_assertions, |&x, &p, &c| {
+            if c != Thing {
+                Some((x, (p, c)))
+            } else {
+                None
+            }
+        })
+        .join_core(&type_assertions_by_o_arr, |&x, &(p, c), &u| {
+            Some(((u, p), (x, c)))
+        })
+        .join_core(&abox_by_sp, |&(u, p), &(x, c), &y| {
+            Some(((y, c), (x, p, u)))
+        })
+        .join_core(&type_assertions_by_so_arr, |&(y, c), &(x, p, u), &()| {
+            Some(((x, p, c, u), y))
+        });
+    let cls_maxqc3 = cls_maxqc3_step_one.join_core(
+        &cls_maxqc3_step_one.arrange_by_key(),
+        |&(x, p, c, u), &y1, &y2| {
+            if y1 == y2 {
+                return None;
+            }
+            Some(derive(
+                (y1, sameAs, y2),
+                "cls-maxqc3",
+                &[
+                    (x, maxQualifiedCardinality, cardinalityOne),
+                    (x, onProperty, p),
+                    (x, onClass, c),
+                    (u, r#type, x),
+                    (u, p, y1),
+                    (y1, r#type, c),
+                    (u, p, y2),
+                    (y2, r#type, c),
+                ],
+            ))
+        },
+    );
+
+    // cls-thing and cls-nothing1
+    let cls_thing = abox
+        .scope()
+        .new_collection_from(vec![
+            derive((Thing, r#type, Class), "cls-thing", &[]),
+            derive((Nothing, r#type, Class), "cls-nothing1", &[]),
+        ])
+        .1;
+
+    let mut abox_rules = vec![
+        cax_eqc1, cax_eqc2, prp_dom, prp_rng, prp_symp, prp_trp, prp_spo1, prp_spo2, prp_eqp1,
+        prp_eqp2, prp_inv1, prp_inv2, cls_svf1, cls_svf2, cls_avf, cls_hv1, cls_hv2, cls_int1,
+        cls_int2, cls_uni, cls_oo, cls_thing,
+    ];
+    let mut sas_rules = vec![prp_ifp, prp_key, cls_maxc2, cls_maxqc3];
+    if let Equality::Expansion = equality {
+        abox_rules.extend([eq_rep_s, eq_rep_p, eq_rep_o]);
+        sas_rules.extend([eq_sym, eq_trans]);
+    }
+
+    (
+        cax_sco.concatenate(abox_rules),
+        prp_fp.concatenate(sas_rules),
+    )
+}
 
-            let cls_avf =
-                cls_avf_step_two.join_core(&abox_by_sp, |&(_u, _p), &y, &v| Some((v, r#type, y)));
+// Every single-step derivation of a triple of the materialization, by the same rules, over the
+// materialization itself.
+pub fn owl2rl_provenance<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    let sas_assertions = tbox
+        .concat(abox)
+        .filter(|(_s, p, _o)| *p == sameAs)
+        .map(|(s, _p, o)| (s, o))
+        .distinct();
+    let (abox_derivations, sas_derivations) =
+        owl2rl_abox_rules(tbox, lists, abox, &sas_assertions, Equality::Expansion);
+    justified(&abox_derivations.concat(&sas_derivations))
+}
 
-            // cls-hv1
-            let cls_hv1_step_one =
-                hv_assertions.join_core(&op_assertions, |&x, &y, &p| Some((x, (p, y))));
+// Every single-step derivation of a triple of the tbox materialization.
+pub fn owl2rl_tbox_provenance<'a>(tbox: &TripleCollection<'a>) -> ProvenanceCollection<'a> {
+    justified(&owl2rl_tbox_rules(tbox, &expand_lists(tbox)))
+}
 
-            let cls_hv1 = cls_hv1_step_one
-                .join_core(&type_assertions_by_o_arr, |&_x, &(p, y), &u| {
-                    Some((u, p, y))
-                });
+// The rules that could derive each requested triple, out of the abox stage, with eq-sym and
+// eq-trans, which only apply with equality expanded. Those that derive from the tbox alone, such
+// as cls-thing, come with no premises. The eq-rep ones are left out, as any triple could be derived
+// from a copy of itself, and would have them as candidates.
+pub fn owl2rl_why_not<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    requests: &ExplainRequestCollection<'a>,
+) -> WhyNotCollection<'a> {
+    let by_s = |property: u32| {
+        tbox.filter(move |(_s, p, _o)| *p == property)
+            .map(|(s, _p, o)| (s, o))
+    };
+    let by_o = |property: u32| {
+        tbox.filter(move |(_s, p, _o)| *p == property)
+            .map(|(s, _p, o)| (o, s))
+    };
+    let characteristic_assertions = |characteristic: u32| {
+        tbox.filter(move |(_s, p, o)| *p == r#type && *o == characteristic)
+            .map(|(s, _p, _o)| (s, ()))
+            .arrange_by_key()
+    };
+    let sco_assertions_by_o = by_o(subClassOf).arrange_by_key();
+    let eqc_assertions_arr = by_s(equivalentClass).arrange_by_key();
+    let eqc_assertions_by_o = by_o(equivalentClass).arrange_by_key();
+    let dom_assertions_by_o = by_o(domain).arrange_by_key();
+    let rng_assertions_by_o = by_o(range).arrange_by_key();
+    let spo_assertions_by_o = by_o(subPropertyOf).arrange_by_key();
+    let eqp_assertions_arr = by_s(equivalentProperty).arrange_by_key();
+    let eqp_assertions_by_o = by_o(equivalentProperty).arrange_by_key();
+    let inv_assertions_arr = by_s(inverseOf).arrange_by_key();
+    let inv_assertions_by_o = by_o(inverseOf).arrange_by_key();
+    let symp_assertions = characteristic_assertions(SymmetricProperty);
+    let trans_assertions = characteristic_assertions(TransitiveProperty);
+
+    // Restrictions, keyed by the class or individual they restrict to, with the property and the
+    // restriction itself.
+    let op_assertions_arr = by_s(onProperty).arrange_by_key();
+    let restrictions = |restriction: u32| {
+        by_s(restriction).join_core(&op_assertions_arr, |&x, &y, &p| Some((y, (p, x))))
+    };
+    let svf_restrictions = restrictions(someValuesFrom);
+    let avf_restrictions = restrictions(allValuesFrom);
+    let hv_restrictions = restrictions(hasValue);
+
+    let lists_arr = lists.arrange_by_key();
+    let list_assertions = |list_property: u32| {
+        by_o(list_property).join_core(&lists_arr, |&x, &c, list| Some((c, (x, list.clone()))))
+    };
+
+    // The properties that tie individuals together, and the restrictions to at most one value,
+    // keyed by nothing, as any sameAs request may be derived from them.
+    let unkeyed_properties = |characteristic: u32| {
+        tbox.filter(move |(_s, p, o)| *p == r#type && *o == characteristic)
+            .map(|(p, _p, _o)| ((), p))
+            .arrange_by_key()
+    };
+    let fp_properties = unkeyed_properties(FunctionalProperty);
+    let ifp_properties = unkeyed_properties(InverseFunctionalProperty);
+    let oc_assertions_arr = by_s(onClass).arrange_by_key();
+    let max_one_restrictions = |cardinality: u32| {
+        tbox.filter(move |(_s, p, o)| *p == cardinality && *o == cardinalityOne)
+            .map(|(x, _p, _o)| (x, ()))
+            .join_core(&op_assertions_arr, |&x, &(), &p| Some((x, p)))
+    };
+
+    let type_requests = requests
+        .filter(|(_request, (_x, p, _c))| *p == r#type)
+        .map(|(request, (x, _p, c))| (c, (request, x)));
+    let property_requests = requests
+        .filter(|(_request, (_x, p, _y))| *p != r#type)
+        .map(|(request, (x, p, y))| (p, (request, x, y)));
+    let sas_requests = requests
+        .filter(|(_request, (_x, p, _y))| *p == sameAs)
+        .map(|(request, (x, _p, y))| ((), (request, x, y)));
+
+    // cax-sco
+    let cax_sco = type_requests.join_core(&sco_assertions_by_o, |&c2, &(request, x), &c1| {
+        if c1 != c2 {
+            Some((
+                request,
+                (x, r#type, c2),
+                "cax-sco".to_string(),
+                vec![(c1, subClassOf, c2)],
+                vec![(constant(x), constant(r#type), constant(c1))],
+            ))
+        } else {
+            None
+        }
+    });
 
-            // cls-hv2
-            let cls_hv2_step_one =
-                hv_assertions.join_core(&op_assertions, |&x, &y, &p| Some(((p, y), x)));
+    // cax-eqc1
+    let cax_eqc1 = type_requests.join_core(&eqc_assertions_by_o, |&c2, &(request, x), &c1| {
+        if c1 != c2 {
+            Some((
+                request,
+                (x, r#type, c2),
+                "cax-eqc1".to_string(),
+                vec![(c1, equivalentClass, c2)],
+                vec![(constant(x), constant(r#type), constant(c1))],
+            ))
+        } else {
+            None
+        }
+    });
+
+    // cax-eqc2
+    let cax_eqc2 = type_requests.join_core(&eqc_assertions_arr, |&c1, &(request, x), &c2| {
+        if c1 != c2 {
+            Some((
+                request,
+                (x, r#type, c1),
+                "cax-eqc2".to_string(),
+                vec![(c1, equivalentClass, c2)],
+                vec![(constant(x), constant(r#type), constant(c2))],
+            ))
+        } else {
+            None
+        }
+    });
 
-            let cls_hv2 =
-                cls_hv2_step_one.join_core(&abox_by_po, |&(_p, _y), &x, &u| Some((u, r#type, x)));
+    // prp-dom
+    let prp_dom = type_requests.join_core(&dom_assertions_by_o, |&c, &(request, x), &p| {
+        Some((
+            request,
+            (x, r#type, c),
+            "prp-dom".to_string(),
+            vec![(p, domain, c)],
+            vec![(constant(x), constant(p), variable(0))],
+        ))
+    });
 
-            // cls-oo
+    // prp-rng
+    let prp_rng = type_requests.join_core(&rng_assertions_by_o, |&c, &(request, x), &p| {
+        Some((
+            request,
+            (x, r#type, c),
+            "prp-rng".to_string(),
+            vec![(p, range, c)],
+            vec![(variable(0), constant(p), constant(x))],
+        ))
+    });
 
-            let cls_oo = oof_assertions_by_o
-                .join_core(&lists_arr.enter(&inner), |&_x, &c, list| {
-                    Some((c, list.clone()))
+    // cls-svf1 and cls-svf2
+    let cls_svf = svf_restrictions.map(|(y, (p, x))| (x, (p, y))).join_core(
+        &type_requests.arrange_by_key(),
+        |&x, &(p, y), &(request, u)| {
+            let given = vec![(x, someValuesFrom, y), (x, onProperty, p)];
+            if y == Thing {
+                Some((
+                    request,
+                    (u, r#type, x),
+                    "cls-svf2".to_string(),
+                    given,
+                    vec![(constant(u), constant(p), variable(0))],
+                ))
+            } else {
+                Some((
+                    request,
+                    (u, r#type, x),
+                    "cls-svf1".to_string(),
+                    given,
+                    vec![
+                        (constant(u), constant(p), variable(0)),
+                        (variable(0), constant(r#type), constant(y)),
+                    ],
+                ))
+            }
+        },
+    );
+
+    // cls-avf
+    let cls_avf = type_requests.join_core(
+        &avf_restrictions.arrange_by_key(),
+        |&y, &(request, v), &(p, x)| {
+            Some((
+                request,
+                (v, r#type, y),
+                "cls-avf".to_string(),
+                vec![(x, allValuesFrom, y), (x, onProperty, p)],
+                vec![
+                    (variable(0), constant(r#type), constant(x)),
+                    (variable(0), constant(p), constant(v)),
+                ],
+            ))
+        },
+    );
+
+    // cls-hv1
+    let cls_hv1 = hv_restrictions.map(|(y, (p, x))| (p, (x, y))).join_core(
+        &property_requests.arrange_by_key(),
+        |&p, &(x, y), &(request, u, v)| {
+            if y == v {
+                Some((
+                    request,
+                    (u, p, v),
+                    "cls-hv1".to_string(),
+                    vec![(x, hasValue, y), (x, onProperty, p)],
+                    vec![(constant(u), constant(r#type), constant(x))],
+                ))
+            } else {
+                None
+            }
+        },
+    );
+
+    // cls-hv2
+    let cls_hv2 = hv_restrictions.map(|(y, (p, x))| (x, (p, y))).join_core(
+        &type_requests.arrange_by_key(),
+        |&x, &(p, y), &(request, u)| {
+            Some((
+                request,
+                (u, r#type, x),
+                "cls-hv2".to_string(),
+                vec![(x, hasValue, y), (x, onProperty, p)],
+                vec![(constant(u), constant(p), constant(y))],
+            ))
+        },
+    );
+
+    // cls-int1
+    let cls_int1 = list_assertions(intersectionOf).join_core(
+        &type_requests.arrange_by_key(),
+        |&c, (x, list), &(request, y)| {
+            Some((
+                request,
+                (y, r#type, c),
+                "cls-int1".to_string(),
+                vec![(c, intersectionOf, *x)],
+                list.iter()
+                    .map(|c_i| (constant(y), constant(r#type), constant(*c_i)))
+                    .collect(),
+            ))
+        },
+    );
+
+    // cls-uni
+    let cls_uni = list_assertions(unionOf).join_core(
+        &type_requests.arrange_by_key(),
+        |&c, (x, list), &(request, y)| {
+            list.iter()
+                .map(|c_i| {
+                    (
+                        request,
+                        (y, r#type, c),
+                        "cls-uni".to_string(),
+                        vec![(c, unionOf, *x)],
+                        vec![(constant(y), constant(r#type), constant(*c_i))],
+                    )
                 })
-                .flat_map(|(c, list)| {
-                    list.iter()
-                        .map(|y_x| (*y_x, r#type, c))
-                        .collect::<Vec<Triple>>()
-                });
+                .collect::<Vec<_>>()
+        },
+    );
+
+    // prp-symp
+    let prp_symp = property_requests.join_core(&symp_assertions, |&p, &(request, x, y), &()| {
+        Some((
+            request,
+            (x, p, y),
+            "prp-symp".to_string(),
+            vec![(p, r#type, SymmetricProperty)],
+            vec![(constant(y), constant(p), constant(x))],
+        ))
+    });
 
-            let cls =
-                cls_svf1.concatenate(vec![cls_int1, cls_svf2, cls_avf, cls_hv1, cls_hv2, cls_oo]);
+    // prp-trp
+    let prp_trp = property_requests.join_core(&trans_assertions, |&p, &(request, x, z), &()| {
+        Some((
+            request,
+            (x, p, z),
+            "prp-trp".to_string(),
+            vec![(p, r#type, TransitiveProperty)],
+            vec![
+                (constant(x), constant(p), variable(0)),
+                (variable(0), constant(p), constant(z)),
+            ],
+        ))
+    });
 
-            abox_var.set(
-                &abox
-                    .enter(&inner)
-                    .concatenate(vec![cax, eq_abox, prp_abox, cls]),
-            );
-            tbox_var.set(&tbox.enter(&inner).concatenate(vec![eq_tbox, prp_tbox]));
+    // prp-spo1
+    let prp_spo1 =
+        property_requests.join_core(&spo_assertions_by_o, |&p2, &(request, x, y), &p1| {
+            if p1 != p2 {
+                Some((
+                    request,
+                    (x, p2, y),
+                    "prp-spo1".to_string(),
+                    vec![(p1, subPropertyOf, p2)],
+                    vec![(constant(x), constant(p1), constant(y))],
+                ))
+            } else {
+                None
+            }
+        });
 
-            abox_new.leave()
-        })
-        .concat(&abox)
-        .consolidate()
+    // prp-eqp1
+    let prp_eqp1 =
+        property_requests.join_core(&eqp_assertions_by_o, |&p2, &(request, x, y), &p1| {
+            if p1 != p2 {
+                Some((
+                    request,
+                    (x, p2, y),
+                    "prp-eqp1".to_string(),
+                    vec![(p1, equivalentProperty, p2)],
+                    vec![(constant(x), constant(p1), constant(y))],
+                ))
+            } else {
+                None
+            }
+        });
+
+    // prp-eqp2
+    let prp_eqp2 =
+        property_requests.join_core(&eqp_assertions_arr, |&p1, &(request, x, y), &p2| {
+            if p1 != p2 {
+                Some((
+                    request,
+                    (x, p1, y),
+                    "prp-eqp2".to_string(),
+                    vec![(p1, equivalentProperty, p2)],
+                    vec![(constant(x), constant(p2), constant(y))],
+                ))
+            } else {
+                None
+            }
+        });
+
+    // prp-inv1
+    let prp_inv1 =
+        property_requests.join_core(&inv_assertions_by_o, |&p2, &(request, x, y), &p1| {
+            Some((
+                request,
+                (x, p2, y),
+                "prp-inv1".to_string(),
+                vec![(p1, inverseOf, p2)],
+                vec![(constant(y), constant(p1), constant(x))],
+            ))
+        });
+
+    // prp-inv2
+    let prp_inv2 =
+        property_requests.join_core(&inv_assertions_arr, |&p1, &(request, x, y), &p2| {
+            Some((
+                request,
+                (x, p1, y),
+                "prp-inv2".to_string(),
+                vec![(p1, inverseOf, p2)],
+                vec![(constant(y), constant(p2), constant(x))],
+            ))
+        });
+
+    // prp-fp
+    let prp_fp = sas_requests.join_core(&fp_properties, |&(), &(request, y1, y2), &p| {
+        Some((
+            request,
+            (y1, sameAs, y2),
+            "prp-fp".to_string(),
+            vec![(p, r#type, FunctionalProperty)],
+            vec![
+                (variable(0), constant(p), constant(y1)),
+                (variable(0), constant(p), constant(y2)),
+            ],
+        ))
+    });
+
+    // prp-ifp
+    let prp_ifp = sas_requests.join_core(&ifp_properties, |&(), &(request, x1, x2), &p| {
+        Some((
+            request,
+            (x1, sameAs, x2),
+            "prp-ifp".to_string(),
+            vec![(p, r#type, InverseFunctionalProperty)],
+            vec![
+                (constant(x1), constant(p), variable(0)),
+                (constant(x2), constant(p), variable(0)),
+            ],
+        ))
+    });
+
+    // prp-key, with a variable of its own for the value of every key
+    let prp_key = sas_requests.join_core(
+        &list_assertions(hasKey)
+            .map(|(c, (x, keys))| ((), (c, x, keys)))
+            .arrange_by_key(),
+        |&(), &(request, x, y), (c, list, keys)| {
+            let mut premises = vec![
+                (constant(x), constant(r#type), constant(*c)),
+                (constant(y), constant(r#type), constant(*c)),
+            ];
+            for (i, p_i) in keys.iter().enumerate() {
+                premises.push((constant(x), constant(*p_i), variable(i)));
+                premises.push((constant(y), constant(*p_i), variable(i)));
+            }
+            Some((
+                request,
+                (x, sameAs, y),
+                "prp-key".to_string(),
+                vec![(*c, hasKey, *list)],
+                premises,
+            ))
+        },
+    );
+
+    // prp-spo2, with a variable for every link of the chain between its ends
+    let prp_spo2 = list_assertions(propertyChainAxiom).join_core(
+        &property_requests.arrange_by_key(),
+        |&p, (x, chain), &(request, u, v)| {
+            let last = chain.len().checked_sub(1)?;
+            Some((
+                request,
+                (u, p, v),
+                "prp-spo2".to_string(),
+                vec![(p, propertyChainAxiom, *x)],
+                chain
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p_i)| {
+                        let s = if i == 0 { constant(u) } else { variable(i - 1) };
+                        let o = if i == last { constant(v) } else { variable(i) };
+                        (s, constant(*p_i), o)
+                    })
+                    .collect(),
+            ))
+        },
+    );
+
+    // cls-int2
+    let cls_int2 = list_assertions(intersectionOf)
+        .flat_map(|(c, (x, list))| list.into_iter().map(move |c_i| (c_i, (c, x))))
+        .join_core(
+            &type_requests.arrange_by_key(),
+            |&c_i, &(c, x), &(request, y)| {
+                Some((
+                    request,
+                    (y, r#type, c_i),
+                    "cls-int2".to_string(),
+                    vec![(c, intersectionOf, x)],
+                    vec![(constant(y), constant(r#type), constant(c))],
+                ))
+            },
+        );
+
+    // cls-oo
+    let cls_oo = list_assertions(oneOf)
+        .flat_map(|(c, (x, list))| list.into_iter().map(move |y| ((c, y), x)))
+        .join_core(
+            &type_requests
+                .map(|(c, (request, y))| ((c, y), request))
+                .arrange_by_key(),
+            |&(c, y), &x, &request| {
+                Some((
+                    request,
+                    (y, r#type, c),
+                    "cls-oo".to_string(),
+                    vec![(c, oneOf, x)],
+                    vec![],
+                ))
+            },
+        );
+
+    // cls-thing and cls-nothing1
+    let cls_thing = requests.flat_map(|(request, triple)| {
+        if triple == (Thing, r#type, Class) {
+            Some((request, triple, "cls-thing".to_string(), vec![], vec![]))
+        } else if triple == (Nothing, r#type, Class) {
+            Some((request, triple, "cls-nothing1".to_string(), vec![], vec![]))
+        } else {
+            None
+        }
+    });
+
+    // cls-maxc2 and cls-maxqc4
+    let cls_maxc2 = sas_requests.join_core(
+        &max_one_restrictions(maxCardinality)
+            .map(|(x, p)| ((), (x, p)))
+            .arrange_by_key(),
+        |&(), &(request, y1, y2), &(x, p)| {
+            Some((
+                request,
+                (y1, sameAs, y2),
+                "cls-maxc2".to_string(),
+                vec![(x, maxCardinality, cardinalityOne), (x, onProperty, p)],
+                vec![
+                    (variable(0), constant(r#type), constant(x)),
+                    (variable(0), constant(p), constant(y1)),
+                    (variable(0), constant(p), constant(y2)),
+                ],
+            ))
+        },
+    );
+
+    // cls-maxqc3 and cls-maxqc4
+    let cls_maxqc = sas_requests.join_core(
+        &max_one_restrictions(maxQualifiedCardinality)
+            .join_core(&oc_assertions_arr, |&x, &p, &c| Some(((), (x, p, c))))
+            .arrange_by_key(),
+        |&(), &(request, y1, y2), &(x, p, c)| {
+            let given = vec![
+                (x, maxQualifiedCardinality, cardinalityOne),
+                (x, onProperty, p),
+                (x, onClass, c),
+            ];
+            if c == Thing {
+                Some((
+                    request,
+                    (y1, sameAs, y2),
+                    "cls-maxqc4".to_string(),
+                    given,
+                    vec![
+                        (variable(0), constant(r#type), constant(x)),
+                        (variable(0), constant(p), constant(y1)),
+                        (variable(0), constant(p), constant(y2)),
+                    ],
+                ))
+            } else {
+                Some((
+                    request,
+                    (y1, sameAs, y2),
+                    "cls-maxqc3".to_string(),
+                    given,
+                    vec![
+                        (variable(0), constant(r#type), constant(x)),
+                        (variable(0), constant(p), constant(y1)),
+                        (constant(y1), constant(r#type), constant(c)),
+                        (variable(0), constant(p), constant(y2)),
+                        (constant(y2), constant(r#type), constant(c)),
+                    ],
+                ))
+            }
+        },
+    );
+
+    // eq-sym and eq-trans
+    let eq = sas_requests.flat_map(|((), (request, x, y))| {
+        vec![
+            (
+                request,
+                (x, sameAs, y),
+                "eq-sym".to_string(),
+                vec![],
+                vec![(constant(y), constant(sameAs), constant(x))],
+            ),
+            (
+                request,
+                (x, sameAs, y),
+                "eq-trans".to_string(),
+                vec![],
+                vec![
+                    (constant(x), constant(sameAs), variable(0)),
+                    (variable(0), constant(sameAs), constant(y)),
+                ],
+            ),
+        ]
+    });
+
+    cax_sco.concatenate(vec![
+        cax_eqc1, cax_eqc2, prp_dom, prp_rng, cls_svf, cls_avf, cls_hv1, cls_hv2, cls_int1,
+        cls_uni, prp_symp, prp_trp, prp_spo1, prp_eqp1, prp_eqp2, prp_inv1, prp_inv2, prp_fp,
+        prp_ifp, prp_key, prp_spo2, cls_int2, cls_oo, cls_thing, cls_maxc2, cls_maxqc, eq,
+    ])
 }
@@ -1,9 +1,23 @@
+use differential_dataflow::input::Input;
 use differential_dataflow::operators::arrange::ArrangeByKey;
-use differential_dataflow::operators::{Consolidate, JoinCore};
+use differential_dataflow::operators::iterate::Variable;
+use differential_dataflow::operators::{Consolidate, Join, JoinCore, Threshold};
+use timely::dataflow::Scope;
+use timely::order::Product;
 
-use crate::materialization::common::abox_domain_and_range_type_materialization;
-use crate::model::consts::constants::rdfs::{domain, r#type, range, subClassOf, subPropertyOf};
-use crate::model::types::{ListCollection, TripleCollection};
+use crate::materialization::common::{
+    abox_domain_and_range_type_materialization, derive, justified,
+};
+use crate::model::consts::constants::rdfs::{
+    comment, domain, first, isDefinedBy, label, member, nil, object, predicate, r#type, range,
+    rest, seeAlso, subClassOf, subPropertyOf, subject, value, Alt, Bag, Class, Container,
+    ContainerMembershipProperty, Datatype, List, Literal, Property, Resource, Seq, Statement,
+};
+use crate::model::types::{
+    ExplainRequestCollection, ListCollection, Pattern, ProvenanceCollection, RdfsEntailment,
+    Triple, TripleCollection, WhyNotCollection,
+};
+use crate::query::{constant, variable};
 
 pub fn rdfs<'a>(
     tbox: &TripleCollection<'a>,
@@ -55,3 +69,684 @@ pub fn rdfs<'a>(
         .concat(&type_assertions)
         .consolidate()
 }
+
+// rdfs2, rdfs3, rdfs7 and rdfs9, applied once to the abox, with the tbox closed under rdfs5 and
+// rdfs11 already.
+pub(crate) fn rdfs_rules<'a>(
+    tbox: &TripleCollection<'a>,
+    abox: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    let sco_assertions = tbox
+        .filter(|(_s, p, _o)| *p == subClassOf)
+        .map(|(s, _p, o)| (s, o));
+    let spo_assertions = tbox
+        .filter(|(_s, p, _o)| *p == subPropertyOf)
+        .map(|(s, _p, o)| (s, o));
+    let domain_assertions = tbox
+        .filter(|(_s, p, _o)| *p == domain)
+        .map(|(s, _p, o)| (s, o));
+    let range_assertions = tbox
+        .filter(|(_s, p, _o)| *p == range)
+        .map(|(s, _p, o)| (s, o));
+
+    let type_assertions_by_o_arr = abox
+        .filter(|(_s, p, _o)| *p == r#type)
+        .map(|(s, _p, o)| (o, s))
+        .arrange_by_key();
+    let property_assertions_by_p_arr = abox
+        .filter(|(_s, p, _o)| *p != r#type)
+        .map(|(s, p, o)| (p, (s, o)))
+        .arrange_by_key();
+
+    // rdfs2
+    let rdfs2 = domain_assertions.join_core(&property_assertions_by_p_arr, |&p, &c, &(x, y)| {
+        Some(derive(
+            (x, r#type, c),
+            "rdfs2",
+            &[(p, domain, c), (x, p, y)],
+        ))
+    });
+
+    // rdfs3
+    let rdfs3 = range_assertions.join_core(&property_assertions_by_p_arr, |&p, &c, &(x, y)| {
+        Some(derive((y, r#type, c), "rdfs3", &[(p, range, c), (x, p, y)]))
+    });
+
+    // rdfs7
+    let rdfs7 = spo_assertions.join_core(&property_assertions_by_p_arr, |&a, &b, &(x, y)| {
+        Some(derive(
+            (x, b, y),
+            "rdfs7",
+            &[(a, subPropertyOf, b), (x, a, y)],
+        ))
+    });
+
+    // rdfs9
+    let rdfs9 = sco_assertions.join_core(&type_assertions_by_o_arr, |&c1, &c2, &x| {
+        Some(derive(
+            (x, r#type, c2),
+            "rdfs9",
+            &[(c1, subClassOf, c2), (x, r#type, c1)],
+        ))
+    });
+
+    rdfs2.concatenate(vec![rdfs3, rdfs7, rdfs9])
+}
+
+pub fn rdfs_provenance<'a>(
+    tbox: &TripleCollection<'a>,
+    _lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    justified(&rdfs_rules(tbox, abox))
+}
+
+pub fn rdfs_why_not<'a>(
+    tbox: &TripleCollection<'a>,
+    _lists: &ListCollection<'a>,
+    requests: &ExplainRequestCollection<'a>,
+) -> WhyNotCollection<'a> {
+    let by_o = |property: u32| {
+        tbox.filter(move |(_s, p, _o)| *p == property)
+            .map(|(s, _p, o)| (o, s))
+            .arrange_by_key()
+    };
+    let sco_assertions_by_o = by_o(subClassOf);
+    let spo_assertions_by_o = by_o(subPropertyOf);
+    let domain_assertions_by_o = by_o(domain);
+    let range_assertions_by_o = by_o(range);
+
+    let type_requests = requests
+        .filter(|(_request, (_x, p, _c))| *p == r#type)
+        .map(|(request, (x, _p, c))| (c, (request, x)));
+    let property_requests = requests
+        .filter(|(_request, (_x, p, _y))| *p != r#type)
+        .map(|(request, (x, p, y))| (p, (request, x, y)));
+
+    // rdfs2
+    let rdfs2 = type_requests.join_core(&domain_assertions_by_o, |&c, &(request, x), &p| {
+        Some((
+            request,
+            (x, r#type, c),
+            "rdfs2".to_string(),
+            vec![(p, domain, c)],
+            vec![(constant(x), constant(p), variable(0))],
+        ))
+    });
+
+    // rdfs3
+    let rdfs3 = type_requests.join_core(&range_assertions_by_o, |&c, &(request, x), &p| {
+        Some((
+            request,
+            (x, r#type, c),
+            "rdfs3".to_string(),
+            vec![(p, range, c)],
+            vec![(variable(0), constant(p), constant(x))],
+        ))
+    });
+
+    // rdfs7
+    let rdfs7 = property_requests.join_core(&spo_assertions_by_o, |&b, &(request, x, y), &a| {
+        if a != b {
+            Some((
+                request,
+                (x, b, y),
+                "rdfs7".to_string(),
+                vec![(a, subPropertyOf, b)],
+                vec![(constant(x), constant(a), constant(y))],
+            ))
+        } else {
+            None
+        }
+    });
+
+    // rdfs9
+    let rdfs9 = type_requests.join_core(&sco_assertions_by_o, |&c2, &(request, x), &c1| {
+        if c1 != c2 {
+            Some((
+                request,
+                (x, r#type, c2),
+                "rdfs9".to_string(),
+                vec![(c1, subClassOf, c2)],
+                vec![(constant(x), constant(r#type), constant(c1))],
+            ))
+        } else {
+            None
+        }
+    });
+
+    rdfs2.concatenate(vec![rdfs3, rdfs7, rdfs9])
+}
+
+// The RDF and RDFS axiomatic triples, if they are switched on.
+fn rdfs_axiomatic_triples(entailment: RdfsEntailment) -> Vec<Triple> {
+    if !entailment.axiomatic_triples {
+        return vec![];
+    }
+    vec![
+        // RDF
+        (r#type, r#type, Property),
+        (subject, r#type, Property),
+        (predicate, r#type, Property),
+        (object, r#type, Property),
+        (first, r#type, Property),
+        (rest, r#type, Property),
+        (value, r#type, Property),
+        (nil, r#type, List),
+        // RDFS domains
+        (r#type, domain, Resource),
+        (domain, domain, Property),
+        (range, domain, Property),
+        (subPropertyOf, domain, Property),
+        (subClassOf, domain, Class),
+        (subject, domain, Statement),
+        (predicate, domain, Statement),
+        (object, domain, Statement),
+        (member, domain, Resource),
+        (first, domain, List),
+        (rest, domain, List),
+        (seeAlso, domain, Resource),
+        (isDefinedBy, domain, Resource),
+        (comment, domain, Resource),
+        (label, domain, Resource),
+        (value, domain, Resource),
+        // RDFS ranges
+        (r#type, range, Class),
+        (domain, range, Class),
+        (range, range, Class),
+        (subPropertyOf, range, Property),
+        (subClassOf, range, Class),
+        (subject, range, Resource),
+        (predicate, range, Resource),
+        (object, range, Resource),
+        (member, range, Resource),
+        (first, range, Resource),
+        (rest, range, List),
+        (seeAlso, range, Resource),
+        (isDefinedBy, range, Resource),
+        (comment, range, Literal),
+        (label, range, Literal),
+        (value, range, Resource),
+        // RDFS hierarchy
+        (Alt, subClassOf, Container),
+        (Bag, subClassOf, Container),
+        (Seq, subClassOf, Container),
+        (ContainerMembershipProperty, subClassOf, Property),
+        (isDefinedBy, subPropertyOf, seeAlso),
+        (Datatype, subClassOf, Class),
+    ]
+}
+
+// The axiomatic triples of an rdf:_n property, which are infinitely many altogether, so they are
+// only added for those that occur.
+pub(crate) fn container_membership_axiomatic_triples(p: u32) -> Vec<Triple> {
+    vec![
+        (p, r#type, ContainerMembershipProperty),
+        (p, domain, Resource),
+        (p, range, Resource),
+    ]
+}
+
+// Every rule of the full entailment that is switched on, applied once to the tbox, abox and
+// axiomatic triples together, since the axiomatic triples and the rules below type, and relate, the
+// schema itself.
+fn rdfs_full_rules<'a>(
+    graph: &TripleCollection<'a>,
+    entailment: RdfsEntailment,
+) -> ProvenanceCollection<'a> {
+    let graph_by_p = graph.map(|(s, p, o)| (p, (s, o))).arrange_by_key();
+
+    let type_assertions_by_o = graph
+        .filter(|(_s, p, _o)| *p == r#type)
+        .map(|(s, _p, o)| (o, s));
+    let type_assertions_by_o_arr = type_assertions_by_o.arrange_by_key();
+
+    let sco_assertions = graph
+        .filter(|(_s, p, _o)| *p == subClassOf)
+        .map(|(s, _p, o)| (s, o));
+    let sco_assertions_arr = sco_assertions.arrange_by_key();
+    let spo_assertions = graph
+        .filter(|(_s, p, _o)| *p == subPropertyOf)
+        .map(|(s, _p, o)| (s, o));
+    let spo_assertions_arr = spo_assertions.arrange_by_key();
+    let domain_assertions = graph
+        .filter(|(_s, p, _o)| *p == domain)
+        .map(|(s, _p, o)| (s, o));
+    let range_assertions = graph
+        .filter(|(_s, p, _o)| *p == range)
+        .map(|(s, _p, o)| (s, o));
+
+    // rdfs2
+    let rdfs2 = domain_assertions.join_core(&graph_by_p, |&p, &c, &(x, y)| {
+        Some(derive(
+            (x, r#type, c),
+            "rdfs2",
+            &[(p, domain, c), (x, p, y)],
+        ))
+    });
+
+    // rdfs3
+    let rdfs3 = range_assertions.join_core(&graph_by_p, |&p, &c, &(x, y)| {
+        Some(derive((y, r#type, c), "rdfs3", &[(p, range, c), (x, p, y)]))
+    });
+
+    // rdfs5
+    let rdfs5 =
+        spo_assertions
+            .map(|(p1, p2)| (p2, p1))
+            .join_core(&spo_assertions_arr, |&p2, &p1, &p3| {
+                Some(derive(
+                    (p1, subPropertyOf, p3),
+                    "rdfs5",
+                    &[(p1, subPropertyOf, p2), (p2, subPropertyOf, p3)],
+                ))
+            });
+
+    // rdfs7
+    let rdfs7 = spo_assertions.join_core(&graph_by_p, |&p1, &p2, &(x, y)| {
+        Some(derive(
+            (x, p2, y),
+            "rdfs7",
+            &[(p1, subPropertyOf, p2), (x, p1, y)],
+        ))
+    });
+
+    // rdfs9
+    let rdfs9 = sco_assertions.join_core(&type_assertions_by_o_arr, |&c1, &c2, &x| {
+        Some(derive(
+            (x, r#type, c2),
+            "rdfs9",
+            &[(c1, subClassOf, c2), (x, r#type, c1)],
+        ))
+    });
+
+    // rdfs11
+    let rdfs11 =
+        sco_assertions
+            .map(|(c1, c2)| (c2, c1))
+            .join_core(&sco_assertions_arr, |&c2, &c1, &c3| {
+                Some(derive(
+                    (c1, subClassOf, c3),
+                    "rdfs11",
+                    &[(c1, subClassOf, c2), (c2, subClassOf, c3)],
+                ))
+            });
+
+    let mut rules = vec![rdfs3, rdfs5, rdfs7, rdfs9, rdfs11];
+
+    // rdf1
+    if entailment.property_typing {
+        let rdf1 = graph.map(|(s, p, o)| derive((p, r#type, Property), "rdf1", &[(s, p, o)]));
+        rules.push(rdf1);
+    }
+
+    // rdfs4a and rdfs4b
+    if entailment.resource_typing {
+        let rdfs4 = graph.flat_map(|(s, p, o)| {
+            vec![
+                derive((s, r#type, Resource), "rdfs4a", &[(s, p, o)]),
+                derive((o, r#type, Resource), "rdfs4b", &[(s, p, o)]),
+            ]
+        });
+        rules.push(rdfs4);
+    }
+
+    if entailment.reflexivity {
+        // rdfs6
+        let rdfs6 = type_assertions_by_o
+            .filter(|(o, _p)| *o == Property)
+            .map(|(_o, p)| derive((p, subPropertyOf, p), "rdfs6", &[(p, r#type, Property)]));
+
+        let class_assertions = type_assertions_by_o.filter(|(o, _c)| *o == Class);
+
+        // rdfs8
+        let rdfs8 = class_assertions
+            .map(|(_o, c)| derive((c, subClassOf, Resource), "rdfs8", &[(c, r#type, Class)]));
+
+        // rdfs10
+        let rdfs10 = class_assertions
+            .map(|(_o, c)| derive((c, subClassOf, c), "rdfs10", &[(c, r#type, Class)]));
+
+        rules.extend(vec![rdfs6, rdfs8, rdfs10]);
+    }
+
+    // rdfs12
+    if entailment.container_membership {
+        let rdfs12 = type_assertions_by_o
+            .filter(|(o, _p)| *o == ContainerMembershipProperty)
+            .map(|(_o, p)| {
+                derive(
+                    (p, subPropertyOf, member),
+                    "rdfs12",
+                    &[(p, r#type, ContainerMembershipProperty)],
+                )
+            });
+        rules.push(rdfs12);
+    }
+
+    // rdfs13
+    if entailment.datatypes {
+        let rdfs13 = type_assertions_by_o
+            .filter(|(o, _d)| *o == Datatype)
+            .map(|(_o, d)| derive((d, subClassOf, Literal), "rdfs13", &[(d, r#type, Datatype)]));
+        rules.push(rdfs13);
+    }
+
+    rdfs2.concatenate(rules)
+}
+
+// The tbox, abox and axiomatic triples together, which the full entailment runs over.
+fn rdfs_full_graph<'a>(
+    tbox: &TripleCollection<'a>,
+    abox: &TripleCollection<'a>,
+    entailment: RdfsEntailment,
+) -> TripleCollection<'a> {
+    let mut outer = abox.scope();
+    let axiomatic_triples = rdfs_axiomatic_triples(entailment);
+    let axioms = outer.new_collection_from(axiomatic_triples).1;
+    abox.concatenate(vec![tbox.clone(), axioms])
+}
+
+// Unlike rdfs, which relies on the first stage for the closure of the schema, this runs every rule
+// over the tbox and abox together, since the axiomatic triples and the rules below type, and
+// relate, the schema itself.
+pub fn rdfs_full<'a>(
+    tbox: &TripleCollection<'a>,
+    _lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+    entailment: RdfsEntailment,
+) -> TripleCollection<'a> {
+    let mut outer = abox.scope();
+
+    let graph = rdfs_full_graph(tbox, abox, entailment);
+
+    let materialization = outer.iterative::<usize, _, _>(|inner| {
+        let graph_var = Variable::new(inner, Product::new(Default::default(), 1));
+
+        let graph_new = graph_var.distinct();
+
+        let graph_by_p = graph_new.map(|(s, p, o)| (p, (s, o))).arrange_by_key();
+
+        let type_assertions_by_o = graph_new
+            .filter(|(_s, p, _o)| *p == r#type)
+            .map(|(s, _p, o)| (o, s));
+        let type_assertions_by_o_arr = type_assertions_by_o.arrange_by_key();
+
+        let sco_assertions = graph_new
+            .filter(|(_s, p, _o)| *p == subClassOf)
+            .map(|(s, _p, o)| (s, o));
+        let sco_assertions_arr = sco_assertions.arrange_by_key();
+        let spo_assertions = graph_new
+            .filter(|(_s, p, _o)| *p == subPropertyOf)
+            .map(|(s, _p, o)| (s, o));
+        let spo_assertions_arr = spo_assertions.arrange_by_key();
+        let domain_assertions = graph_new
+            .filter(|(_s, p, _o)| *p == domain)
+            .map(|(s, _p, o)| (s, o));
+        let range_assertions = graph_new
+            .filter(|(_s, p, _o)| *p == range)
+            .map(|(s, _p, o)| (s, o));
+
+        // rdfs2
+        let rdfs2 =
+            domain_assertions.join_core(&graph_by_p, |&_p, &c, &(x, _y)| Some((x, r#type, c)));
+
+        // rdfs3
+        let rdfs3 =
+            range_assertions.join_core(&graph_by_p, |&_p, &c, &(_x, y)| Some((y, r#type, c)));
+
+        // rdfs5
+        let rdfs5 = spo_assertions
+            .map(|(p1, p2)| (p2, p1))
+            .join_core(&spo_assertions_arr, |&_p2, &p1, &p3| {
+                Some((p1, subPropertyOf, p3))
+            });
+
+        // rdfs7
+        let rdfs7 = spo_assertions.join_core(&graph_by_p, |&_p1, &p2, &(x, y)| Some((x, p2, y)));
+
+        // rdfs9
+        let rdfs9 = sco_assertions.join_core(&type_assertions_by_o_arr, |&_c1, &c2, &x| {
+            Some((x, r#type, c2))
+        });
+
+        // rdfs11
+        let rdfs11 = sco_assertions
+            .map(|(c1, c2)| (c2, c1))
+            .join_core(&sco_assertions_arr, |&_c2, &c1, &c3| {
+                Some((c1, subClassOf, c3))
+            });
+
+        let mut rules = vec![rdfs3, rdfs5, rdfs7, rdfs9, rdfs11];
+
+        // rdf1
+        if entailment.property_typing {
+            let rdf1 = graph_new
+                .map(|(_s, p, _o)| p)
+                .distinct()
+                .map(|p| (p, r#type, Property));
+            rules.push(rdf1);
+        }
+
+        // rdfs4a and rdfs4b
+        if entailment.resource_typing {
+            let rdfs4 =
+                graph_new.flat_map(|(s, _p, o)| vec![(s, r#type, Resource), (o, r#type, Resource)]);
+            rules.push(rdfs4);
+        }
+
+        if entailment.reflexivity {
+            // rdfs6
+            let rdfs6 = type_assertions_by_o
+                .filter(|(o, _p)| *o == Property)
+                .map(|(_o, p)| (p, subPropertyOf, p));
+
+            let class_assertions = type_assertions_by_o.filter(|(o, _c)| *o == Class);
+
+            // rdfs8
+            let rdfs8 = class_assertions.map(|(_o, c)| (c, subClassOf, Resource));
+
+            // rdfs10
+            let rdfs10 = class_assertions.map(|(_o, c)| (c, subClassOf, c));
+
+            rules.extend(vec![rdfs6, rdfs8, rdfs10]);
+        }
+
+        // rdfs12
+        if entailment.container_membership {
+            let rdfs12 = type_assertions_by_o
+                .filter(|(o, _p)| *o == ContainerMembershipProperty)
+                .map(|(_o, p)| (p, subPropertyOf, member));
+            rules.push(rdfs12);
+        }
+
+        // rdfs13
+        if entailment.datatypes {
+            let rdfs13 = type_assertions_by_o
+                .filter(|(o, _d)| *o == Datatype)
+                .map(|(_o, d)| (d, subClassOf, Literal));
+            rules.push(rdfs13);
+        }
+
+        graph_var.set(&graph.enter(inner).concat(&rdfs2.concatenate(rules)));
+
+        graph_new.leave()
+    });
+
+    // The tbox has already been output by the first stage.
+    materialization
+        .map(|triple| (triple, ()))
+        .antijoin(&tbox.distinct())
+        .map(|(triple, ())| triple)
+        .consolidate()
+}
+
+// Runs over the materialization of rdfs_full, along with the tbox and the axiomatic triples that it
+// leaves out. The axiomatic triples are derived by a rule of their own, without premises.
+pub fn rdfs_full_provenance<'a>(
+    tbox: &TripleCollection<'a>,
+    _lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+    entailment: RdfsEntailment,
+) -> ProvenanceCollection<'a> {
+    let mut outer = abox.scope();
+    let axiomatic_triples = rdfs_axiomatic_triples(entailment);
+    let axiomatic = outer
+        .new_collection_from(
+            axiomatic_triples
+                .into_iter()
+                .map(|triple| derive(triple, "axiomatic", &[]))
+                .collect::<Vec<_>>(),
+        )
+        .1;
+    justified(
+        &rdfs_full_rules(
+            &rdfs_full_graph(tbox, abox, entailment).distinct(),
+            entailment,
+        )
+        .concat(&axiomatic),
+    )
+}
+
+// The rules of the full entailment that could derive each requested triple. rdfs2, 3, 7 and 9 are
+// instantiated with the tbox and the axiomatic triples, like in rdfs_why_not, and rdfs5 and 11 with
+// the tbox alone, while the rules that switch on with the entailment match on their premises. The
+// axiomatic triples come with no premises.
+pub fn rdfs_full_why_not<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    requests: &ExplainRequestCollection<'a>,
+    entailment: RdfsEntailment,
+) -> WhyNotCollection<'a> {
+    let mut outer = requests.scope();
+    let axiomatic_triples = rdfs_axiomatic_triples(entailment);
+    let axioms = outer.new_collection_from(axiomatic_triples.clone()).1;
+    let schema = tbox.concat(&axioms).distinct();
+
+    let sco_assertions_by_o = tbox
+        .filter(|(_s, p, _o)| *p == subClassOf)
+        .map(|(s, _p, o)| (o, s))
+        .arrange_by_key();
+    let spo_assertions_by_o = tbox
+        .filter(|(_s, p, _o)| *p == subPropertyOf)
+        .map(|(s, _p, o)| (o, s))
+        .arrange_by_key();
+
+    let sco_requests = requests
+        .filter(|(_request, (_c1, p, _c3))| *p == subClassOf)
+        .map(|(request, (c1, _p, c3))| (c3, (request, c1)));
+    let spo_requests = requests
+        .filter(|(_request, (_p1, p, _p3))| *p == subPropertyOf)
+        .map(|(request, (p1, _p, p3))| (p3, (request, p1)));
+
+    // rdfs5
+    let rdfs5 = spo_requests.join_core(&spo_assertions_by_o, |&p3, &(request, p1), &p2| {
+        if p1 != p2 && p2 != p3 {
+            Some((
+                request,
+                (p1, subPropertyOf, p3),
+                "rdfs5".to_string(),
+                vec![(p2, subPropertyOf, p3)],
+                vec![(constant(p1), constant(subPropertyOf), constant(p2))],
+            ))
+        } else {
+            None
+        }
+    });
+
+    // rdfs11
+    let rdfs11 = sco_requests.join_core(&sco_assertions_by_o, |&c3, &(request, c1), &c2| {
+        if c1 != c2 && c2 != c3 {
+            Some((
+                request,
+                (c1, subClassOf, c3),
+                "rdfs11".to_string(),
+                vec![(c2, subClassOf, c3)],
+                vec![(constant(c1), constant(subClassOf), constant(c2))],
+            ))
+        } else {
+            None
+        }
+    });
+
+    // rdfs2, rdfs3, rdfs7 and rdfs9
+    let rdfs = rdfs_why_not(&schema, lists, requests);
+
+    let mut rules = vec![rdfs5, rdfs11];
+
+    // The axiomatic triples
+    let axiomatic = requests.flat_map(move |(request, triple)| {
+        axiomatic_triples
+            .contains(&triple)
+            .then(|| (request, triple, "axiomatic".to_string(), vec![], vec![]))
+    });
+    rules.push(axiomatic);
+
+    // A rule with a single premise, which only derives the requested triple if it matches the
+    // head, with the premise filled in from it.
+    let head = |rule: &'static str, premise: fn(Triple) -> Option<Pattern>| {
+        requests.flat_map(move |(request, triple)| {
+            premise(triple)
+                .map(|premise| (request, triple, rule.to_string(), vec![], vec![premise]))
+        })
+    };
+
+    // rdf1
+    if entailment.property_typing {
+        rules.push(head("rdf1", |(p, q, c)| {
+            (q == r#type && c == Property).then(|| (variable(0), constant(p), variable(1)))
+        }));
+    }
+
+    // rdfs4a and rdfs4b
+    if entailment.resource_typing {
+        rules.push(head("rdfs4a", |(x, p, c)| {
+            (p == r#type && c == Resource).then(|| (constant(x), variable(0), variable(1)))
+        }));
+        rules.push(head("rdfs4b", |(x, p, c)| {
+            (p == r#type && c == Resource).then(|| (variable(0), variable(1), constant(x)))
+        }));
+    }
+
+    if entailment.reflexivity {
+        // rdfs6
+        rules.push(head("rdfs6", |(p1, q, p2)| {
+            (q == subPropertyOf && p1 == p2)
+                .then(|| (constant(p1), constant(r#type), constant(Property)))
+        }));
+
+        // rdfs8
+        rules.push(head("rdfs8", |(c, q, d)| {
+            (q == subClassOf && d == Resource)
+                .then(|| (constant(c), constant(r#type), constant(Class)))
+        }));
+
+        // rdfs10
+        rules.push(head("rdfs10", |(c1, q, c2)| {
+            (q == subClassOf && c1 == c2).then(|| (constant(c1), constant(r#type), constant(Class)))
+        }));
+    }
+
+    // rdfs12
+    if entailment.container_membership {
+        rules.push(head("rdfs12", |(p, q, m)| {
+            (q == subPropertyOf && m == member).then(|| {
+                (
+                    constant(p),
+                    constant(r#type),
+                    constant(ContainerMembershipProperty),
+                )
+            })
+        }));
+    }
+
+    // rdfs13
+    if entailment.datatypes {
+        rules.push(head("rdfs13", |(d, q, l)| {
+            (q == subClassOf && l == Literal)
+                .then(|| (constant(d), constant(r#type), constant(Datatype)))
+        }));
+    }
+
+    rdfs.concatenate(rules)
+}
@@ -1,7 +1,14 @@
-use crate::materialization::common::abox_domain_and_range_type_materialization;
+use crate::materialization::common::{
+    abox_domain_and_range_type_materialization, derive, justified,
+};
+use crate::materialization::rdfs::{rdfs_rules, rdfs_why_not};
 use crate::model::consts::constants::owl::{inverseOf, TransitiveProperty};
 use crate::model::consts::constants::rdfs::{domain, r#type, range, subClassOf, subPropertyOf};
-use crate::model::types::{ListCollection, TripleCollection};
+use crate::model::types::{
+    ExplainRequestCollection, ListCollection, ProvenanceCollection, TripleCollection,
+    WhyNotCollection,
+};
+use crate::query::{constant, variable};
 use differential_dataflow::operators::arrange::ArrangeByKey;
 use differential_dataflow::operators::iterate::Variable;
 use differential_dataflow::operators::{Consolidate, JoinCore, Threshold};
@@ -55,8 +62,8 @@ pub fn rdfspp<'a>(
         let spo_assertions = spo_assertions.enter(inner);
         let general_trans_assertions = general_trans_assertions.enter(inner);
 
-        let inverse_of_assertions = inverse_of_assertions.enter(&inner);
-        let inverse_of_assertions_by_o = inverse_of_assertions_by_o.enter(&inner);
+        let inverse_of_assertions = inverse_of_assertions.enter(inner);
+        let inverse_of_assertions_by_o = inverse_of_assertions_by_o.enter(inner);
 
         let spo_iter_step = spo_assertions
             .join_core(&spo_type_gen_trans_inv_arr, |_a, &b, &(x, y)| {
@@ -119,3 +126,132 @@ pub fn rdfspp<'a>(
         .concat(&type_assertions)
         .consolidate()
 }
+
+// The RDFS rules along with prp-trp, prp-inv1 and prp-inv2, applied once to the abox.
+fn rdfspp_rules<'a>(
+    tbox: &TripleCollection<'a>,
+    abox: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    let trans_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == TransitiveProperty)
+        .map(|(s, _p, _o)| (s, ()));
+    let inverse_of_assertions = tbox
+        .filter(|(_s, p, _o)| *p == inverseOf)
+        .map(|(s, _p, o)| (s, o));
+    let inverse_of_assertions_by_o = inverse_of_assertions.map(|(s, o)| (o, s));
+
+    let property_assertions = abox.filter(|(_s, p, _o)| *p != r#type);
+    let property_assertions_by_p_arr = property_assertions
+        .map(|(s, p, o)| (p, (s, o)))
+        .arrange_by_key();
+    let property_assertions_by_sp_arr = property_assertions
+        .map(|(s, p, o)| ((s, p), o))
+        .arrange_by_key();
+
+    // prp-trp
+    let prp_trp = trans_assertions
+        .join_core(&property_assertions_by_p_arr, |&p, &(), &(x, y)| {
+            Some(((y, p), x))
+        })
+        .join_core(&property_assertions_by_sp_arr, |&(y, p), &x, &z| {
+            Some(derive(
+                (x, p, z),
+                "prp-trp",
+                &[(p, r#type, TransitiveProperty), (x, p, y), (y, p, z)],
+            ))
+        });
+
+    // prp-inv1
+    let prp_inv1 =
+        inverse_of_assertions.join_core(&property_assertions_by_p_arr, |&p1, &p2, &(x, y)| {
+            Some(derive(
+                (y, p2, x),
+                "prp-inv1",
+                &[(p1, inverseOf, p2), (x, p1, y)],
+            ))
+        });
+
+    // prp-inv2
+    let prp_inv2 =
+        inverse_of_assertions_by_o.join_core(&property_assertions_by_p_arr, |&p2, &p1, &(x, y)| {
+            Some(derive(
+                (y, p1, x),
+                "prp-inv2",
+                &[(p1, inverseOf, p2), (x, p2, y)],
+            ))
+        });
+
+    rdfs_rules(tbox, abox).concatenate(vec![prp_trp, prp_inv1, prp_inv2])
+}
+
+pub fn rdfspp_provenance<'a>(
+    tbox: &TripleCollection<'a>,
+    _lists: &ListCollection<'a>,
+    abox: &TripleCollection<'a>,
+) -> ProvenanceCollection<'a> {
+    justified(&rdfspp_rules(tbox, abox))
+}
+
+pub fn rdfspp_why_not<'a>(
+    tbox: &TripleCollection<'a>,
+    lists: &ListCollection<'a>,
+    requests: &ExplainRequestCollection<'a>,
+) -> WhyNotCollection<'a> {
+    let trans_assertions = tbox
+        .filter(|(_s, p, o)| *p == r#type && *o == TransitiveProperty)
+        .map(|(s, _p, _o)| (s, ()))
+        .arrange_by_key();
+    let inv_assertions = tbox
+        .filter(|(_s, p, _o)| *p == inverseOf)
+        .map(|(s, _p, o)| (s, o));
+    let inv_assertions_arr = inv_assertions.arrange_by_key();
+    let inv_assertions_by_o_arr = inv_assertions.map(|(s, o)| (o, s)).arrange_by_key();
+
+    let property_requests = requests
+        .filter(|(_request, (_x, p, _y))| *p != r#type)
+        .map(|(request, (x, p, y))| (p, (request, x, y)));
+
+    // prp-trp
+    let prp_trp = property_requests.join_core(&trans_assertions, |&p, &(request, x, y), &()| {
+        Some((
+            request,
+            (x, p, y),
+            "prp-trp".to_string(),
+            vec![(p, r#type, TransitiveProperty)],
+            vec![
+                (constant(x), constant(p), variable(0)),
+                (variable(0), constant(p), constant(y)),
+            ],
+        ))
+    });
+
+    // prp-inv1
+    let prp_inv1 =
+        property_requests.join_core(&inv_assertions_by_o_arr, |&p2, &(request, x, y), &p1| {
+            Some((
+                request,
+                (x, p2, y),
+                "prp-inv1".to_string(),
+                vec![(p1, inverseOf, p2)],
+                vec![(constant(y), constant(p1), constant(x))],
+            ))
+        });
+
+    // prp-inv2
+    let prp_inv2 =
+        property_requests.join_core(&inv_assertions_arr, |&p1, &(request, x, y), &p2| {
+            Some((
+                request,
+                (x, p1, y),
+                "prp-inv2".to_string(),
+                vec![(p1, inverseOf, p2)],
+                vec![(constant(y), constant(p2), constant(x))],
+            ))
+        });
+
+    prp_trp.concatenate(vec![
+        prp_inv1,
+        prp_inv2,
+        rdfs_why_not(tbox, lists, requests),
+    ])
+}
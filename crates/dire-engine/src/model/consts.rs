@@ -25,6 +25,45 @@ pub mod constants {
         pub const nil: u32 = 9;
         //                 let _rdfl: &str = "<http://www.w3.org/2000/01/rdf-schema#Literal>";
         pub const Literal: u32 = 10;
+        // The rest of the RDF and RDFS vocabulary is only needed by the full RDFS entailment. It
+        // counts down from the top of the id space, so that it does not shift the ids of encoded
+        // datasets.
+        //                 let _rdfsres: &str = "<http://www.w3.org/2000/01/rdf-schema#Resource>";
+        pub const Resource: u32 = u32::MAX;
+        //                 let _rdfscla: &str = "<http://www.w3.org/2000/01/rdf-schema#Class>";
+        pub const Class: u32 = u32::MAX - 1;
+        //                 let _rdfspro: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Property>";
+        pub const Property: u32 = u32::MAX - 2;
+        //                 let _rdfsdat: &str = "<http://www.w3.org/2000/01/rdf-schema#Datatype>";
+        pub const Datatype: u32 = u32::MAX - 3;
+        //                 let _rdfscon: &str = "<http://www.w3.org/2000/01/rdf-schema#ContainerMembershipProperty>";
+        pub const ContainerMembershipProperty: u32 = u32::MAX - 4;
+        //                 let _rdfsmem: &str = "<http://www.w3.org/2000/01/rdf-schema#member>";
+        pub const member: u32 = u32::MAX - 5;
+        //                 let _rdfscon: &str = "<http://www.w3.org/2000/01/rdf-schema#Container>";
+        pub const Container: u32 = u32::MAX - 6;
+        //                 let _rdfssta: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement>";
+        pub const Statement: u32 = u32::MAX - 7;
+        //                 let _rdfssub: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#subject>";
+        pub const subject: u32 = u32::MAX - 8;
+        //                 let _rdfspre: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate>";
+        pub const predicate: u32 = u32::MAX - 9;
+        //                 let _rdfsobj: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#object>";
+        pub const object: u32 = u32::MAX - 10;
+        //                 let _rdfslis: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#List>";
+        pub const List: u32 = u32::MAX - 11;
+        //                 let _rdfsval: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#value>";
+        pub const value: u32 = u32::MAX - 12;
+        //                 let _rdfssee: &str = "<http://www.w3.org/2000/01/rdf-schema#seeAlso>";
+        pub const seeAlso: u32 = u32::MAX - 13;
+        //                 let _rdfsisd: &str = "<http://www.w3.org/2000/01/rdf-schema#isDefinedBy>";
+        pub const isDefinedBy: u32 = u32::MAX - 14;
+        //                 let _rdfsalt: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Alt>";
+        pub const Alt: u32 = u32::MAX - 15;
+        //                 let _rdfsbag: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Bag>";
+        pub const Bag: u32 = u32::MAX - 16;
+        //                 let _rdfsseq: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Seq>";
+        pub const Seq: u32 = u32::MAX - 17;
     }
 
     pub mod owl {
@@ -101,5 +140,28 @@ pub mod constants {
         //
         pub const oneOf: u32 = 46;
         //                 let _owloo: &str = "<http://www.w3.org/2002/07/owl#oneOf>"
+        // The vocabulary of the consistency rules that the ntencoder does not reserve, which
+        // continues below the extra RDFS vocabulary.
+        //                 let _owlall: &str = "<http://www.w3.org/2002/07/owl#AllDisjointProperties>";
+        pub const AllDisjointProperties: u32 = u32::MAX - 18;
+        //                 let _owlneg: &str = "<http://www.w3.org/2002/07/owl#NegativePropertyAssertion>";
+        pub const NegativePropertyAssertion: u32 = u32::MAX - 19;
+        //                 let _owlsou: &str = "<http://www.w3.org/2002/07/owl#sourceIndividual>";
+        pub const sourceIndividual: u32 = u32::MAX - 20;
+        //                 let _owlass: &str = "<http://www.w3.org/2002/07/owl#assertionProperty>";
+        pub const assertionProperty: u32 = u32::MAX - 21;
+        //                 let _owlti: &str = "<http://www.w3.org/2002/07/owl#targetIndividual>";
+        pub const targetIndividual: u32 = u32::MAX - 22;
+        //                 let _owltv: &str = "<http://www.w3.org/2002/07/owl#targetValue>";
+        pub const targetValue: u32 = u32::MAX - 23;
+        //                 let _owldif: &str = "<http://www.w3.org/2002/07/owl#differentFrom>";
+        pub const differentFrom: u32 = u32::MAX - 24;
+        //                 let _owlmc: &str = "<http://www.w3.org/2002/07/owl#maxCardinality>";
+        pub const maxCardinality: u32 = u32::MAX - 25;
+        // The only cardinalities the OWL 2 RL rules reason with, as literals.
+        //                 let _owlzero: &str = "\"0\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger>";
+        pub const cardinalityZero: u32 = u32::MAX - 26;
+        //                 let _owlone: &str = "\"1\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger>";
+        pub const cardinalityOne: u32 = u32::MAX - 27;
     }
 }
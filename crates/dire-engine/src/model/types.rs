@@ -1,15 +1,51 @@
 use differential_dataflow::Collection;
 use flume::{Receiver, Sender};
-use std::fmt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use timely::communication::allocator::Generic;
 use timely::dataflow::scopes::Child;
 use timely::worker::Worker;
 
-pub type DoneSink = Sender<()>;
-pub type DoneSource = Receiver<()>;
+// What the workers are told to do by the driver, each of them getting every command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    // Takes in everything sent through the input channels so far as a new epoch, and works it
+    // through
+    Commit,
+    // Takes in and works through what is left, and stops
+    Shutdown,
+    // Stops the work left in the background until the next command, while input is still read
+    // and held for the next commit
+    Pause,
+    // Reports the totals since the start, without a round
+    Checkpoint,
+    // Answers the pending explain requests and queries as of the last round, in a round of its own
+    // that leaves the input channels be, and every epoch open, so that whatever was sent meanwhile
+    // waits for the next commit
+    Query,
+    // Takes in everything sent so far like a commit, and closes every epoch before the given one,
+    // with timed updates for them that come in later left out, and reported
+    AdvanceTo(u64),
+}
+
+// Sent by every worker once it is through with a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ack {
+    pub worker: usize,
+    // The first epoch that has not been put out yet
+    pub epoch: u64,
+    // In milliseconds
+    pub latency: u128,
+    pub added: usize,
+    pub removed: usize,
+    // What the worker could not take in, such as a shard that failed to load, and was left out
+    pub errors: Vec<String>,
+}
 
-pub type MasterSink = Sender<String>;
-pub type MasterSource = Receiver<String>;
+pub type CommandSink = Sender<Command>;
+pub type CommandSource = Receiver<Command>;
+pub type AckSink = Sender<Ack>;
+pub type AckSource = Receiver<Ack>;
 
 pub type Tuple = (u32, u32);
 pub type Triple = (u32, u32, u32);
@@ -17,24 +53,189 @@ pub type KeyedTriple = (u32, (u32, u32));
 pub type List = (u32, Vec<u32>);
 
 pub type TripleInputSink = Sender<(Triple, isize)>;
-pub type TripleOutputSink = Sender<(Triple, usize, isize)>;
+pub type TripleOutputSink = Sender<(Triple, u64, isize)>;
 
 pub type TripleInputSource = Receiver<(Triple, isize)>;
-pub type TripleOutputSource = Receiver<(Triple, usize, isize)>;
+// Updates at an epoch of the caller's choosing, such as the time of an event in milliseconds.
+pub type TimedTripleInputSink = Sender<(Triple, u64, isize)>;
+pub type TimedTripleInputSource = Receiver<(Triple, u64, isize)>;
+pub type TripleOutputSource = Receiver<(Triple, u64, isize)>;
+
+// Literals that are not in the value space of the datatype they claim to have, each alongside
+// that datatype. They hold of the literals alone, so they are never retracted.
+pub type IllTypedInputSink = Sender<Tuple>;
+pub type IllTypedInputSource = Receiver<Tuple>;
+
+// The name of a rule with a `false` head, alongside the triples that witness its violation.
+pub type Inconsistency = (String, Vec<Triple>);
+
+pub type InconsistencyOutputSink = Sender<(Inconsistency, u64, isize)>;
+pub type InconsistencyOutputSource = Receiver<(Inconsistency, u64, isize)>;
+
+// A derived triple, alongside the name of the rule and the premises it was derived from in one step.
+pub type Provenance = (Triple, String, Vec<Triple>);
+
+pub type ProvenanceOutputSink = Sender<(Provenance, u64, isize)>;
+pub type ProvenanceOutputSource = Receiver<(Provenance, u64, isize)>;
+
+// A triple to be explained, tagged with the id of the request.
+pub type ExplainRequest = (usize, Triple);
+// A triple that the proofs of a request may go through, along with whether it was given as input,
+// and either one of the ways it was derived, or none just to tell that it is entailed.
+pub type Explanation = (usize, Triple, bool, Option<(String, Vec<Triple>)>);
+
+pub type ExplainInputSink = Sender<ExplainRequest>;
+pub type ExplainInputSource = Receiver<ExplainRequest>;
+pub type ExplanationOutputSink = Sender<(Explanation, u64, isize)>;
+pub type ExplanationOutputSource = Receiver<(Explanation, u64, isize)>;
+
+// A triple pattern, each of whose terms is either a constant, or a variable numbered past u32::MAX.
+pub type Pattern = (u64, u64, u64);
+// A rule that could derive the triple of a request, along with the tbox triples it is instantiated
+// with, and its premises from the materialization, which share variables where they have to join.
+pub type WhyNot = (usize, Triple, String, Vec<Triple>, Vec<Pattern>);
+
+pub type WhyNotOutputSink = Sender<(WhyNot, u64, isize)>;
+pub type WhyNotOutputSource = Receiver<(WhyNot, u64, isize)>;
+
+// A basic graph pattern, tagged with the id of the request, to be answered as of the given epoch.
+pub type QueryRequest = (usize, u64, Vec<Pattern>);
+// The terms each variable of a query is bound to, in order.
+pub type Answer = (usize, Vec<u32>);
+
+pub type QueryInputSink = Sender<QueryRequest>;
+pub type QueryInputSource = Receiver<QueryRequest>;
+pub type AnswerOutputSink = Sender<(Answer, u64, isize)>;
+pub type AnswerOutputSource = Receiver<(Answer, u64, isize)>;
+
+// Continuous queries are registered with 1, and stay in place until they are unregistered with -1.
+pub type ContinuousQueryInputSink = Sender<(QueryRequest, isize)>;
+pub type ContinuousQueryInputSource = Receiver<(QueryRequest, isize)>;
+pub type ContinuousAnswerSink = Sender<(Vec<u32>, u64, isize)>;
+pub type ContinuousAnswerSource = Receiver<(Vec<u32>, u64, isize)>;
+// The channel that the answers of each continuous query go to, by the id of the query.
+pub type ContinuousAnswerSinks = Arc<Mutex<HashMap<usize, ContinuousAnswerSink>>>;
+
+// Reads the share of an input held by the worker with the given index, out of the given amount
+// of workers across all processes, or tells why it could not.
+pub type ShardLoader = Arc<dyn Fn(usize, usize) -> Result<Vec<Triple>, String> + Send + Sync>;
 
-pub type TupleCollection<'b> = Collection<Child<'b, Worker<Generic>, usize>, Tuple>;
-pub type TripleCollection<'b> = Collection<Child<'b, Worker<Generic>, usize>, Triple>;
-pub type KeyedTripleCollection<'b> = Collection<Child<'b, Worker<Generic>, usize>, KeyedTriple>;
-pub type ListCollection<'b> = Collection<Child<'b, Worker<Generic>, usize>, List>;
+// Runs the datatype rules on the driver, as the workers only see ids, over a term of a triple that
+// is inserted. Entails the tbox triples that dt-type1, dt-type2 and dt-eq do from it, and whether
+// it is ill-typed, alongside the datatype it claims to have, if it is a literal it has not been
+// given before, and nothing otherwise.
+pub type LiteralEntailer = Box<dyn FnMut(u32) -> (Vec<Triple>, Vec<Tuple>) + Send>;
+
+// Tells the rdf:_n properties apart on the driver, for the same reason, so that the full RDFS
+// entailment has the axiomatic triples of those that occur in the inserted triples.
+pub type ContainerMembershipTest = Box<dyn FnMut(u32) -> bool + Send>;
+
+pub type TupleCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, Tuple>;
+pub type TripleCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, Triple>;
+pub type KeyedTripleCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, KeyedTriple>;
+pub type ListCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, List>;
+pub type InconsistencyCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, Inconsistency>;
+pub type ProvenanceCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, Provenance>;
+pub type ExplainRequestCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, ExplainRequest>;
+pub type ExplanationCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, Explanation>;
+pub type WhyNotCollection<'b> = Collection<Child<'b, Worker<Generic>, u64>, WhyNot>;
 
 pub type FirstStageMaterialization =
     for<'a> fn(&TripleCollection<'a>) -> (TripleCollection<'a>, ListCollection<'a>);
-pub type SecondStageMaterialization = for<'a> fn(
+// A closure rather than a plain function, so that it can carry the options of the engine.
+pub type SecondStageMaterialization = Arc<
+    dyn for<'a> Fn(
+            &TripleCollection<'a>,
+            &ListCollection<'a>,
+            &TripleCollection<'a>,
+        ) -> TripleCollection<'a>
+        + Send
+        + Sync,
+>;
+
+// Runs over the tbox, its lists and the abox materialization, as rules with a `false` head derive
+// nothing that the materialization depends on, and over the ill-typed literals, which dt-not-type
+// rejects as they are.
+pub type ConsistencyCheck = for<'a> fn(
     &TripleCollection<'a>,
     &ListCollection<'a>,
     &TripleCollection<'a>,
-) -> TripleCollection<'a>;
+    &TupleCollection<'a>,
+) -> InconsistencyCollection<'a>;
+
+// Also runs over the tbox, its lists and the abox materialization, applying the same rules as the
+// second stage. As the materialization is closed under them, every way of deriving one of its
+// triples in a single step has its premises in it, so the records come and go along with them.
+// A closure, like the second stage, to carry the same options.
+pub type ProvenanceTracking = Arc<
+    dyn for<'a> Fn(
+            &TripleCollection<'a>,
+            &ListCollection<'a>,
+            &TripleCollection<'a>,
+        ) -> ProvenanceCollection<'a>
+        + Send
+        + Sync,
+>;
+
+// The same, for the tbox stage, over its own materialization. Only worked out for the explainer,
+// as it walks back from the requested triples.
+pub type TboxProvenanceTracking = for<'a> fn(&TripleCollection<'a>) -> ProvenanceCollection<'a>;
+
+// The rules that could derive the requested triples, out of the tbox and its lists, each with all
+// of its premises from the materialization. A closure as well, to carry the same options.
+pub type WhyNotCandidates = Arc<
+    dyn for<'a> Fn(
+            &TripleCollection<'a>,
+            &ListCollection<'a>,
+            &ExplainRequestCollection<'a>,
+        ) -> WhyNotCollection<'a>
+        + Send
+        + Sync,
+>;
+
+// Toggles the parts of the full RDFS entailment beyond rdfs2, 3, 5, 7, 9 and 11, most of which
+// blow up the materialization with little information. All of them are on by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RdfsEntailment {
+    // The RDF and RDFS axiomatic triples, save for those about rdf:_n properties, which the
+    // reasoner sends along for those that its container_membership test picks out
+    pub axiomatic_triples: bool,
+    // rdf1: every predicate is an rdf:Property
+    pub property_typing: bool,
+    // rdfs4a and rdfs4b: every subject and object is an rdfs:Resource
+    pub resource_typing: bool,
+    // rdfs6, rdfs8 and rdfs10: reflexive subPropertyOf and subClassOf, and subClassOf Resource
+    pub reflexivity: bool,
+    // rdfs12: every rdfs:ContainerMembershipProperty is a subPropertyOf rdfs:member
+    pub container_membership: bool,
+    // rdfs13: every rdfs:Datatype is a subClassOf rdfs:Literal
+    pub datatypes: bool,
+}
+
+impl Default for RdfsEntailment {
+    fn default() -> Self {
+        Self {
+            axiomatic_triples: true,
+            property_typing: true,
+            resource_typing: true,
+            reflexivity: true,
+            container_membership: true,
+            datatypes: true,
+        }
+    }
+}
+
+// How long abox facts are kept for, before the engine retracts them on its own, along with whatever
+// they entail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    // For the given span of epochs from the one they are inserted at
+    Duration(u64),
+    // For the given amount of commits, counting the one that takes them in
+    Epochs(usize),
+}
 
+#[allow(non_snake_case)]
 pub struct RuntimeLog {
     File: usize,
     Latency: u128,
@@ -44,6 +245,7 @@ pub struct RuntimeLog {
 }
 
 impl RuntimeLog {
+    #[allow(non_snake_case)]
     pub fn new(File: usize, Latency: u128, Added: usize, Removed: usize, Worker: usize) -> Self {
         Self {
             File,
@@ -55,6 +257,7 @@ impl RuntimeLog {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for RuntimeLog {
     fn to_string(&self) -> String {
         format!(
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use differential_dataflow::operators::arrange::{
+    ArrangeByKey, Arranged, ShutdownButton, TraceAgent,
+};
+use differential_dataflow::operators::iterate::Variable;
+use differential_dataflow::operators::{Join, JoinCore, Threshold};
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+use differential_dataflow::trace::TraceReader;
+use differential_dataflow::{Collection, ExchangeData};
+use timely::communication::allocator::Generic;
+use timely::dataflow::operators::CapabilitySet;
+use timely::dataflow::scopes::Child;
+use timely::dataflow::Scope;
+use timely::order::Product;
+use timely::progress::frontier::AntichainRef;
+use timely::worker::Worker;
+
+use crate::control::Controller;
+use crate::model::types::{
+    AnswerOutputSource, Command, ContinuousAnswerSinks, ContinuousAnswerSource,
+    ContinuousQueryInputSink, Pattern, QueryInputSink, QueryRequest, Triple, TripleCollection,
+};
+
+// Encoded terms from here on are variables.
+const VARIABLES: u64 = u32::MAX as u64 + 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Term {
+    Constant(u32),
+    // Variables are numbered from zero, and every number up to the largest one of a query has to
+    // occur in it.
+    Variable(usize),
+}
+
+pub type TriplePattern = (Term, Term, Term);
+
+pub(crate) fn constant(term: u32) -> u64 {
+    term as u64
+}
+
+pub(crate) fn variable(variable: usize) -> u64 {
+    VARIABLES + variable as u64
+}
+
+fn encode(term: Term) -> u64 {
+    match term {
+        Term::Constant(term) => constant(term),
+        Term::Variable(number) => variable(number),
+    }
+}
+
+pub(crate) fn decode(term: u64) -> Term {
+    if term < VARIABLES {
+        Term::Constant(term as u32)
+    } else {
+        Term::Variable((term - VARIABLES) as usize)
+    }
+}
+
+// Why a query could not be answered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    // The reasoner was built without an index, neither to query nor to explain
+    NotIndexed,
+    // There is no round to answer as of yet
+    NotCommitted,
+    // A variable numbered below the largest one of the query that occurs in none of its patterns,
+    // which no answer could bind
+    UnboundVariable(usize),
+}
+
+// Checks that every variable up to the largest one occurs in the patterns, for all of them to be
+// bound by every answer.
+fn check(patterns: &[TriplePattern]) -> Result<(), QueryError> {
+    let variables: Vec<usize> = patterns
+        .iter()
+        .flat_map(|pattern| [pattern.0, pattern.1, pattern.2])
+        .filter_map(|term| match term {
+            Term::Variable(variable) => Some(variable),
+            Term::Constant(_) => None,
+        })
+        .collect();
+    let count = variables.iter().max().map_or(0, |largest| largest + 1);
+    match (0..count).find(|variable| !variables.contains(variable)) {
+        Some(variable) => Err(QueryError::UnboundVariable(variable)),
+        None => Ok(()),
+    }
+}
+
+// Orders the patterns so that each one has as many of its terms bound as possible, by constants or
+// by the patterns before it, falling back to the given order on ties.
+fn plan(patterns: &[TriplePattern]) -> Vec<Pattern> {
+    let mut pending: Vec<TriplePattern> = patterns.to_vec();
+    let mut bound: Vec<usize> = vec![];
+    let mut planned = vec![];
+    while !pending.is_empty() {
+        let boundness = |pattern: &TriplePattern| {
+            [pattern.0, pattern.1, pattern.2]
+                .iter()
+                .filter(|term| match term {
+                    Term::Constant(_) => true,
+                    Term::Variable(variable) => bound.contains(variable),
+                })
+                .count()
+        };
+        let next = (0..pending.len())
+            .rev()
+            .max_by_key(|index| boundness(&pending[*index]))
+            .unwrap();
+        let pattern = pending.remove(next);
+        for term in [pattern.0, pattern.1, pattern.2] {
+            if let Term::Variable(variable) = term {
+                bound.push(variable)
+            }
+        }
+        planned.push((encode(pattern.0), encode(pattern.1), encode(pattern.2)));
+    }
+    planned
+}
+
+// The term in place of an encoded one, if it is a constant or an already bound variable.
+fn resolve(term: u64, bindings: &[Option<u32>]) -> Option<u32> {
+    if term < VARIABLES {
+        Some(term as u32)
+    } else {
+        bindings[(term - VARIABLES) as usize]
+    }
+}
+
+// The pattern with its bound variables in place.
+fn substitute(pattern: Pattern, bindings: &[Option<u32>]) -> Pattern {
+    let substitute = |term| resolve(term, bindings).map_or(term, constant);
+    (
+        substitute(pattern.0),
+        substitute(pattern.1),
+        substitute(pattern.2),
+    )
+}
+
+// The triple the pattern stands for, once all of its variables are bound.
+pub(crate) fn instantiate(pattern: Pattern, bindings: &[Option<u32>]) -> Option<Triple> {
+    Some((
+        resolve(pattern.0, bindings)?,
+        resolve(pattern.1, bindings)?,
+        resolve(pattern.2, bindings)?,
+    ))
+}
+
+// The bindings extended with the terms of the triple, if it matches the pattern.
+fn bind(pattern: Pattern, bindings: &[Option<u32>], triple: Triple) -> Option<Vec<Option<u32>>> {
+    let mut bindings = bindings.to_vec();
+    for (term, value) in [
+        (pattern.0, triple.0),
+        (pattern.1, triple.1),
+        (pattern.2, triple.2),
+    ] {
+        if term < VARIABLES {
+            if term as u32 != value {
+                return None;
+            }
+        } else {
+            let binding = &mut bindings[(term - VARIABLES) as usize];
+            match binding {
+                Some(bound) if *bound != value => return None,
+                Some(_) => {}
+                None => *binding = Some(value),
+            }
+        }
+    }
+    Some(bindings)
+}
+
+type IndexArrangement<'a, K> =
+    Arranged<Child<'a, Worker<Generic>, u64>, TraceAgent<OrdValSpine<K, Triple, u64, isize>>>;
+type IndexTrace<K> = TraceAgent<OrdValSpine<K, Triple, u64, isize>>;
+
+// The triples of the materialization keyed by each of their terms, and by nothing for patterns
+// with none of them bound.
+pub(crate) struct Index<'a> {
+    by_s: IndexArrangement<'a, u32>,
+    by_p: IndexArrangement<'a, u32>,
+    by_o: IndexArrangement<'a, u32>,
+    all: IndexArrangement<'a, ()>,
+}
+
+impl<'a> Index<'a> {
+    pub(crate) fn new(triples: &TripleCollection<'a>) -> Self {
+        let triples = triples.distinct();
+        Self {
+            by_s: triples.map(|(s, p, o)| (s, (s, p, o))).arrange_by_key(),
+            by_p: triples.map(|(s, p, o)| (p, (s, p, o))).arrange_by_key(),
+            by_o: triples.map(|(s, p, o)| (o, (s, p, o))).arrange_by_key(),
+            all: triples.map(|triple| ((), triple)).arrange_by_key(),
+        }
+    }
+
+    // The requested triples that are in the materialization.
+    pub(crate) fn semijoin<D: ExchangeData + Hash>(
+        &self,
+        requests: &Collection<Child<'a, Worker<Generic>, u64>, (D, Triple)>,
+    ) -> Collection<Child<'a, Worker<Generic>, u64>, (D, Triple)> {
+        requests
+            .map(|(item, (s, p, o))| (s, (item, (s, p, o))))
+            .join_core(&self.by_s, |_s, (item, requested), triple| {
+                (requested == triple).then(|| (item.clone(), *triple))
+            })
+    }
+
+    pub(crate) fn traces(&self) -> IndexTraces {
+        IndexTraces {
+            by_s: self.by_s.trace.clone(),
+            by_p: self.by_p.trace.clone(),
+            by_o: self.by_o.trace.clone(),
+            all: self.all.trace.clone(),
+        }
+    }
+}
+
+// The traces of an index, which the worker holds on to, so that the dataflows it builds to answer
+// requests can read the materialization as of the last round.
+pub(crate) struct IndexTraces {
+    by_s: IndexTrace<u32>,
+    by_p: IndexTrace<u32>,
+    by_o: IndexTrace<u32>,
+    all: IndexTrace<()>,
+}
+
+impl IndexTraces {
+    // The index in another dataflow, along with the buttons that stop reading from it, once the
+    // dataflow has read what is of interest.
+    pub(crate) fn import<'a>(
+        &mut self,
+        scope: &Child<'a, Worker<Generic>, u64>,
+    ) -> (Index<'a>, Vec<ShutdownButton<CapabilitySet<u64>>>) {
+        let (by_s, by_s_button) = self.by_s.import_core(scope, "index_by_s");
+        let (by_p, by_p_button) = self.by_p.import_core(scope, "index_by_p");
+        let (by_o, by_o_button) = self.by_o.import_core(scope, "index_by_o");
+        let (all, all_button) = self.all.import_core(scope, "index_all");
+        (
+            Index {
+                by_s,
+                by_p,
+                by_o,
+                all,
+            },
+            vec![by_s_button, by_p_button, by_o_button, all_button],
+        )
+    }
+
+    // Lets the traces forget the epochs before the given one, which they can still be read as of.
+    pub(crate) fn compact(&mut self, epoch: u64) {
+        let frontier = [epoch];
+        self.by_s
+            .set_logical_compaction(AntichainRef::new(&frontier));
+        self.by_s
+            .set_physical_compaction(AntichainRef::new(&frontier));
+        self.by_p
+            .set_logical_compaction(AntichainRef::new(&frontier));
+        self.by_p
+            .set_physical_compaction(AntichainRef::new(&frontier));
+        self.by_o
+            .set_logical_compaction(AntichainRef::new(&frontier));
+        self.by_o
+            .set_physical_compaction(AntichainRef::new(&frontier));
+        self.all
+            .set_logical_compaction(AntichainRef::new(&frontier));
+        self.all
+            .set_physical_compaction(AntichainRef::new(&frontier));
+    }
+}
+
+// A collection of the dataflow that requests are matched in.
+type RequestCollection<'a, D> = Collection<Child<'a, Worker<Generic>, u64>, D>;
+
+// A match under way: what it is for, its patterns, how many of them it is through, the bindings so
+// far, and the patterns it went on without.
+type Partial<D> = (D, Vec<Pattern>, usize, Vec<Option<u32>>, Vec<Pattern>);
+
+// Matches the patterns of every request one at a time and in order, each against the arrangement
+// of the index keyed by the first of its terms that is bound, to every binding of their variables.
+// When lenient, a pattern that no triple matches under the bindings so far does not end the match,
+// which goes on without it, and it is put out along with the bindings, with those in place, as
+// one that is missing.
+pub(crate) fn matches<'a, D>(
+    requests: &RequestCollection<'a, (D, Vec<Pattern>)>,
+    index: &Index<'a>,
+    lenient: bool,
+) -> RequestCollection<'a, (D, Vec<Option<u32>>, Vec<Pattern>)>
+where
+    D: ExchangeData + Hash,
+{
+    let partials = requests.map(|(item, patterns)| {
+        let variables = patterns
+            .iter()
+            .flat_map(|(s, p, o)| [*s, *p, *o])
+            .filter(|term| *term >= VARIABLES)
+            .map(|term| (term - VARIABLES) as usize + 1)
+            .max()
+            .unwrap_or(0);
+        (item, patterns, 0, vec![None; variables], vec![])
+    });
+
+    let mut outer = requests.scope();
+    outer.iterative::<usize, _, _>(|inner| {
+        let partials_var =
+            Variable::new_from(partials.enter(inner), Product::new(Default::default(), 1));
+
+        let pending = partials_var
+            .filter(|(_item, patterns, step, _bindings, _missing)| *step < patterns.len());
+        let keyed = |position: usize| {
+            pending.flat_map(move |partial: Partial<D>| {
+                let (s, p, o) = partial.1[partial.2];
+                let (s, p, o) = (
+                    resolve(s, &partial.3),
+                    resolve(p, &partial.3),
+                    resolve(o, &partial.3),
+                );
+                let key = match (s, p, o) {
+                    (Some(s), _, _) => (0, s),
+                    (None, Some(p), _) => (1, p),
+                    (None, None, Some(o)) => (2, o),
+                    (None, None, None) => (3, 0),
+                };
+                if key.0 == position {
+                    Some((key.1, partial))
+                } else {
+                    None
+                }
+            })
+        };
+        // Every step is put out along with the partial match it was taken from.
+        let step = |partial: &Partial<D>, triple: &Triple| {
+            let (item, patterns, step, bindings, missing) = partial;
+            bind(patterns[*step], bindings, *triple).map(|bindings| {
+                (
+                    partial.clone(),
+                    (
+                        item.clone(),
+                        patterns.clone(),
+                        step + 1,
+                        bindings,
+                        missing.clone(),
+                    ),
+                )
+            })
+        };
+
+        let stepped = keyed(0)
+            .join_core(&index.by_s.enter(inner), move |_s, partial, triple| {
+                step(partial, triple)
+            })
+            .concatenate(vec![
+                keyed(1).join_core(&index.by_p.enter(inner), move |_p, partial, triple| {
+                    step(partial, triple)
+                }),
+                keyed(2).join_core(&index.by_o.enter(inner), move |_o, partial, triple| {
+                    step(partial, triple)
+                }),
+                keyed(3)
+                    .map(|(_key, partial)| ((), partial))
+                    .join_core(&index.all.enter(inner), move |&(), partial, triple| {
+                        step(partial, triple)
+                    }),
+            ]);
+        let skipped = pending
+            .filter(move |_partial| lenient)
+            .map(|partial| (partial, ()))
+            .antijoin(&stepped.map(|(partial, _next)| partial).distinct())
+            .map(|((item, patterns, step, bindings, mut missing), ())| {
+                missing.push(substitute(patterns[step], &bindings));
+                (item, patterns, step + 1, bindings, missing)
+            });
+
+        let matched = partials_var
+            .filter(|(_item, patterns, step, _bindings, _missing)| *step == patterns.len())
+            .map(|(item, _patterns, _step, bindings, missing)| (item, bindings, missing));
+
+        partials_var.set(
+            &partials
+                .enter(inner)
+                .concatenate(vec![stepped.map(|(_partial, next)| next), skipped]),
+        );
+
+        matched.leave()
+    })
+}
+
+// Every binding of the variables of each query that puts all of its patterns in the index. Queries
+// are checked to bind all of their variables beforehand.
+pub(crate) fn answers<'a, D>(
+    requests: &RequestCollection<'a, (D, Vec<Pattern>)>,
+    index: &Index<'a>,
+) -> RequestCollection<'a, (D, Vec<u32>)>
+where
+    D: ExchangeData + Hash,
+{
+    matches(requests, index, false).flat_map(|(item, bindings, _missing)| {
+        bindings
+            .into_iter()
+            .collect::<Option<Vec<u32>>>()
+            .map(|answer| (item, answer))
+    })
+}
+
+// A query registered with the querier, whose answers keep coming in as they change, until it is
+// unregistered.
+pub struct ContinuousQuery {
+    request: QueryRequest,
+    answers: ContinuousAnswerSource,
+}
+
+impl ContinuousQuery {
+    // Every answer, the epoch it changed at, and whether it was added or retracted.
+    pub fn answers(&self) -> &ContinuousAnswerSource {
+        &self.answers
+    }
+}
+
+// Answers basic graph pattern queries over the materialized tbox and abox of a running engine, when
+// it keeps them indexed.
+pub struct Querier {
+    query_input_sink: QueryInputSink,
+    answer_output_source: AnswerOutputSource,
+    continuous_query_input_sink: ContinuousQueryInputSink,
+    continuous_answer_sinks: ContinuousAnswerSinks,
+    controller: Controller,
+    // Whether the engine keeps the materialization indexed
+    indexed: bool,
+    next_query: usize,
+}
+
+impl Querier {
+    pub(crate) fn new(
+        query_input_sink: QueryInputSink,
+        answer_output_source: AnswerOutputSource,
+        continuous_query_input_sink: ContinuousQueryInputSink,
+        continuous_answer_sinks: ContinuousAnswerSinks,
+        controller: Controller,
+        indexed: bool,
+    ) -> Self {
+        Self {
+            query_input_sink,
+            answer_output_source,
+            continuous_query_input_sink,
+            continuous_answer_sinks,
+            controller,
+            indexed,
+            next_query: 0,
+        }
+    }
+
+    // Every binding of the variables that puts all the patterns in the materialization, as of the
+    // last round.
+    pub fn query(&mut self, patterns: &[TriplePattern]) -> Result<Vec<Vec<u32>>, QueryError> {
+        self.query_at(patterns, u64::MAX)
+    }
+
+    // The same, as of the given epoch, which is the time of the round that the input of interest
+    // went in with, as reported in the output channels. The index only goes back as many epochs
+    // as the engine keeps history for, and earlier ones are answered as of the first it holds.
+    pub fn query_at(
+        &mut self,
+        patterns: &[TriplePattern],
+        epoch: u64,
+    ) -> Result<Vec<Vec<u32>>, QueryError> {
+        if !self.indexed {
+            return Err(QueryError::NotIndexed);
+        }
+        check(patterns)?;
+        let query = self.next_query;
+        self.next_query += 1;
+        self.query_input_sink
+            .send((query, epoch, plan(patterns)))
+            .unwrap();
+        let acks = self.controller.broadcast(Command::Query);
+        // Queries sent before the first round are dropped, which the acks tell of.
+        if acks[0].epoch == 0 {
+            return Err(QueryError::NotCommitted);
+        }
+
+        // Every answer is put out by the time the round is through.
+        let mut multiplicities: HashMap<Vec<u32>, isize> = HashMap::new();
+        for ((answered, answer), _time, diff) in self.answer_output_source.try_iter() {
+            if answered == query {
+                *multiplicities.entry(answer).or_insert(0) += diff;
+            }
+        }
+        let mut answers: Vec<Vec<u32>> = multiplicities
+            .into_iter()
+            .filter(|(_answer, multiplicity)| *multiplicity > 0)
+            .map(|(answer, _multiplicity)| answer)
+            .collect();
+        answers.sort();
+        Ok(answers)
+    }
+
+    // Keeps the query answered over the materialization from the next commit on, which sends the
+    // answers as of then, and every change to them after each round that follows.
+    pub fn register(&mut self, patterns: &[TriplePattern]) -> Result<ContinuousQuery, QueryError> {
+        if !self.indexed {
+            return Err(QueryError::NotIndexed);
+        }
+        check(patterns)?;
+        let query = self.next_query;
+        self.next_query += 1;
+        let (continuous_answer_sink, continuous_answer_source) = flume::unbounded();
+        self.continuous_answer_sinks
+            .lock()
+            .unwrap()
+            .insert(query, continuous_answer_sink);
+        let request = (query, u64::MAX, plan(patterns));
+        self.continuous_query_input_sink
+            .send((request.clone(), 1))
+            .unwrap();
+        Ok(ContinuousQuery {
+            request,
+            answers: continuous_answer_source,
+        })
+    }
+
+    // Stops sending answers to the query right away, and stops answering it from the next commit.
+    pub fn unregister(&mut self, query: ContinuousQuery) {
+        self.continuous_query_input_sink
+            .send((query.request.clone(), -1))
+            .unwrap();
+        self.continuous_answer_sinks
+            .lock()
+            .unwrap()
+            .remove(&query.request.0);
+    }
+}
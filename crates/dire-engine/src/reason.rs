@@ -1,131 +1,620 @@
+use crate::explain::{explanations, missing_premises};
 use crate::model::types::{
-    DoneSink, DoneSource, FirstStageMaterialization, LogSink, MasterSource, RuntimeLog,
-    SecondStageMaterialization, Triple, TripleInputSource, TripleOutputSink,
+    Ack, AckSink, AnswerOutputSink, Command, CommandSource, ConsistencyCheck,
+    ContinuousAnswerSinks, ContinuousQueryInputSource, ExplainInputSource, ExplainRequest,
+    ExplanationOutputSink, FirstStageMaterialization, IllTypedInputSource, InconsistencyOutputSink,
+    LogSink, Pattern, ProvenanceOutputSink, ProvenanceTracking, QueryInputSource, QueryRequest,
+    RuntimeLog, SecondStageMaterialization, ShardLoader, TboxProvenanceTracking,
+    TimedTripleInputSource, Triple, TripleInputSource, TripleOutputSink, Tuple, WhyNotCandidates,
+    WhyNotOutputSink, Window,
 };
-use differential_dataflow::input::Input;
+use crate::query::{answers, Index};
+use differential_dataflow::input::{Input, InputSession};
 use differential_dataflow::operators::arrange::{ArrangeByKey, ArrangeBySelf};
 use differential_dataflow::operators::Threshold;
-use differential_dataflow::trace::Trace;
+use differential_dataflow::trace::TraceReader;
+use flume::{RecvTimeoutError, TryRecvError};
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 use timely::communication::allocator::Generic;
-use timely::worker::{AsWorker, Worker};
-use timely::PartialOrder;
+use timely::dataflow::ProbeHandle;
+use timely::progress::frontier::AntichainRef;
+use timely::worker::Worker;
 
-pub fn reason(
+// The rules the workers run, as picked for the engine.
+pub(crate) struct Logic {
+    pub(crate) tbox_materialization: FirstStageMaterialization,
+    pub(crate) abox_materialization: SecondStageMaterialization,
+    pub(crate) consistency_check: ConsistencyCheck,
+    pub(crate) provenance_tracking: ProvenanceTracking,
+    pub(crate) tbox_provenance_tracking: TboxProvenanceTracking,
+    pub(crate) why_not_candidates: WhyNotCandidates,
+}
+
+// What the engine keeps track of on top of the materialization, and takes in on top of the input
+// channels.
+pub(crate) struct Options {
+    // Every way of deriving each triple of the abox materialization in one step is output, for
+    // every engine but the dummy one
+    pub(crate) provenance: bool,
+    // The input and the materialization are kept arranged, for the explainer to walk back from
+    // the triples it is asked about, through the rules that could derive them
+    pub(crate) explain: bool,
+    // The materialization is kept arranged for the querier, which explaining does as well
+    pub(crate) index: bool,
+    // How many epochs before the last round the index goes back
+    pub(crate) history: u64,
+    // Every worker, across all processes, loads its own share of the tbox and abox with these,
+    // ahead of anything sent through the channels
+    pub(crate) tbox_shard: Option<ShardLoader>,
+    pub(crate) abox_shard: Option<ShardLoader>,
+    // Abox facts are retracted by the engine once they fall out of it
+    pub(crate) window: Option<Window>,
+}
+
+// The receiving ends of the channels that the workers read from.
+pub(crate) struct Sources {
+    pub(crate) tbox_input_source: TripleInputSource,
+    pub(crate) abox_input_source: TripleInputSource,
+    pub(crate) tbox_timed_input_source: TimedTripleInputSource,
+    pub(crate) abox_timed_input_source: TimedTripleInputSource,
+    pub(crate) ill_typed_input_source: IllTypedInputSource,
+    pub(crate) explain_input_source: ExplainInputSource,
+    pub(crate) query_input_source: QueryInputSource,
+    pub(crate) continuous_query_input_source: ContinuousQueryInputSource,
+    // One per worker of this process
+    pub(crate) commands: Vec<CommandSource>,
+}
+
+// The sending ends of the channels that the workers write to.
+pub(crate) struct Sinks {
+    pub(crate) tbox_output_sink: TripleOutputSink,
+    pub(crate) abox_output_sink: TripleOutputSink,
+    pub(crate) inconsistency_output_sink: InconsistencyOutputSink,
+    // None without provenance
+    pub(crate) provenance_output_sink: Option<ProvenanceOutputSink>,
+    pub(crate) explanation_output_sink: ExplanationOutputSink,
+    pub(crate) why_not_output_sink: WhyNotOutputSink,
+    pub(crate) answer_output_sink: AnswerOutputSink,
+    pub(crate) continuous_answer_sinks: ContinuousAnswerSinks,
+    pub(crate) acks: AckSink,
+    pub(crate) logger: LogSink,
+}
+
+pub(crate) fn reason(
     cfg: timely::Config,
-    tbox_materialization: FirstStageMaterialization,
-    abox_materialization: SecondStageMaterialization,
-    tbox_input_source: TripleInputSource,
-    abox_input_source: TripleInputSource,
-    tbox_output_sink: TripleOutputSink,
-    abox_output_sink: TripleOutputSink,
-    done: DoneSink,
-    terminator: MasterSource,
-    logger: LogSink,
-) -> () {
+    logic: Logic,
+    options: Options,
+    sources: Sources,
+    sinks: Sinks,
+) {
+    let Logic {
+        tbox_materialization,
+        abox_materialization,
+        consistency_check,
+        provenance_tracking,
+        tbox_provenance_tracking,
+        why_not_candidates,
+    } = logic;
+    let Options {
+        explain,
+        index,
+        history,
+        tbox_shard,
+        abox_shard,
+        window,
+        ..
+    } = options;
+    let Sources {
+        tbox_input_source,
+        abox_input_source,
+        tbox_timed_input_source,
+        abox_timed_input_source,
+        ill_typed_input_source,
+        explain_input_source,
+        query_input_source,
+        continuous_query_input_source,
+        commands,
+    } = sources;
+    let Sinks {
+        tbox_output_sink,
+        abox_output_sink,
+        inconsistency_output_sink,
+        provenance_output_sink,
+        explanation_output_sink,
+        why_not_output_sink,
+        answer_output_sink,
+        continuous_answer_sinks,
+        acks,
+        logger,
+    } = sinks;
     timely::execute(cfg, move |worker: &mut Worker<Generic>| {
-        let (mut tbox_input_session, mut tbox_trace, tbox_probe, mut expanded_lists_trace) = worker
-            .dataflow_named::<usize, _, _>("tbox_materialization", |scope| {
-                let tbox_output_sink = tbox_output_sink.clone();
-                let (tbox_input_session, tbox_collection) = scope.new_collection::<Triple, isize>();
-                let (tbox_materialization, expanded_lists) = tbox_materialization(&tbox_collection);
-                (
-                    tbox_input_session,
-                    tbox_materialization.arrange_by_self().trace,
-                    tbox_materialization
-                        .distinct()
-                        .inspect_batch(move |_t, xs| {
-                            for ((s, p, o), time, diff) in xs {
-                                tbox_output_sink.send(((*s, *p, *o), *time, *diff)).unwrap()
-                            }
-                        })
-                        .probe(),
-                    expanded_lists.arrange_by_key().trace,
+        let (
+            mut tbox_input_session,
+            mut tbox_input_trace,
+            mut tbox_trace,
+            tbox_probe,
+            mut expanded_lists_trace,
+        ) = worker.dataflow_named::<u64, _, _>("tbox_materialization", |scope| {
+            let tbox_output_sink = tbox_output_sink.clone();
+            let (tbox_input_session, tbox_collection) = scope.new_collection::<Triple, isize>();
+            let (tbox_materialization, expanded_lists) = tbox_materialization(&tbox_collection);
+            (
+                tbox_input_session,
+                tbox_collection.arrange_by_self().trace,
+                tbox_materialization.arrange_by_self().trace,
+                tbox_materialization
+                    .distinct()
+                    .inspect_batch(move |_t, xs| {
+                        for ((s, p, o), time, diff) in xs {
+                            tbox_output_sink.send(((*s, *p, *o), *time, *diff)).unwrap()
+                        }
+                    })
+                    .probe(),
+                expanded_lists.arrange_by_key().trace,
+            )
+        });
+        let (
+            mut abox_input_session,
+            mut ill_typed_input_session,
+            mut continuous_query_input_session,
+            abox_probe,
+            inconsistency_probe,
+            provenance_probe,
+            continuous_answer_probe,
+            mut abox_input_trace,
+            mut index_traces,
+        ) = worker.dataflow_named::<u64, _, _>("abox_materialization", |scope| {
+            let abox_output_sink = abox_output_sink.clone();
+            let inconsistency_output_sink = inconsistency_output_sink.clone();
+            let provenance_output_sink = provenance_output_sink.clone();
+            let continuous_answer_sinks = continuous_answer_sinks.clone();
+            let (abox_input_session, abox_collection) = scope.new_collection::<Triple, isize>();
+            let (ill_typed_input_session, ill_typed_collection) =
+                scope.new_collection::<Tuple, isize>();
+            let (continuous_query_input_session, continuous_queries) =
+                scope.new_collection::<QueryRequest, isize>();
+            let tbox_collection = tbox_trace
+                .import(scope)
+                .as_collection(|(s, p, o), _v| (*s, *p, *o));
+            let expanded_lists_collection = expanded_lists_trace
+                .import(scope)
+                .as_collection(|head, tail| (*head, tail.clone()));
+            let materialization = abox_materialization(
+                &tbox_collection,
+                &expanded_lists_collection,
+                &abox_collection,
+            );
+            let inconsistencies = consistency_check(
+                &tbox_collection,
+                &expanded_lists_collection,
+                &materialization,
+                &ill_typed_collection,
+            );
+            let provenance = provenance_tracking(
+                &tbox_collection,
+                &expanded_lists_collection,
+                &materialization,
+            );
+            // Explain requests and one-shot queries are answered by dataflows of their own, over the
+            // input and the materialization as arranged here. Continuous queries are answered here,
+            // as the materialization changes.
+            let abox_input_trace = explain.then(|| abox_collection.arrange_by_self().trace);
+            let index =
+                (explain || index).then(|| Index::new(&tbox_collection.concat(&materialization)));
+            let continuous_answer_probe = index.as_ref().map(|index| {
+                answers(
+                    &continuous_queries.map(|(query, _epoch, patterns)| (query, patterns)),
+                    index,
                 )
+                .distinct()
+                .inspect_batch(move |_t, xs| {
+                    let continuous_answer_sinks = continuous_answer_sinks.lock().unwrap();
+                    for ((query, answer), time, diff) in xs {
+                        // Consumers may drop the receiving end before unregistering.
+                        if let Some(continuous_answer_sink) = continuous_answer_sinks.get(query) {
+                            continuous_answer_sink
+                                .send((answer.clone(), *time, *diff))
+                                .unwrap_or_default()
+                        }
+                    }
+                })
+                .probe()
             });
-        let (mut abox_input_session, abox_probe) =
-            worker.dataflow_named::<usize, _, _>("abox_materialization", |scope| {
-                let abox_output_sink = abox_output_sink.clone();
-                let (abox_input_session, abox_collection) = scope.new_collection::<Triple, isize>();
-                let tbox_collection = tbox_trace
-                    .import(scope)
-                    .as_collection(|(s, p, o), _v| (*s, *p, *o));
-                let expanded_lists_collection = expanded_lists_trace
-                    .import(scope)
-                    .as_collection(|head, tail| (*head, tail.clone()));
-                let materialization = abox_materialization(
-                    &tbox_collection,
-                    &expanded_lists_collection,
-                    &abox_collection,
-                );
-                (
-                    abox_input_session,
-                    materialization
-                        .distinct()
-                        .inspect_batch(move |_t, xs| {
-                            for ((s, p, o), time, diff) in xs {
-                                abox_output_sink.send(((*s, *p, *o), *time, *diff)).unwrap()
+            let index_traces = index.map(|index| index.traces());
+            (
+                abox_input_session,
+                ill_typed_input_session,
+                continuous_query_input_session,
+                materialization
+                    .distinct()
+                    .inspect_batch(move |_t, xs| {
+                        for ((s, p, o), time, diff) in xs {
+                            abox_output_sink.send(((*s, *p, *o), *time, *diff)).unwrap()
+                        }
+                    })
+                    .probe(),
+                inconsistencies
+                    .distinct()
+                    .inspect_batch(move |_t, xs| {
+                        for (inconsistency, time, diff) in xs {
+                            inconsistency_output_sink
+                                .send((inconsistency.clone(), *time, *diff))
+                                .unwrap()
+                        }
+                    })
+                    .probe(),
+                provenance
+                    .distinct()
+                    .inspect_batch(move |_t, xs| {
+                        if let Some(provenance_output_sink) = &provenance_output_sink {
+                            for (provenance, time, diff) in xs {
+                                provenance_output_sink
+                                    .send((provenance.clone(), *time, *diff))
+                                    .unwrap()
                             }
-                        })
-                        .probe(),
-                )
-            });
-        let mut last_run = false;
+                        }
+                    })
+                    .probe(),
+                continuous_answer_probe,
+                abox_input_trace,
+                index_traces,
+            )
+        });
+        let commands = commands[worker.index() % commands.len()].clone();
         let mut last_ts = 0;
         let mut data_ingested = 0;
-        let mut current_data_ingested = 0;
         let mut data_regurgitated = 0;
-        let mut current_data_regurgitated = 0;
-        let mut current_latency = 0;
         let mut total_latency = 0;
-        let mut round = 0;
         let mut files_loaded = 0;
-        let mut iterate = true;
+        let mut paused = false;
+        let mut shards_pending = tbox_shard.is_some() || abox_shard.is_some();
+        // Input taken in since the last commit.
+        let mut tbox_pending: Vec<(Triple, isize)> = vec![];
+        let mut abox_pending: Vec<(Triple, isize)> = vec![];
+        let mut ill_typed_pending: Vec<Tuple> = vec![];
+        // Timed input taken in since the last commit, for the stats.
+        let mut timed_data_ingested = 0;
+        let mut timed_data_regurgitated = 0;
+        let mut windowed = window.map(Windowed::new);
+        // Timed updates at an epoch that was closed already, which are left out, to be reported
+        // along with the next ack that takes in input.
+        let mut late_updates: Vec<String> = vec![];
 
         loop {
+            // Idle workers take in what is sent meanwhile, even when paused, so that the input
+            // channels do not fill up and block the driver on its way to the next command.
+            tbox_pending.extend(tbox_input_source.try_iter());
+            abox_pending.extend(abox_input_source.try_iter());
+            ill_typed_pending.extend(ill_typed_input_source.try_iter());
+            timed_updates(
+                &tbox_timed_input_source,
+                &mut tbox_input_session,
+                &mut timed_data_ingested,
+                &mut timed_data_regurgitated,
+                &mut late_updates,
+                None,
+            );
+            timed_updates(
+                &abox_timed_input_source,
+                &mut abox_input_session,
+                &mut timed_data_ingested,
+                &mut timed_data_regurgitated,
+                &mut late_updates,
+                windowed.as_mut(),
+            );
+            let command = if paused {
+                match commands.recv_timeout(Duration::from_millis(10)) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => None,
+                }
+            } else {
+                // Unless paused, they carry on with whatever their dataflows have left to do.
+                worker.step_or_park(Some(Duration::from_millis(10)));
+                match commands.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(TryRecvError::Empty) => continue,
+                    Err(TryRecvError::Disconnected) => None,
+                }
+            };
+            // The driver going away counts as being told to shut down.
+            let command = command.unwrap_or(Command::Shutdown);
+            paused = command == Command::Pause;
+
             let now = Instant::now();
-            if !abox_input_source.is_empty() || !tbox_input_source.is_empty() {
-                let mut current_local_data_ingested = 0;
-                tbox_input_source.try_iter().for_each(|triple| {
+            let mut current_data_ingested = 0;
+            let mut current_data_regurgitated = 0;
+            let mut errors: Vec<String> = vec![];
+            // Timed input is taken in as soon as it is read, and untimed input is held back for
+            // the next commit.
+            timed_updates(
+                &tbox_timed_input_source,
+                &mut tbox_input_session,
+                &mut timed_data_ingested,
+                &mut timed_data_regurgitated,
+                &mut late_updates,
+                None,
+            );
+            timed_updates(
+                &abox_timed_input_source,
+                &mut abox_input_session,
+                &mut timed_data_ingested,
+                &mut timed_data_regurgitated,
+                &mut late_updates,
+                windowed.as_mut(),
+            );
+            // Every round advances all of the input sessions to the same epoch, on every worker.
+            // Queries read what is there already, and leave every epoch open.
+            let next_epoch = match command {
+                Command::Commit => Some(last_ts + 1),
+                Command::AdvanceTo(epoch) if epoch > last_ts => Some(epoch),
+                _ => None,
+            };
+            // Shutting down takes in whatever is left as well, for it to be worked through.
+            if let Command::Commit | Command::AdvanceTo(_) | Command::Shutdown = command {
+                current_data_ingested += timed_data_ingested;
+                current_data_regurgitated += timed_data_regurgitated;
+                timed_data_ingested = 0;
+                timed_data_regurgitated = 0;
+                errors.append(&mut late_updates);
+                // Sharded inputs are read by every worker on its own, instead of going
+                // through the channels, and all make it into the first commit.
+                // A shard that fails to load is left out, and reported along with the ack.
+                if shards_pending {
+                    if let Some(tbox_shard) = &tbox_shard {
+                        match tbox_shard(worker.index(), worker.peers()) {
+                            Ok(triples) => triples.into_iter().for_each(|triple| {
+                                tbox_input_session.insert(triple);
+                                current_data_ingested += 1;
+                            }),
+                            Err(error) => errors.push(error),
+                        }
+                    }
+                    if let Some(abox_shard) = &abox_shard {
+                        match abox_shard(worker.index(), worker.peers()) {
+                            Ok(triples) => triples.into_iter().for_each(|triple| {
+                                abox_input_session.insert(triple);
+                                if let Some(windowed) = &mut windowed {
+                                    windowed.insert(triple, last_ts);
+                                }
+                                current_data_ingested += 1;
+                            }),
+                            Err(error) => errors.push(error),
+                        }
+                    }
+                    shards_pending = false;
+                }
+                tbox_pending.extend(tbox_input_source.try_iter());
+                abox_pending.extend(abox_input_source.try_iter());
+                ill_typed_pending.extend(ill_typed_input_source.try_iter());
+                tbox_pending.drain(..).for_each(|triple| {
                     tbox_input_session.update(triple.0, triple.1);
-                    current_local_data_ingested += 1;
+                    current_data_ingested += 1;
                 });
-
-                tbox_input_session.advance_to(*tbox_input_session.epoch() + 1);
-                last_ts += 1;
-                tbox_input_session.flush();
-
-                abox_input_source.try_iter().for_each(|triple| {
-                    abox_input_session.update(triple.0, triple.1);
-                    if triple.1 > 0 {
-                        current_local_data_ingested += 1;
-                    } else {
-                        data_regurgitated += 1;
+                abox_pending.drain(..).for_each(|(triple, diff)| {
+                    if diff > 0 {
+                        abox_input_session.update(triple, diff);
+                        if let Some(windowed) = &mut windowed {
+                            windowed.insert(triple, last_ts);
+                        }
+                        current_data_ingested += 1;
+                    } else if windowed.as_mut().is_none_or(|windowed| {
+                        windowed.retract(triple, last_ts, &mut abox_input_session)
+                    }) {
+                        abox_input_session.update(triple, diff);
                         current_data_regurgitated += 1;
                     }
                 });
+                ill_typed_pending
+                    .drain(..)
+                    .for_each(|literal| ill_typed_input_session.insert(literal));
 
-                abox_input_session.advance_to(*abox_input_session.epoch() + 1);
+                // Windowed abox facts are retracted by the engine once they fall out of the
+                // window, unless they were retracted by hand already.
+                if let (Some(windowed), false) = (&mut windowed, command == Command::Shutdown) {
+                    windowed
+                        .expire(*abox_input_session.epoch())
+                        .into_iter()
+                        .for_each(|(triple, time)| {
+                            abox_input_session.update_at(triple, time, -1);
+                            current_data_regurgitated += 1;
+                        });
+                }
+            }
+            if let Some(next_epoch) = next_epoch {
+                tbox_input_session.advance_to(next_epoch);
+                last_ts = next_epoch;
+                tbox_input_session.flush();
+
+                abox_input_session.advance_to(next_epoch);
                 abox_input_session.flush();
 
+                ill_typed_input_session.advance_to(next_epoch);
+                ill_typed_input_session.flush();
+
+                continuous_query_input_source
+                    .try_iter()
+                    .for_each(|(query, diff)| continuous_query_input_session.update(query, diff));
+                continuous_query_input_session.advance_to(next_epoch);
+                continuous_query_input_session.flush();
+
                 worker.step_or_park_while(Some(Duration::from_millis(50)), || {
                     tbox_probe.less_than(tbox_input_session.time())
                 });
                 worker.step_or_park_while(Some(Duration::from_millis(50)), || {
                     abox_probe.less_than(abox_input_session.time())
+                        || inconsistency_probe.less_than(abox_input_session.time())
+                        || provenance_probe.less_than(abox_input_session.time())
+                        || continuous_answer_probe
+                            .as_ref()
+                            .is_some_and(|probe| probe.less_than(abox_input_session.time()))
                 });
 
-                round += 1;
-                let round_elapsed_time = now.elapsed().as_millis();
-                data_ingested += current_local_data_ingested;
-                current_data_ingested += current_local_data_ingested;
-                current_latency += round_elapsed_time;
-                total_latency += round_elapsed_time;
-                iterate = false;
+                // The traces are only read as of the last round.
+                let readable = [last_ts - 1];
+                for trace in [&mut tbox_input_trace, &mut tbox_trace] {
+                    trace.set_logical_compaction(AntichainRef::new(&readable));
+                    trace.set_physical_compaction(AntichainRef::new(&readable));
+                }
+                expanded_lists_trace.set_logical_compaction(AntichainRef::new(&readable));
+                expanded_lists_trace.set_physical_compaction(AntichainRef::new(&readable));
+                if let Some(abox_input_trace) = &mut abox_input_trace {
+                    abox_input_trace.set_logical_compaction(AntichainRef::new(&readable));
+                    abox_input_trace.set_physical_compaction(AntichainRef::new(&readable));
+                }
+                // The index goes back as many epochs as there is history kept for.
+                if let Some(index_traces) = &mut index_traces {
+                    index_traces.compact((last_ts - 1).saturating_sub(history));
+                }
             }
 
-            if abox_input_source.is_empty() && tbox_input_source.is_empty() && !last_run && !iterate
-            {
+            // Explain requests are answered as of the last round, by walking back from the
+            // requested triples in a dataflow that reads the traces, and goes away once through.
+            // Every worker builds it, whether or not it read any of the requests.
+            if command == Command::Query {
+                let requests: Vec<ExplainRequest> = explain_input_source.try_iter().collect();
+                if let (Some(abox_input_trace), Some(index_traces), true) =
+                    (&mut abox_input_trace, &mut index_traces, last_ts > 0)
+                {
+                    let (mut requests_session, explanation_probe, mut buttons) = worker
+                        .dataflow_named::<u64, _, _>("explain", |scope| {
+                            let explanation_output_sink = explanation_output_sink.clone();
+                            let why_not_output_sink = why_not_output_sink.clone();
+                            let (requests_session, requests) =
+                                scope.new_collection::<ExplainRequest, isize>();
+                            let (tbox_inputs, tbox_inputs_button) =
+                                tbox_input_trace.import_core(scope, "tbox_inputs");
+                            let (abox_inputs, abox_inputs_button) =
+                                abox_input_trace.import_core(scope, "abox_inputs");
+                            let (tbox, tbox_button) = tbox_trace.import_core(scope, "tbox");
+                            let (lists, lists_button) =
+                                expanded_lists_trace.import_core(scope, "expanded_lists");
+                            let (index, mut buttons) = index_traces.import(scope);
+                            buttons.extend([
+                                tbox_inputs_button,
+                                abox_inputs_button,
+                                tbox_button,
+                                lists_button,
+                            ]);
+                            let tbox = tbox.as_collection(|(s, p, o), _v| (*s, *p, *o));
+                            let lists = lists.as_collection(|head, tail| (*head, tail.clone()));
+
+                            let explanations = explanations(
+                                &requests,
+                                &tbox_inputs,
+                                &abox_inputs,
+                                &index,
+                                &tbox_provenance_tracking(&tbox),
+                                |requests| why_not_candidates(&tbox, &lists, requests),
+                            );
+                            let why_nots = missing_premises(
+                                &why_not_candidates(&tbox, &lists, &requests),
+                                &index,
+                            );
+
+                            let mut explanation_probe = ProbeHandle::new();
+                            explanations
+                                .distinct()
+                                .inspect_batch(move |_t, xs| {
+                                    for (explanation, time, diff) in xs {
+                                        explanation_output_sink
+                                            .send((explanation.clone(), *time, *diff))
+                                            .unwrap()
+                                    }
+                                })
+                                .probe_with(&mut explanation_probe);
+                            why_nots
+                                .distinct()
+                                .inspect_batch(move |_t, xs| {
+                                    for (why_not, time, diff) in xs {
+                                        why_not_output_sink
+                                            .send((why_not.clone(), *time, *diff))
+                                            .unwrap()
+                                    }
+                                })
+                                .probe_with(&mut explanation_probe);
+                            (requests_session, explanation_probe, buttons)
+                        });
+                    requests_session.advance_to(last_ts - 1);
+                    requests
+                        .into_iter()
+                        .for_each(|request| requests_session.insert(request));
+                    requests_session.close();
+                    // The imports only stop once everything up to the last round is read, and the
+                    // walk takes an epoch past it for every step.
+                    worker.step_while(|| explanation_probe.less_than(&last_ts));
+                    buttons.iter_mut().for_each(|button| button.press());
+                    worker.step_while(|| !explanation_probe.done());
+                }
+
+                // Queries are answered the same way, each as of its own epoch, by reading the index
+                // up to it.
+                let queries: Vec<QueryRequest> = query_input_source.try_iter().collect();
+                if let (Some(index_traces), true) = (&mut index_traces, last_ts > 0) {
+                    let last = last_ts - 1;
+                    let kept = last.saturating_sub(history);
+                    let (mut queries_session, answer_probe, mut buttons) = worker
+                        .dataflow_named::<u64, _, _>("query", |scope| {
+                            let answer_output_sink = answer_output_sink.clone();
+                            let (queries_session, queries) =
+                                scope.new_collection::<((usize, u64), Vec<Pattern>), isize>();
+                            let (index, buttons) = index_traces.import(scope);
+
+                            let mut answer_probe = ProbeHandle::new();
+                            answers(&queries, &index)
+                                .distinct()
+                                .inspect_batch(move |_t, xs| {
+                                    for (((query, epoch), answer), time, diff) in xs {
+                                        // Changes past the epoch of the query are left out.
+                                        if time <= epoch {
+                                            answer_output_sink
+                                                .send(((*query, answer.clone()), *time, *diff))
+                                                .unwrap()
+                                        }
+                                    }
+                                })
+                                .probe_with(&mut answer_probe);
+                            (queries_session, answer_probe, buttons)
+                        });
+                    queries_session.advance_to(kept);
+                    queries.into_iter().for_each(|(query, epoch, patterns)| {
+                        let epoch = epoch.clamp(kept, last);
+                        queries_session.update_at(((query, epoch), patterns), epoch, 1);
+                    });
+                    queries_session.close();
+                    worker.step_while(|| answer_probe.less_than(&last_ts));
+                    buttons.iter_mut().for_each(|button| button.press());
+                    worker.step_while(|| !answer_probe.done());
+                }
+            }
+
+            // The sessions are closed once out of the loop, and everything up to the last epoch is
+            // worked through before the ack.
+            if command == Command::Shutdown {
+                abox_input_session.advance_to(last_ts + 1);
+                tbox_input_session.advance_to(last_ts + 1);
+                ill_typed_input_session.advance_to(last_ts + 1);
+                continuous_query_input_session.advance_to(last_ts + 1);
+                abox_input_session.flush();
+                tbox_input_session.flush();
+                ill_typed_input_session.flush();
+                continuous_query_input_session.flush();
+                worker.step_while(|| tbox_probe.less_than(&(last_ts + 1)));
+                worker.step_while(|| abox_probe.less_than(&(last_ts + 1)));
+                worker.step_while(|| inconsistency_probe.less_than(&(last_ts + 1)));
+                worker.step_while(|| provenance_probe.less_than(&(last_ts + 1)));
+                if let Some(continuous_answer_probe) = &continuous_answer_probe {
+                    worker.step_while(|| continuous_answer_probe.less_than(&(last_ts + 1)));
+                }
+            }
+
+            let current_latency = now.elapsed().as_millis();
+            data_ingested += current_data_ingested;
+            data_regurgitated += current_data_regurgitated;
+            total_latency += current_latency;
+
+            if let Command::Commit | Command::AdvanceTo(_) = command {
                 let log = RuntimeLog::new(
                     files_loaded,
                     current_latency,
@@ -133,33 +622,40 @@ pub fn reason(
                     current_data_regurgitated,
                     worker.index(),
                 );
-                logger.send(log.to_string());
+                let _ = logger.send(log.to_string());
                 files_loaded += 1;
-                done.send(());
-                if let Ok(command) = terminator.recv() {
-                    match command.as_str() {
-                        "STOP" => last_run = true,
-                        _ => {
-                            current_latency = 0;
-                            current_data_ingested = 0;
-                            current_data_regurgitated = 0;
-                            iterate = true;
-                            continue;
-                        }
-                    }
-                }
             }
 
-            worker.step();
+            let ack = if command == Command::Checkpoint {
+                Ack {
+                    worker: worker.index(),
+                    epoch: last_ts,
+                    latency: total_latency,
+                    added: data_ingested,
+                    removed: data_regurgitated,
+                    errors,
+                }
+            } else {
+                Ack {
+                    worker: worker.index(),
+                    epoch: last_ts,
+                    latency: current_latency,
+                    added: current_data_ingested,
+                    removed: current_data_regurgitated,
+                    errors,
+                }
+            };
+            // The driver may be gone already, if it is what the shutdown came from.
+            acks.send(ack).unwrap_or_default();
 
-            if last_run {
-                abox_input_session.close();
-                tbox_input_session.close();
-                worker.step_while(|| tbox_probe.less_than(&(last_ts + 1)));
-                worker.step_while(|| abox_probe.less_than(&(last_ts + 1)));
+            if command == Command::Shutdown {
                 break;
             }
         }
+        abox_input_session.close();
+        tbox_input_session.close();
+        ill_typed_input_session.close();
+        continuous_query_input_session.close();
 
         println!(
             "Total latency and triples processed at worker {}:{} ms, {} triples",
@@ -170,3 +666,149 @@ pub fn reason(
     })
     .unwrap();
 }
+
+// The abox facts in the window, one per insertion, for the engine to retract once they fall out of
+// it.
+struct Windowed {
+    window: Window,
+    // Taken in since the last commit, at their epochs
+    taken_in: Vec<(Triple, u64)>,
+    // Taken in by each of the last commits, at their epochs, with Window::Epochs
+    commits: VecDeque<Vec<(Triple, u64)>>,
+    // The epochs that those taken in before the last commit are retracted at, with
+    // Window::Duration
+    expiries: HashMap<Triple, Vec<u64>>,
+}
+
+impl Windowed {
+    fn new(window: Window) -> Self {
+        Self {
+            window,
+            taken_in: vec![],
+            commits: VecDeque::new(),
+            expiries: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, triple: Triple, time: u64) {
+        self.taken_in.push((triple, time));
+    }
+
+    // Whether a retraction at the given epoch is to go in, which it only is for a fact that is
+    // still in the window, calling off the retraction by the engine that was due for it instead.
+    // The fact having been retracted already, by hand or by the engine, it would only take the
+    // count of the triple below zero, and hide it from a later insertion.
+    fn retract(
+        &mut self,
+        triple: Triple,
+        time: u64,
+        input_session: &mut InputSession<u64, Triple, isize>,
+    ) -> bool {
+        if let Some(position) = self
+            .taken_in
+            .iter()
+            .position(|(taken_in, since)| *taken_in == triple && *since <= time)
+        {
+            self.taken_in.swap_remove(position);
+            return true;
+        }
+        match self.window {
+            Window::Duration(_) => {
+                let Some(expiries) = self.expiries.get_mut(&triple) else {
+                    return false;
+                };
+                let Some(position) = expiries.iter().position(|expiry| *expiry > time) else {
+                    return false;
+                };
+                // Due at an epoch past the retraction, which is still open.
+                input_session.update_at(triple, expiries.swap_remove(position), 1);
+                if expiries.is_empty() {
+                    self.expiries.remove(&triple);
+                }
+                true
+            }
+            Window::Epochs(_) => self.commits.iter_mut().any(|commit| {
+                commit
+                    .iter()
+                    .position(|(windowed, since)| *windowed == triple && *since <= time)
+                    .map(|position| commit.swap_remove(position))
+                    .is_some()
+            }),
+        }
+    }
+
+    // Takes the facts taken in since the last commit into the window, and returns those that fall
+    // out of it, with the epoch each is to be retracted at. With Window::Duration, that is the end
+    // of its span, which is still open, as it was taken in at an epoch that was open since the
+    // last commit. With Window::Epochs, it is the epoch of the commit, or the one it was taken in
+    // at if that is later, so that the retraction does not come before the insertion.
+    fn expire(&mut self, epoch: u64) -> Vec<(Triple, u64)> {
+        match self.window {
+            Window::Duration(duration) => {
+                // Those that are through already cannot be called off anymore.
+                self.expiries.retain(|_triple, expiries| {
+                    expiries.retain(|expiry| *expiry >= epoch);
+                    !expiries.is_empty()
+                });
+                self.taken_in
+                    .drain(..)
+                    .map(|(triple, time)| {
+                        let expiry = time + duration;
+                        self.expiries.entry(triple).or_default().push(expiry);
+                        (triple, expiry)
+                    })
+                    .collect()
+            }
+            Window::Epochs(epochs) => {
+                self.commits.push_back(self.taken_in.drain(..).collect());
+                if self.commits.len() > epochs {
+                    self.commits
+                        .pop_front()
+                        .unwrap()
+                        .into_iter()
+                        .map(|(triple, time)| (triple, epoch.max(time)))
+                        .collect()
+                } else {
+                    vec![]
+                }
+            }
+        }
+    }
+}
+
+// Takes in the timed updates read so far at their own epochs, keeping track of the windowed facts
+// if there is a window. Those at an epoch that was closed already are left out, and reported.
+fn timed_updates(
+    timed_input_source: &TimedTripleInputSource,
+    input_session: &mut InputSession<u64, Triple, isize>,
+    ingested: &mut usize,
+    regurgitated: &mut usize,
+    late: &mut Vec<String>,
+    mut windowed: Option<&mut Windowed>,
+) {
+    timed_input_source
+        .try_iter()
+        .for_each(|(triple, time, diff)| {
+            let epoch = *input_session.epoch();
+            if time < epoch {
+                late.push(format!(
+                    "left out an update to {:?} at epoch {}, as every epoch before {} is closed",
+                    triple, time, epoch
+                ));
+                return;
+            }
+            if diff > 0 {
+                input_session.update_at(triple, time, diff);
+                if let Some(windowed) = windowed.as_mut() {
+                    windowed.insert(triple, time);
+                }
+                *ingested += 1;
+            } else if windowed
+                .as_mut()
+                .is_none_or(|windowed| windowed.retract(triple, time, input_session))
+            {
+                input_session.update_at(triple, time, diff);
+                *regurgitated += 1;
+            }
+        });
+}
@@ -0,0 +1,875 @@
+use std::collections::HashSet;
+use std::panic;
+use std::thread::{self, JoinHandle};
+
+use crate::control::Controller;
+use crate::entrypoint::{entrypoint, Engine, Handles};
+use crate::explain::Explainer;
+use crate::materialization::rdfs::container_membership_axiomatic_triples;
+use crate::model::types::{
+    Ack, Command, ContainerMembershipTest, IllTypedInputSink, InconsistencyOutputSource,
+    LiteralEntailer, LogSource, ProvenanceOutputSource, RdfsEntailment, ShardLoader,
+    TimedTripleInputSink, Triple, TripleInputSink, TripleOutputSource, Tuple, Window,
+};
+use crate::query::Querier;
+use crate::reason::Options;
+
+// Sets up a reasoner, by default with a single worker thread running RDFS.
+pub struct ReasonerBuilder {
+    config: timely::Config,
+    engine: Engine,
+    batch_size: usize,
+    options: Options,
+    literal_entailer: Option<LiteralEntailer>,
+    container_membership: Option<ContainerMembershipTest>,
+}
+
+impl Default for ReasonerBuilder {
+    fn default() -> Self {
+        Self {
+            config: timely::Config::thread(),
+            engine: Engine::RDFS,
+            batch_size: 1024,
+            options: Options {
+                provenance: false,
+                explain: false,
+                index: false,
+                history: 0,
+                tbox_shard: None,
+                abox_shard: None,
+                window: None,
+            },
+            literal_entailer: None,
+            container_membership: None,
+        }
+    }
+}
+
+impl ReasonerBuilder {
+    pub fn config(mut self, config: timely::Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    // The capacity of the tbox and abox input channels, past which inserting and retracting block
+    // until the workers catch up.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn provenance(mut self, provenance: bool) -> Self {
+        self.options.provenance = provenance;
+        self
+    }
+
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.options.explain = explain;
+        self
+    }
+
+    // Keeps the materialization indexed, for the querier to answer queries over, which it does not
+    // otherwise. Explaining keeps it indexed as well.
+    pub fn index(mut self, index: bool) -> Self {
+        self.options.index = index;
+        self
+    }
+
+    // How many epochs before the last round the index goes back, for the querier to answer queries
+    // as of them.
+    pub fn history(mut self, history: u64) -> Self {
+        self.options.history = history;
+        self
+    }
+
+    pub fn tbox_shard(mut self, tbox_shard: ShardLoader) -> Self {
+        self.options.tbox_shard = Some(tbox_shard);
+        self
+    }
+
+    pub fn abox_shard(mut self, abox_shard: ShardLoader) -> Self {
+        self.options.abox_shard = Some(abox_shard);
+        self
+    }
+
+    // Has the engine retract abox facts once they fall out of the window. Retracting one by hand
+    // before then calls off its retraction by the engine, and retracting one that is not in the
+    // window anymore does nothing.
+    pub fn window(mut self, window: Window) -> Self {
+        self.options.window = Some(window);
+        self
+    }
+
+    // Has the datatype rules run over every literal that comes in through insert_tbox, insert_abox
+    // and the like, with their entailments sent along. Shards do not go through it.
+    pub fn literal_entailer(mut self, literal_entailer: LiteralEntailer) -> Self {
+        self.literal_entailer = Some(literal_entailer);
+        self
+    }
+
+    // Has the axiomatic triples of every rdf:_n property that comes in through insert_tbox,
+    // insert_abox and the like sent along, the first time it does, under the full RDFS entailment
+    // with its axiomatic triples. Shards do not go through it.
+    pub fn container_membership(mut self, container_membership: ContainerMembershipTest) -> Self {
+        self.container_membership = Some(container_membership);
+        self
+    }
+
+    pub fn build(self) -> Reasoner {
+        let container_membership = self.container_membership.filter(|_| {
+            matches!(
+                self.engine,
+                Engine::RDFSFull(RdfsEntailment {
+                    axiomatic_triples: true,
+                    ..
+                })
+            )
+        });
+        // Shards are taken in by the first commit, like anything sent before it.
+        let pending = self.options.tbox_shard.is_some() || self.options.abox_shard.is_some();
+        let Handles {
+            tbox_input_sink,
+            abox_input_sink,
+            tbox_timed_input_sink,
+            abox_timed_input_sink,
+            ill_typed_input_sink,
+            tbox_output_source,
+            abox_output_source,
+            inconsistency_output_source,
+            provenance_output_source,
+            explainer,
+            querier,
+            controller,
+            log_source,
+            join_handle,
+        } = entrypoint(self.config, self.batch_size, self.engine, self.options);
+        Reasoner {
+            tbox_input_sink,
+            abox_input_sink,
+            tbox_timed_input_sink,
+            abox_timed_input_sink,
+            ill_typed_input_sink,
+            tbox_output_source,
+            abox_output_source,
+            inconsistency_output_source,
+            provenance_output_source,
+            explainer,
+            querier,
+            controller,
+            log_source,
+            literal_entailer: self.literal_entailer,
+            container_membership,
+            container_membership_properties: HashSet::new(),
+            join_handle: Some(join_handle),
+            pending,
+            frontier: 0,
+            errors: vec![],
+        }
+    }
+}
+
+// A running engine. Changes are sent to it as they come, and every one sent before a commit is
+// reflected in the output channels once the commit returns, at the epoch that it returns.
+pub struct Reasoner {
+    tbox_input_sink: TripleInputSink,
+    abox_input_sink: TripleInputSink,
+    tbox_timed_input_sink: TimedTripleInputSink,
+    abox_timed_input_sink: TimedTripleInputSink,
+    ill_typed_input_sink: IllTypedInputSink,
+    tbox_output_source: TripleOutputSource,
+    abox_output_source: TripleOutputSource,
+    inconsistency_output_source: InconsistencyOutputSource,
+    provenance_output_source: ProvenanceOutputSource,
+    explainer: Explainer,
+    querier: Querier,
+    controller: Controller,
+    log_source: LogSource,
+    literal_entailer: Option<LiteralEntailer>,
+    container_membership: Option<ContainerMembershipTest>,
+    // The rdf:_n properties whose axiomatic triples were sent already
+    container_membership_properties: HashSet<u32>,
+    // None once shut down
+    join_handle: Option<JoinHandle<()>>,
+    // Whether anything was sent since the last commit
+    pending: bool,
+    // The first epoch that is still open
+    frontier: u64,
+    // What the workers reported they could not take in
+    errors: Vec<String>,
+}
+
+impl Reasoner {
+    pub fn builder() -> ReasonerBuilder {
+        ReasonerBuilder::default()
+    }
+
+    pub fn insert_tbox(&mut self, triple: Triple) {
+        self.update(triple, 1, true)
+    }
+
+    pub fn insert_abox(&mut self, triple: Triple) {
+        self.update(triple, 1, false)
+    }
+
+    pub fn retract_tbox(&mut self, triple: Triple) {
+        self.update(triple, -1, true)
+    }
+
+    pub fn retract_abox(&mut self, triple: Triple) {
+        self.update(triple, -1, false)
+    }
+
+    fn update(&mut self, triple: Triple, diff: isize, tbox: bool) {
+        self.assert_running();
+        let (mut tbox_entailed, ill_typed) = self.entail_literals(triple, diff);
+        tbox_entailed.extend(self.container_membership_axioms(triple, diff));
+        let input_sink = if tbox {
+            &self.tbox_input_sink
+        } else {
+            &self.abox_input_sink
+        };
+        input_sink.send((triple, diff)).unwrap();
+        for triple in tbox_entailed {
+            self.tbox_input_sink.send((triple, 1)).unwrap();
+        }
+        for literal in ill_typed {
+            self.ill_typed_input_sink.send(literal).unwrap();
+        }
+        self.pending = true;
+    }
+
+    // Reports a literal that is not in the value space of the datatype it claims to have, alongside
+    // that datatype, as a dt-not-type inconsistency as of the next commit, without asserting
+    // anything about it. Those that come in through a `LiteralEntailer` are reported on their own.
+    pub fn insert_ill_typed(&mut self, literal: Tuple) {
+        self.assert_running();
+        self.ill_typed_input_sink.send(literal).unwrap();
+        self.pending = true;
+    }
+
+    // What the datatype rules entail from the literals of an inserted triple, and which of them
+    // are ill-typed. It is never retracted, as it holds of the literals alone.
+    fn entail_literals(&mut self, (s, _p, o): Triple, diff: isize) -> (Vec<Triple>, Vec<Tuple>) {
+        let mut tbox_entailed = vec![];
+        let mut ill_typed = vec![];
+        if let (Some(literal_entailer), true) = (&mut self.literal_entailer, diff > 0) {
+            for term in [s, o] {
+                let (tbox, literals) = literal_entailer(term);
+                tbox_entailed.extend(tbox);
+                ill_typed.extend(literals);
+            }
+        }
+        (tbox_entailed, ill_typed)
+    }
+
+    // The axiomatic triples of the rdf:_n properties among the terms of an inserted triple, that
+    // were not sent before. Like the entailments of literals, they are never retracted.
+    fn container_membership_axioms(&mut self, (s, p, o): Triple, diff: isize) -> Vec<Triple> {
+        let mut axioms = vec![];
+        if let (Some(container_membership), true) = (&mut self.container_membership, diff > 0) {
+            for term in [s, p, o] {
+                if container_membership(term) && self.container_membership_properties.insert(term) {
+                    axioms.extend(container_membership_axiomatic_triples(term));
+                }
+            }
+        }
+        axioms
+    }
+
+    // Changes at an epoch of the caller's choosing, which are reported at the same epoch, once every
+    // epoch up to it is closed by advance_to. Those at an epoch that is closed already are left
+    // out, and reported through errors by the next commit or advance_to.
+    pub fn insert_tbox_at(&mut self, triple: Triple, epoch: u64) {
+        self.update_at(triple, epoch, 1, true)
+    }
+
+    pub fn insert_abox_at(&mut self, triple: Triple, epoch: u64) {
+        self.update_at(triple, epoch, 1, false)
+    }
+
+    pub fn retract_tbox_at(&mut self, triple: Triple, epoch: u64) {
+        self.update_at(triple, epoch, -1, true)
+    }
+
+    pub fn retract_abox_at(&mut self, triple: Triple, epoch: u64) {
+        self.update_at(triple, epoch, -1, false)
+    }
+
+    fn update_at(&mut self, triple: Triple, epoch: u64, diff: isize, tbox: bool) {
+        self.assert_running();
+        let (mut tbox_entailed, ill_typed) = self.entail_literals(triple, diff);
+        tbox_entailed.extend(self.container_membership_axioms(triple, diff));
+        let timed_input_sink = if tbox {
+            &self.tbox_timed_input_sink
+        } else {
+            &self.abox_timed_input_sink
+        };
+        timed_input_sink.send((triple, epoch, diff)).unwrap();
+        for triple in tbox_entailed {
+            self.tbox_timed_input_sink.send((triple, epoch, 1)).unwrap();
+        }
+        // Ill-typed literals are not tied to an epoch, and are reported as of the next round.
+        for literal in ill_typed {
+            self.ill_typed_input_sink.send(literal).unwrap();
+        }
+        self.pending = true;
+    }
+
+    // Takes in everything sent so far, closes every epoch before the given one, and waits for
+    // the changes at them to be worked through. Returns the first epoch still open, which stays
+    // put if it is past the given one already.
+    pub fn advance_to(&mut self, epoch: u64) -> u64 {
+        self.assert_running();
+        let acks = self.controller.broadcast(Command::AdvanceTo(epoch));
+        self.frontier = acks[0].epoch;
+        self.collect_errors(acks);
+        self.pending = false;
+        self.frontier
+    }
+
+    pub fn frontier(&self) -> u64 {
+        self.frontier
+    }
+
+    // Starts a transaction, whose changes all go into the same epoch once it is committed.
+    pub fn begin(&mut self) -> Transaction<'_> {
+        Transaction {
+            reasoner: self,
+            updates: vec![],
+        }
+    }
+
+    // Waits for every change sent so far to be worked through, and returns the epoch that they are
+    // reported at, which is the first one still open before the commit.
+    pub fn commit(&mut self) -> u64 {
+        self.assert_running();
+        let acks = self.controller.broadcast(Command::Commit);
+        // Every worker is at the same epoch after a round.
+        self.frontier = acks[0].epoch;
+        self.collect_errors(acks);
+        self.pending = false;
+        self.frontier - 1
+    }
+
+    fn collect_errors(&mut self, acks: Vec<Ack>) {
+        self.errors
+            .extend(acks.into_iter().flat_map(|ack| ack.errors.into_iter()));
+    }
+
+    // Waits for the changes at the given epoch to be worked through, taking in everything sent so
+    // far like advance_to, and closing every epoch up to it, if it is still open.
+    pub fn wait_for(&mut self, epoch: u64) {
+        if self.frontier <= epoch {
+            self.advance_to(epoch + 1);
+        }
+    }
+
+    // Has the workers leave the work of their dataflows until the next command, such as a commit,
+    // while still reading whatever is sent, so that inserting and retracting do not block.
+    pub fn pause(&mut self) {
+        self.assert_running();
+        self.controller.broadcast(Command::Pause);
+    }
+
+    // What each worker has taken in and spent since the start.
+    pub fn checkpoint(&mut self) -> Vec<Ack> {
+        self.assert_running();
+        self.controller.broadcast(Command::Checkpoint)
+    }
+
+    // Every change to the materialized tbox, with its epoch.
+    pub fn tbox_changes(&self) -> &TripleOutputSource {
+        &self.tbox_output_source
+    }
+
+    // Every change to the materialized abox, with its epoch.
+    pub fn abox_changes(&self) -> &TripleOutputSource {
+        &self.abox_output_source
+    }
+
+    pub fn inconsistencies(&self) -> &InconsistencyOutputSource {
+        &self.inconsistency_output_source
+    }
+
+    // Empty, unless the reasoner was built with provenance.
+    pub fn provenance(&self) -> &ProvenanceOutputSource {
+        &self.provenance_output_source
+    }
+
+    // Whatever the workers of this process could not take in so far, such as shards that failed to
+    // load, or changes at an epoch that was closed already, which were left out.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    // A log line per worker and commit.
+    pub fn logs(&self) -> &LogSource {
+        &self.log_source
+    }
+
+    // Explain requests do not take in anything left to commit.
+    pub fn explainer(&mut self) -> &mut Explainer {
+        self.assert_running();
+        &mut self.explainer
+    }
+
+    // Queries do not take in anything left to commit.
+    pub fn querier(&mut self) -> &mut Querier {
+        self.assert_running();
+        &mut self.querier
+    }
+
+    // Commits whatever is pending, and waits for the workers to stop. The output channels can
+    // still be read afterwards, and shutting down again does nothing, but anything else that
+    // reaches the workers panics from then on.
+    pub fn shutdown(&mut self) {
+        if self.join_handle.is_some() {
+            if self.pending {
+                self.commit();
+            }
+            self.controller.broadcast(Command::Shutdown);
+            if let Some(Err(panic)) = self.join_handle.take().map(JoinHandle::join) {
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    fn assert_running(&self) {
+        assert!(
+            self.join_handle.is_some(),
+            "the reasoner has been shut down"
+        );
+    }
+}
+
+// Dropping the reasoner shuts it down as well, so that the workers are not left running. As
+// panicking in a drop aborts, it gives up on the workers if they are gone already, and leaves
+// whatever is pending uncommitted if it is dropped while panicking.
+impl Drop for Reasoner {
+    fn drop(&mut self) {
+        if let Some(join_handle) = self.join_handle.take() {
+            if self.pending && !thread::panicking() {
+                self.controller.try_broadcast(Command::Commit);
+            }
+            if self.controller.try_broadcast(Command::Shutdown).is_some() {
+                join_handle.join().unwrap_or_default();
+            }
+        }
+    }
+}
+
+// Changes held back until they are committed together, and dropped along with the transaction
+// otherwise.
+pub struct Transaction<'a> {
+    reasoner: &'a mut Reasoner,
+    // The change to each triple, and whether it goes to the tbox
+    updates: Vec<(Triple, isize, bool)>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn insert_tbox(&mut self, triple: Triple) {
+        self.updates.push((triple, 1, true))
+    }
+
+    pub fn insert_abox(&mut self, triple: Triple) {
+        self.updates.push((triple, 1, false))
+    }
+
+    pub fn retract_tbox(&mut self, triple: Triple) {
+        self.updates.push((triple, -1, true))
+    }
+
+    pub fn retract_abox(&mut self, triple: Triple) {
+        self.updates.push((triple, -1, false))
+    }
+
+    // Sends the changes, and waits for them to be worked through, returning the epoch that they
+    // are all reported at. Whatever else was sent to the reasoner before the commit goes along.
+    pub fn commit(self) -> u64 {
+        let Transaction { reasoner, updates } = self;
+        for (triple, diff, tbox) in updates {
+            reasoner.update(triple, diff, tbox);
+        }
+        reasoner.commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entrypoint::Engine;
+    use crate::model::consts::constants::rdfs::{
+        domain, member, r#type, range, subClassOf, ContainerMembershipProperty, Resource,
+    };
+    use crate::model::consts::constants::MAX_CONST;
+    use crate::model::types::{RdfsEntailment, Triple, Window};
+    use crate::reasoner::{Reasoner, ReasonerBuilder};
+    use dire_parser::dictionary::Dictionary;
+    use dire_parser::literals::DatatypeRules;
+
+    #[test]
+    fn reasoner_commits_and_shuts_down() {
+        let mut reasoner = rdfs_reasoner().batch_size(16).build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        let first = reasoner.commit();
+        assert!(reasoner
+            .abox_changes()
+            .try_iter()
+            .any(|change| change == ((full_professor_7, r#type, employee), first, 1)));
+
+        reasoner.retract_abox((full_professor_7, r#type, faculty));
+        let second = reasoner.commit();
+        assert_eq!(first + 1, second);
+        assert!(reasoner
+            .abox_changes()
+            .try_iter()
+            .any(|change| change == ((full_professor_7, r#type, employee), second, -1)));
+
+        // Commits without any changes make for epochs of their own as well.
+        reasoner.wait_for(second + 1);
+        let checkpoint = reasoner.checkpoint();
+        assert_eq!(1, checkpoint.len());
+        assert_eq!(second + 2, checkpoint[0].epoch);
+        assert_eq!((2, 1), (checkpoint[0].added, checkpoint[0].removed));
+
+        reasoner.shutdown();
+        assert_eq!(3, reasoner.logs().try_iter().count());
+    }
+
+    #[test]
+    #[should_panic(expected = "the reasoner has been shut down")]
+    fn reasoner_refuses_commands_once_shut_down() {
+        let mut reasoner = rdfs_reasoner().build();
+        reasoner.shutdown();
+        // Shutting down again does nothing.
+        reasoner.shutdown();
+        reasoner.commit();
+    }
+
+    #[test]
+    fn reasoner_commits_transactions_as_one_epoch() {
+        let mut reasoner = Reasoner::builder()
+            .config(timely::Config::process(2))
+            .engine(Engine::RDFS)
+            .batch_size(1)
+            .build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let full_professor_8 = MAX_CONST + 4;
+
+        let mut transaction = reasoner.begin();
+        transaction.insert_tbox((faculty, subClassOf, employee));
+        transaction.insert_abox((full_professor_7, r#type, faculty));
+        transaction.insert_abox((full_professor_8, r#type, faculty));
+        let epoch = transaction.commit();
+        let tbox_changes: Vec<_> = reasoner.tbox_changes().try_iter().collect();
+        let abox_changes: Vec<_> = reasoner.abox_changes().try_iter().collect();
+        assert_eq!(
+            vec![((faculty, subClassOf, employee), epoch, 1)],
+            tbox_changes
+        );
+        assert_eq!(4, abox_changes.len());
+        assert!(abox_changes
+            .iter()
+            .all(|(_triple, time, _diff)| *time == epoch));
+
+        // Dropping a transaction leaves its changes out.
+        let mut transaction = reasoner.begin();
+        transaction.retract_abox((full_professor_7, r#type, faculty));
+        drop(transaction);
+        let epoch = reasoner.commit();
+        assert_eq!(0, reasoner.abox_changes().try_iter().count());
+
+        let mut transaction = reasoner.begin();
+        transaction.retract_tbox((faculty, subClassOf, employee));
+        assert_eq!(epoch + 1, transaction.commit());
+        assert_eq!(2, reasoner.abox_changes().try_iter().count());
+
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn reasoner_reports_changes_at_the_callers_epochs() {
+        let mut reasoner = rdfs_reasoner().build();
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let full_professor_8 = MAX_CONST + 4;
+
+        reasoner.insert_tbox_at((faculty, subClassOf, employee), 1000);
+        reasoner.insert_abox_at((full_professor_8, r#type, faculty), 2000);
+        reasoner.insert_abox_at((full_professor_7, r#type, faculty), 1500);
+        assert_eq!(1600, reasoner.advance_to(1600));
+        assert_eq!(
+            vec![((faculty, subClassOf, employee), 1000, 1)],
+            reasoner.tbox_changes().try_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                ((full_professor_7, r#type, faculty), 1500, 1),
+                ((full_professor_7, r#type, employee), 1500, 1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+
+        // Changes at an epoch that is closed already are left out, and reported.
+        reasoner.retract_abox_at((full_professor_7, r#type, faculty), 1200);
+        assert_eq!(2500, reasoner.advance_to(2500));
+        assert_eq!(1, reasoner.errors().len());
+        assert_eq!(2500, reasoner.advance_to(2000));
+        assert_eq!(
+            vec![
+                ((full_professor_8, r#type, faculty), 2000, 1),
+                ((full_professor_8, r#type, employee), 2000, 1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn reasoner_retracts_facts_falling_out_of_the_window() {
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let full_professor_8 = MAX_CONST + 4;
+
+        let mut reasoner = rdfs_reasoner().window(Window::Epochs(2)).build();
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        let first = reasoner.commit();
+        reasoner.insert_abox((full_professor_8, r#type, faculty));
+        reasoner.commit();
+        reasoner.abox_changes().try_iter().for_each(drop);
+        let third = reasoner.commit();
+        assert_eq!(
+            vec![
+                ((full_professor_7, r#type, faculty), third, -1),
+                ((full_professor_7, r#type, employee), third, -1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+        assert_eq!(first + 2, third);
+        reasoner.shutdown();
+
+        let mut reasoner = rdfs_reasoner().window(Window::Duration(1000)).build();
+        reasoner.insert_tbox_at((faculty, subClassOf, employee), 0);
+        reasoner.insert_abox_at((full_professor_7, r#type, faculty), 500);
+        reasoner.insert_abox_at((full_professor_8, r#type, faculty), 1200);
+        reasoner.advance_to(1400);
+        reasoner.abox_changes().try_iter().for_each(drop);
+        reasoner.advance_to(2000);
+        assert_eq!(
+            vec![
+                ((full_professor_7, r#type, faculty), 1500, -1),
+                ((full_professor_7, r#type, employee), 1500, -1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn reasoner_retracts_timed_facts_no_earlier_than_they_were_taken_in() {
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let full_professor_8 = MAX_CONST + 4;
+
+        let mut reasoner = rdfs_reasoner().window(Window::Epochs(1)).build();
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_8, r#type, faculty));
+        reasoner.insert_abox_at((full_professor_7, r#type, faculty), 5000);
+        let first = reasoner.commit();
+        let second = reasoner.commit();
+        reasoner.advance_to(6000);
+        // Falling out of the window before the epoch it was taken in at, the timed fact is
+        // retracted at that epoch, rather than ahead of its insertion.
+        assert_eq!(
+            vec![
+                ((full_professor_8, r#type, faculty), first, 1),
+                ((full_professor_8, r#type, faculty), second, -1),
+                ((full_professor_8, r#type, employee), first, 1),
+                ((full_professor_8, r#type, employee), second, -1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn reasoner_calls_off_the_expiry_of_facts_retracted_early() {
+        let faculty = MAX_CONST + 1;
+        let employee = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+
+        let mut reasoner = rdfs_reasoner().window(Window::Epochs(2)).build();
+        reasoner.insert_tbox((faculty, subClassOf, employee));
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        reasoner.commit();
+        reasoner.abox_changes().try_iter().for_each(drop);
+        reasoner.retract_abox((full_professor_7, r#type, faculty));
+        let second = reasoner.commit();
+        reasoner.insert_abox((full_professor_7, r#type, faculty));
+        let third = reasoner.commit();
+        // The first insertion would have fallen out of the window by now.
+        reasoner.commit();
+        assert_eq!(
+            vec![
+                ((full_professor_7, r#type, faculty), second, -1),
+                ((full_professor_7, r#type, faculty), third, 1),
+                ((full_professor_7, r#type, employee), second, -1),
+                ((full_professor_7, r#type, employee), third, 1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+        reasoner.shutdown();
+
+        let mut reasoner = rdfs_reasoner().window(Window::Duration(1000)).build();
+        reasoner.insert_tbox_at((faculty, subClassOf, employee), 0);
+        reasoner.insert_abox_at((full_professor_7, r#type, faculty), 500);
+        reasoner.advance_to(600);
+        reasoner.abox_changes().try_iter().for_each(drop);
+        reasoner.retract_abox_at((full_professor_7, r#type, faculty), 800);
+        reasoner.insert_abox_at((full_professor_7, r#type, faculty), 900);
+        reasoner.advance_to(2000);
+        assert_eq!(
+            vec![
+                ((full_professor_7, r#type, faculty), 800, -1),
+                ((full_professor_7, r#type, faculty), 900, 1),
+                ((full_professor_7, r#type, faculty), 1900, -1),
+                ((full_professor_7, r#type, employee), 800, -1),
+                ((full_professor_7, r#type, employee), 900, 1),
+                ((full_professor_7, r#type, employee), 1900, -1),
+            ],
+            sorted_abox_changes(&reasoner)
+        );
+        reasoner.shutdown();
+    }
+
+    #[test]
+    fn reasoner_runs_the_datatype_rules_over_inserted_literals() {
+        let xsd = |name: &str| format!("<http://www.w3.org/2001/XMLSchema#{}>", name);
+        let literal = |lexical: &str, name: &str| format!("\"{}\"^^{}", lexical, xsd(name));
+        let mut dictionary = Dictionary::new();
+        let age = dictionary.encode("<http://example.org/age>");
+        let full_professor_7 = dictionary.encode("<http://example.org/FullProfessor7>");
+        let full_professor_8 = dictionary.encode("<http://example.org/FullProfessor8>");
+        let forty = dictionary.encode(&literal("40", "integer"));
+        let forty_again = dictionary.encode(&literal("40.0", "decimal"));
+        let ill_typed = dictionary.encode(&literal("forty", "integer"));
+        let integer = dictionary.encode(&xsd("integer"));
+        let mut rules = DatatypeRules::new(&mut dictionary);
+        let mut reasoner = Reasoner::builder()
+            .engine(Engine::OWL2RL)
+            .literal_entailer(Box::new(move |id| {
+                let term = dictionary.decode(id).unwrap_or_default().to_string();
+                let entailments = rules.entail(id, &term);
+                (entailments.triples, entailments.ill_typed)
+            }))
+            .build();
+
+        reasoner.insert_abox((full_professor_7, age, forty));
+        reasoner.commit();
+        assert!(reasoner
+            .tbox_changes()
+            .try_iter()
+            .any(|(triple, _time, _diff)| triple == (forty, r#type, integer)));
+
+        // The literals of later commits are equated with those of earlier ones, and checked as well.
+        reasoner.insert_abox((full_professor_8, age, forty_again));
+        reasoner.insert_abox((full_professor_8, age, ill_typed));
+        reasoner.commit();
+        let abox_changes = sorted_abox_changes(&reasoner);
+        assert!(abox_changes
+            .iter()
+            .any(|(triple, _time, _diff)| *triple == (full_professor_8, age, forty)));
+        // The ill-typed literal is reported, without being typed with the datatype it claims
+        assert!(!abox_changes
+            .iter()
+            .any(|(triple, _time, _diff)| *triple == (ill_typed, r#type, integer)));
+        assert_eq!(
+            vec![(
+                "dt-not-type".to_string(),
+                vec![(ill_typed, r#type, integer)]
+            )],
+            reasoner
+                .inconsistencies()
+                .try_iter()
+                .map(|(inconsistency, _time, _diff)| inconsistency)
+                .collect::<Vec<_>>()
+        );
+        reasoner.shutdown();
+    }
+
+    // A single-threaded reasoner running RDFS, to be set up further.
+    #[test]
+    fn reasoner_sends_the_axiomatic_triples_of_inserted_container_membership_properties() {
+        let bag = MAX_CONST + 1;
+        let member_1 = MAX_CONST + 2;
+        let full_professor_7 = MAX_CONST + 3;
+        let mut axioms = vec![
+            (member_1, r#type, ContainerMembershipProperty),
+            (member_1, domain, Resource),
+            (member_1, range, Resource),
+        ];
+        axioms.sort();
+        for axiomatic_triples in [true, false] {
+            let mut reasoner = Reasoner::builder()
+                .engine(Engine::RDFSFull(RdfsEntailment {
+                    axiomatic_triples,
+                    ..Default::default()
+                }))
+                .container_membership(Box::new(move |id| id == member_1))
+                .build();
+
+            reasoner.insert_abox((bag, member_1, full_professor_7));
+            reasoner.insert_abox((full_professor_7, member_1, bag));
+            reasoner.commit();
+            let mut tbox_changes: Vec<Triple> = reasoner
+                .tbox_changes()
+                .try_iter()
+                .map(|(triple, _time, _diff)| triple)
+                .collect();
+            let abox_changes: Vec<Triple> = reasoner
+                .abox_changes()
+                .try_iter()
+                .map(|(triple, _time, _diff)| triple)
+                .collect();
+            tbox_changes.sort();
+            if axiomatic_triples {
+                assert_eq!(axioms, tbox_changes);
+                // Through rdfs12 and rdfs7
+                assert!(abox_changes.contains(&(bag, member, full_professor_7)));
+            } else {
+                assert!(tbox_changes.is_empty());
+                assert!(!abox_changes.contains(&(bag, member, full_professor_7)));
+            }
+            reasoner.shutdown();
+        }
+    }
+
+    fn rdfs_reasoner() -> ReasonerBuilder {
+        Reasoner::builder()
+            .config(timely::Config::process(1))
+            .engine(Engine::RDFS)
+    }
+
+    // Every change to the abox read so far, in order.
+    fn sorted_abox_changes(reasoner: &Reasoner) -> Vec<(Triple, u64, isize)> {
+        let mut changes: Vec<_> = reasoner.abox_changes().try_iter().collect();
+        changes.sort();
+        changes
+    }
+}
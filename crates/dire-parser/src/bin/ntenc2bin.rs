@@ -0,0 +1,39 @@
+use dire_parser::binary::{convert3enc, BinaryTriples};
+use dire_parser::error::ParseMode;
+use std::env::args;
+use std::process::exit;
+
+// Converts an `.ntenc` file into the memory-mappable `.ntbin` format:
+// ntenc2bin <input.ntenc> <output.ntbin> [--lenient]
+fn main() {
+    // The flag may come anywhere, so it is taken out before the paths are read.
+    let (flags, arguments): (Vec<String>, Vec<String>) =
+        args().skip(1).partition(|argument| argument == "--lenient");
+    if arguments.len() < 2 {
+        eprintln!("usage: ntenc2bin <input.ntenc> <output.ntbin> [--lenient]");
+        exit(2);
+    }
+    let mode = if flags.is_empty() {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    };
+    let converted = convert3enc(&arguments[0], &arguments[1], mode)
+        .and_then(|skipped| Ok((skipped, BinaryTriples::open(&arguments[1])?)));
+    match converted {
+        Ok((skipped, triples)) => {
+            if !skipped.is_empty() {
+                println!(
+                    "Skipped {} malformed lines in {}",
+                    skipped.len(),
+                    arguments[0]
+                );
+            }
+            println!("Wrote {} triples to {}", triples.len(), arguments[1]);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::fs::{remove_file, File};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+
+use memmap2::Mmap;
+
+use crate::error::{ParseError, ParseMode};
+use crate::load3enc;
+
+// The file starts with a 16 byte header: the magic bytes, the format version and the amount of
+// triples, followed by the triples themselves as three little-endian u32 ids each.
+pub const MAGIC: &[u8; 4] = b"DIRE";
+pub const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+const TRIPLE_SIZE: usize = 12;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+pub fn write3bin<'a>(
+    filename: &str,
+    triples: impl Iterator<Item = &'a (u32, u32, u32)>,
+) -> Result<u64, ParseError> {
+    write_triples(filename, triples.map(|triple| Ok(*triple)))
+}
+
+// Writes the triples as they come, stopping at the first error.
+fn write_triples(
+    filename: &str,
+    triples: impl Iterator<Item = Result<(u32, u32, u32), ParseError>>,
+) -> Result<u64, ParseError> {
+    let io_error = |error: std::io::Error| ParseError::Io {
+        file: filename.to_string(),
+        reason: error.to_string(),
+    };
+    let file = File::create(filename).map_err(io_error)?;
+    let mut writer = BufWriter::new(file);
+    // The count is only known once every triple went through, so it is patched in at the end.
+    writer.write_all(MAGIC).map_err(io_error)?;
+    writer.write_all(&VERSION.to_le_bytes()).map_err(io_error)?;
+    writer.write_all(&0u64.to_le_bytes()).map_err(io_error)?;
+    let mut count: u64 = 0;
+    for triple in triples {
+        let (s, p, o) = triple?;
+        writer.write_all(&s.to_le_bytes()).map_err(io_error)?;
+        writer.write_all(&p.to_le_bytes()).map_err(io_error)?;
+        writer.write_all(&o.to_le_bytes()).map_err(io_error)?;
+        count += 1;
+    }
+    writer.seek(SeekFrom::Start(8)).map_err(io_error)?;
+    writer.write_all(&count.to_le_bytes()).map_err(io_error)?;
+    writer.flush().map_err(io_error)?;
+    Ok(count)
+}
+
+/// Converts an `.ntenc` file to the binary format, returning the lines that were skipped in
+/// lenient mode. Triples are written as they are read, so the input is never held in memory.
+pub fn convert3enc(
+    input: &str,
+    output: &str,
+    mode: ParseMode,
+) -> Result<Vec<ParseError>, ParseError> {
+    let mut skipped = vec![];
+    let triples = load3enc(input)?.filter_map(|triple| match (triple, mode) {
+        (Ok(triple), _) => Some(Ok(triple)),
+        (Err(error), ParseMode::Strict) => Some(Err(error)),
+        (Err(error), ParseMode::Lenient) => {
+            skipped.push(error);
+            None
+        }
+    });
+    // A malformed line in strict mode leaves the output half written, so it is removed.
+    if let Err(error) = write_triples(output, triples) {
+        let _ = remove_file(output);
+        return Err(error);
+    }
+    Ok(skipped)
+}
+
+/// A memory-mapped binary triple file. Triples are decoded straight from the mapped pages, so
+/// nothing is read before it is accessed, and workers can each be handed their own slice.
+pub struct BinaryTriples {
+    mmap: Mmap,
+    len: usize,
+}
+
+impl BinaryTriples {
+    pub fn open(filename: &str) -> Result<Self, ParseError> {
+        let format_error = |reason: String| ParseError::Format {
+            file: filename.to_string(),
+            reason,
+        };
+        let file = File::open(filename).map_err(|error| ParseError::Io {
+            file: filename.to_string(),
+            reason: error.to_string(),
+        })?;
+        // Safety: the mapping is read-only, and the file is not expected to be modified while
+        // the reasoner runs.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|error| ParseError::Io {
+            file: filename.to_string(),
+            reason: error.to_string(),
+        })?;
+        if mmap.len() < HEADER_SIZE || &mmap[..4] != MAGIC {
+            return Err(format_error("not a binary triple file".to_string()));
+        }
+        let version = read_u32(&mmap, 4);
+        if version != VERSION {
+            return Err(format_error(format!(
+                "unsupported format version {}, expected {}",
+                version, VERSION
+            )));
+        }
+        let count = u64::from_le_bytes(mmap[8..HEADER_SIZE].try_into().unwrap());
+        let size = mmap.len() - HEADER_SIZE;
+        // The count comes from the file, so it is not trusted to fit, let alone to match.
+        let count = usize::try_from(count)
+            .ok()
+            .filter(|count| count.checked_mul(TRIPLE_SIZE) == Some(size))
+            .ok_or_else(|| {
+                format_error(format!(
+                    "header announces {} triples, but the file holds {} bytes of them",
+                    count, size
+                ))
+            })?;
+        Ok(Self { mmap, len: count })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> TripleSlice<'_> {
+        TripleSlice {
+            bytes: &self.mmap[HEADER_SIZE..],
+        }
+    }
+
+    /// The contiguous share of the triples belonging to the `index`-th out of `peers` workers.
+    /// The partitions of all workers cover every triple exactly once.
+    pub fn partition(&self, index: usize, peers: usize) -> TripleSlice<'_> {
+        let start = self.len * index / peers;
+        let end = self.len * (index + 1) / peers;
+        self.as_slice().range(start, end)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TripleSlice<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> TripleSlice<'a> {
+    pub fn len(&self) -> usize {
+        self.bytes.len() / TRIPLE_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<(u32, u32, u32)> {
+        if idx >= self.len() {
+            return None;
+        }
+        let offset = idx * TRIPLE_SIZE;
+        Some((
+            read_u32(self.bytes, offset),
+            read_u32(self.bytes, offset + 4),
+            read_u32(self.bytes, offset + 8),
+        ))
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> TripleSlice<'a> {
+        TripleSlice {
+            bytes: &self.bytes[start * TRIPLE_SIZE..end * TRIPLE_SIZE],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u32, u32)> + 'a {
+        let slice = *self;
+        (0..slice.len()).map(move |idx| slice.get(idx).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary::{convert3enc, BinaryTriples};
+    use crate::error::{ParseError, ParseMode};
+    use crate::load3enc;
+    use std::env::temp_dir;
+    use std::fs::write;
+
+    #[test]
+    fn convert3enc_and_mmap_round_trip() {
+        let path = temp_dir().join("dire_parser_round_trip.ntbin");
+        let filename = path.to_str().unwrap();
+        let skipped = convert3enc(
+            "../../data/lubm/toy/abox.ntenc",
+            filename,
+            ParseMode::Strict,
+        )
+        .unwrap();
+        assert!(skipped.is_empty());
+
+        let expected: Vec<(u32, u32, u32)> = load3enc("../../data/lubm/toy/abox.ntenc")
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let triples = BinaryTriples::open(filename).unwrap();
+        assert_eq!(expected.len(), triples.len());
+        assert_eq!(expected, triples.as_slice().iter().collect::<Vec<_>>());
+
+        let partitioned: Vec<(u32, u32, u32)> = (0..3)
+            .flat_map(|index| triples.partition(index, 3).iter())
+            .collect();
+        assert_eq!(expected, partitioned);
+    }
+
+    #[test]
+    fn convert3enc_skips_or_stops_at_malformed_lines() {
+        let input_path = temp_dir().join("dire_parser_convert_malformed.ntenc");
+        let output_path = temp_dir().join("dire_parser_convert_malformed.ntbin");
+        let input = input_path.to_str().unwrap();
+        let output = output_path.to_str().unwrap();
+        write(&input_path, "47 4 33\n47 x 48\n49 0 47\n").unwrap();
+
+        let skipped = convert3enc(input, output, ParseMode::Lenient).unwrap();
+        assert_eq!(
+            vec![ParseError::InvalidTerm {
+                file: input.to_string(),
+                line: 2,
+                token: "x".to_string()
+            }],
+            skipped
+        );
+        let triples = BinaryTriples::open(output).unwrap();
+        assert_eq!(
+            vec![(47, 4, 33), (49, 0, 47)],
+            triples.as_slice().iter().collect::<Vec<_>>()
+        );
+
+        assert!(convert3enc(input, output, ParseMode::Strict).is_err());
+        assert!(!output_path.exists());
+    }
+
+    #[test]
+    fn open_rejects_malformed_headers() {
+        let path = temp_dir().join("dire_parser_malformed.ntbin");
+        let filename = path.to_str().unwrap();
+        write(&path, "47 4 33\n49 0 47\n").unwrap();
+        assert_eq!(
+            BinaryTriples::open(filename).err(),
+            Some(ParseError::Format {
+                file: filename.to_string(),
+                reason: "not a binary triple file".to_string()
+            })
+        );
+
+        let mut truncated = b"DIRE".to_vec();
+        truncated.extend_from_slice(&1u32.to_le_bytes());
+        truncated.extend_from_slice(&2u64.to_le_bytes());
+        truncated.extend_from_slice(&[0; 12]);
+        write(&path, truncated).unwrap();
+        assert!(matches!(
+            BinaryTriples::open(filename),
+            Err(ParseError::Format { .. })
+        ));
+
+        let mut overflowing = b"DIRE".to_vec();
+        overflowing.extend_from_slice(&1u32.to_le_bytes());
+        overflowing.extend_from_slice(&(u64::MAX / 3 + 1).to_le_bytes());
+        overflowing.extend_from_slice(&[0; 4]);
+        write(&path, overflowing).unwrap();
+        assert!(matches!(
+            BinaryTriples::open(filename),
+            Err(ParseError::Format { .. })
+        ));
+
+        write(&path, b"DIRE\x01\x00").unwrap();
+        assert!(matches!(
+            BinaryTriples::open(filename),
+            Err(ParseError::Format { .. })
+        ));
+    }
+}
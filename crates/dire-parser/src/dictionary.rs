@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::error::ParseError;
+use crate::literals::integer_value;
+use crate::read_file;
+
+// Must be kept in lockstep with dire_engine::model::consts::constants, as the position of each
+// term in this list is the id the engine expects it to have.
+pub const RESERVED_TERMS: [&str; 47] = [
+    "<http://www.w3.org/2000/01/rdf-schema#subClassOf>",
+    "<http://www.w3.org/2000/01/rdf-schema#subPropertyOf>",
+    "<http://www.w3.org/2000/01/rdf-schema#domain>",
+    "<http://www.w3.org/2000/01/rdf-schema#range>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>",
+    "<http://www.w3.org/2000/01/rdf-schema#comment>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#rest>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#first>",
+    "<http://www.w3.org/2000/01/rdf-schema#label>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#nil>",
+    "<http://www.w3.org/2000/01/rdf-schema#Literal>",
+    "<http://www.w3.org/2002/07/owl#TransitiveProperty>",
+    "<http://www.w3.org/2002/07/owl#inverseOf>",
+    "<http://www.w3.org/2002/07/owl#Thing>",
+    "<http://www.w3.org/2002/07/owl#maxQualifiedCardinality>",
+    "<http://www.w3.org/2002/07/owl#someValuesFrom>",
+    "<http://www.w3.org/2002/07/owl#equivalentClass>",
+    "<http://www.w3.org/2002/07/owl#intersectionOf>",
+    "<http://www.w3.org/2002/07/owl#members>",
+    "<http://www.w3.org/2002/07/owl#equivalentProperty>",
+    "<http://www.w3.org/2002/07/owl#onProperty>",
+    "<http://www.w3.org/2002/07/owl#propertyChainAxiom>",
+    "<http://www.w3.org/2002/07/owl#disjointWith>",
+    "<http://www.w3.org/2002/07/owl#propertyDisjointWith>",
+    "<http://www.w3.org/2002/07/owl#unionOf>",
+    "<http://www.w3.org/2002/07/owl#hasKey>",
+    "<http://www.w3.org/2002/07/owl#allValuesFrom>",
+    "<http://www.w3.org/2002/07/owl#complementOf>",
+    "<http://www.w3.org/2002/07/owl#onClass>",
+    "<http://www.w3.org/2002/07/owl#distinctMembers>",
+    "<http://www.w3.org/2002/07/owl#FunctionalProperty>",
+    "<http://www.w3.org/2002/07/owl#NamedIndividual>",
+    "<http://www.w3.org/2002/07/owl#ObjectProperty>",
+    "<http://www.w3.org/2002/07/owl#Class>",
+    "<http://www.w3.org/2002/07/owl#AllDisjointClasses>",
+    "<http://www.w3.org/2002/07/owl#Restriction>",
+    "<http://www.w3.org/2002/07/owl#DatatypeProperty>",
+    "<http://www.w3.org/2002/07/owl#Ontology>",
+    "<http://www.w3.org/2002/07/owl#AsymmetricProperty>",
+    "<http://www.w3.org/2002/07/owl#SymmetricProperty>",
+    "<http://www.w3.org/2002/07/owl#IrreflexiveProperty>",
+    "<http://www.w3.org/2002/07/owl#AllDifferent>",
+    "<http://www.w3.org/2002/07/owl#InverseFunctionalProperty>",
+    "<http://www.w3.org/2002/07/owl#sameAs>",
+    "<http://www.w3.org/2002/07/owl#hasValue>",
+    "<http://www.w3.org/2002/07/owl#Nothing>",
+    "<http://www.w3.org/2002/07/owl#oneOf>",
+];
+
+// The vocabulary added after the ntencoder, first the rest of RDF and RDFS, then that of the OWL 2
+// RL consistency and cardinality rules. Each term has the id `u32::MAX - position`, in lockstep with
+// dire_engine::model::consts::constants, so that reserving it does not shift the ids the
+// ntencoder assigns.
+pub const EXTENDED_VOCABULARY: [&str; 28] = [
+    "<http://www.w3.org/2000/01/rdf-schema#Resource>",
+    "<http://www.w3.org/2000/01/rdf-schema#Class>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Property>",
+    "<http://www.w3.org/2000/01/rdf-schema#Datatype>",
+    "<http://www.w3.org/2000/01/rdf-schema#ContainerMembershipProperty>",
+    "<http://www.w3.org/2000/01/rdf-schema#member>",
+    "<http://www.w3.org/2000/01/rdf-schema#Container>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#subject>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#object>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#List>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#value>",
+    "<http://www.w3.org/2000/01/rdf-schema#seeAlso>",
+    "<http://www.w3.org/2000/01/rdf-schema#isDefinedBy>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Alt>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Bag>",
+    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#Seq>",
+    "<http://www.w3.org/2002/07/owl#AllDisjointProperties>",
+    "<http://www.w3.org/2002/07/owl#NegativePropertyAssertion>",
+    "<http://www.w3.org/2002/07/owl#sourceIndividual>",
+    "<http://www.w3.org/2002/07/owl#assertionProperty>",
+    "<http://www.w3.org/2002/07/owl#targetIndividual>",
+    "<http://www.w3.org/2002/07/owl#targetValue>",
+    "<http://www.w3.org/2002/07/owl#differentFrom>",
+    "<http://www.w3.org/2002/07/owl#maxCardinality>",
+    "\"0\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger>",
+    "\"1\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger>",
+];
+
+const MAX_QUALIFIED_CARDINALITY: &str = RESERVED_TERMS[14];
+const MAX_CARDINALITY: &str = EXTENDED_VOCABULARY[25];
+const CARDINALITY_ZERO: &str = EXTENDED_VOCABULARY[26];
+const CARDINALITY_ONE: &str = EXTENDED_VOCABULARY[27];
+
+// The reserved literal of a cardinality of 0 or 1, whichever integer datatype it is written with,
+// or if it is a plain literal.
+fn cardinality_literal(term: &str) -> Option<&'static str> {
+    if !term.starts_with('"') {
+        return None;
+    }
+    match integer_value(term)? {
+        0 => Some(CARDINALITY_ZERO),
+        1 => Some(CARDINALITY_ONE),
+        _ => None,
+    }
+}
+
+const RDF_CONTAINER_MEMBERSHIP_PREFIX: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#_";
+
+/// Interns terms, in their N-Triples lexical form, into dense `u32` ids. Ids are handed out in
+/// order of first appearance, right after the reserved vocabulary.
+///
+/// On disk a dictionary is a plain text file with one term per line, the line number being the
+/// id, which is conventionally kept next to the `.ntenc` files it was used to encode.
+pub struct Dictionary {
+    ids: HashMap<String, u32>,
+    terms: Vec<String>,
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        let mut dictionary = Self {
+            ids: HashMap::new(),
+            terms: vec![],
+        };
+        RESERVED_TERMS.iter().for_each(|term| {
+            dictionary.encode(term);
+        });
+        dictionary.reserve_extended_vocabulary();
+        dictionary
+    }
+
+    fn reserve_extended_vocabulary(&mut self) {
+        for (position, term) in EXTENDED_VOCABULARY.iter().enumerate() {
+            self.ids
+                .insert(term.to_string(), u32::MAX - position as u32);
+        }
+    }
+
+    pub fn encode(&mut self, term: &str) -> u32 {
+        if let Some(id) = self.ids.get(term) {
+            return *id;
+        }
+        let id = self.terms.len() as u32;
+        self.ids.insert(term.to_string(), id);
+        self.terms.push(term.to_string());
+        id
+    }
+
+    /// Interns a blank node that is guaranteed not to have been seen before, for the anonymous
+    /// nodes of syntaxes that, unlike N-Triples, do not label all of them.
+    pub fn fresh_blank_node(&mut self) -> u32 {
+        let mut label = format!("_:genid{}", self.terms.len());
+        while self.ids.contains_key(&label) {
+            label.push('x');
+        }
+        self.encode(&label)
+    }
+
+    pub fn decode(&self, id: u32) -> Option<&str> {
+        match self.terms.get(id as usize) {
+            Some(term) => Some(term.as_str()),
+            None => EXTENDED_VOCABULARY.get((u32::MAX - id) as usize).copied(),
+        }
+    }
+
+    /// The ids of every `rdf:_n` property seen so far. Their axiomatic triples can not be
+    /// materialized up front, as there are infinitely many of them.
+    pub fn container_membership_properties(&self) -> Vec<u32> {
+        self.terms
+            .iter()
+            .enumerate()
+            .filter(|(_id, term)| {
+                term.strip_prefix(RDF_CONTAINER_MEMBERSHIP_PREFIX)
+                    .and_then(|rest| rest.strip_suffix('>'))
+                    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|(id, _term)| id as u32)
+            .collect()
+    }
+
+    /// Every interned term alongside its id, the extended vocabulary included, in no particular
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.ids.iter().map(|(term, id)| (*id, term.as_str()))
+    }
+
+    /// The triple with the cardinality of an `owl:maxCardinality` or `owl:maxQualifiedCardinality`
+    /// restriction replaced by the reserved `"0"^^xsd:nonNegativeInteger` or
+    /// `"1"^^xsd:nonNegativeInteger`, if it is 0 or 1, as the OWL 2 RL cardinality rules only match
+    /// their ids. Every other triple is left as it is.
+    pub fn normalize_cardinality(&mut self, (s, p, o): (u32, u32, u32)) -> (u32, u32, u32) {
+        let cardinality = match (self.decode(p), self.decode(o)) {
+            (Some(MAX_CARDINALITY | MAX_QUALIFIED_CARDINALITY), Some(term)) => {
+                cardinality_literal(term)
+            }
+            _ => None,
+        };
+        match cardinality {
+            Some(cardinality) => (s, p, self.encode(cardinality)),
+            None => (s, p, o),
+        }
+    }
+
+    pub fn decode_triple(&self, (s, p, o): (u32, u32, u32)) -> Option<(&str, &str, &str)> {
+        Some((self.decode(s)?, self.decode(p)?, self.decode(o)?))
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), ParseError> {
+        let io_error = |error: std::io::Error| ParseError::Io {
+            file: filename.to_string(),
+            reason: error.to_string(),
+        };
+        let file = File::create(filename).map_err(io_error)?;
+        let mut writer = BufWriter::new(file);
+        for term in &self.terms {
+            writeln!(writer, "{}", term).map_err(io_error)?;
+        }
+        writer.flush().map_err(io_error)
+    }
+
+    pub fn load(filename: &str) -> Result<Self, ParseError> {
+        let mut dictionary = Self {
+            ids: HashMap::new(),
+            terms: vec![],
+        };
+        for line in read_file(filename)? {
+            let (line_number, term) = line?;
+            // Saved terms keep their ids as they are, as the datasets encoded with the dictionary
+            // rely on them.
+            if dictionary.encode(&term) as usize != line_number - 1 {
+                return Err(ParseError::Syntax {
+                    file: filename.to_string(),
+                    line: line_number,
+                    token: term,
+                    reason: "duplicate dictionary term",
+                });
+            }
+        }
+        for (id, term) in RESERVED_TERMS.iter().enumerate() {
+            if dictionary.decode(id as u32) != Some(term) {
+                return Err(ParseError::Syntax {
+                    file: filename.to_string(),
+                    line: id + 1,
+                    token: dictionary.decode(id as u32).unwrap_or("").to_string(),
+                    reason: "dictionary does not start with the reserved vocabulary",
+                });
+            }
+        }
+        // Dictionaries saved before the extended vocabulary was reserved may hold some of it at a
+        // dense id, which the engine would not recognize, and which can not be moved either, as
+        // the datasets encoded with them rely on it.
+        if let Some((id, term)) = dictionary
+            .terms
+            .iter()
+            .enumerate()
+            .find(|(_id, term)| EXTENDED_VOCABULARY.contains(&term.as_str()))
+        {
+            return Err(ParseError::Syntax {
+                file: filename.to_string(),
+                line: id + 1,
+                token: term.clone(),
+                reason: "extended vocabulary term at a dense id, from before it was reserved",
+            });
+        }
+        dictionary.reserve_extended_vocabulary();
+        Ok(dictionary)
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::{
+        Dictionary, EXTENDED_VOCABULARY, MAX_CARDINALITY, MAX_QUALIFIED_CARDINALITY, RESERVED_TERMS,
+    };
+    use crate::error::ParseError;
+    use std::env::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn extended_vocabulary_is_reserved_at_the_top() {
+        let mut dictionary = Dictionary::new();
+        let resource = dictionary.encode("<http://www.w3.org/2000/01/rdf-schema#Resource>");
+        let seq = dictionary.encode("<http://www.w3.org/1999/02/22-rdf-syntax-ns#Seq>");
+        let first_member = dictionary.encode("<http://www.w3.org/1999/02/22-rdf-syntax-ns#_1>");
+        dictionary.encode("<http://www.w3.org/1999/02/22-rdf-syntax-ns#_x>");
+
+        assert_eq!(u32::MAX, resource);
+        assert_eq!(u32::MAX - 17, seq);
+        assert_eq!(47, first_member);
+        assert_eq!(
+            Some("<http://www.w3.org/2000/01/rdf-schema#Resource>"),
+            dictionary.decode(resource)
+        );
+        assert_eq!(
+            vec![first_member],
+            dictionary.container_membership_properties()
+        );
+    }
+
+    #[test]
+    fn dictionaries_holding_the_extended_vocabulary_are_rejected() {
+        let path = temp_dir().join("dire_parser_extended_vocabulary.dict");
+        let mut terms: Vec<&str> = RESERVED_TERMS.to_vec();
+        terms.push("<http://example.org/Person>");
+        fs::write(&path, terms.join("\n")).unwrap();
+        assert!(Dictionary::load(path.to_str().unwrap()).is_ok());
+
+        terms.push(EXTENDED_VOCABULARY[0]);
+        fs::write(&path, terms.join("\n")).unwrap();
+        match Dictionary::load(path.to_str().unwrap()) {
+            Err(ParseError::Syntax { line, .. }) => assert_eq!(terms.len(), line),
+            _ => panic!("the dictionary was loaded"),
+        }
+    }
+
+    #[test]
+    fn cardinalities_are_encoded_as_the_reserved_literals() {
+        let mut dictionary = Dictionary::new();
+        let one = dictionary.encode("\"1\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger>");
+        let zero =
+            dictionary.encode("\"0\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger>");
+        let restriction = dictionary.encode("_:restriction");
+        let max_cardinality = dictionary.encode(MAX_CARDINALITY);
+        let max_qualified_cardinality = dictionary.encode(MAX_QUALIFIED_CARDINALITY);
+        let name = dictionary.encode("<http://example.org/name>");
+        let mut cardinality = |predicate: u32, term: &str| {
+            let object = dictionary.encode(term);
+            dictionary
+                .normalize_cardinality((restriction, predicate, object))
+                .2
+        };
+
+        assert_eq!(u32::MAX - 27, one);
+        assert_eq!(u32::MAX - 26, zero);
+        assert_eq!(
+            one,
+            cardinality(
+                max_cardinality,
+                "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+            )
+        );
+        assert_eq!(
+            one,
+            cardinality(
+                max_qualified_cardinality,
+                "\"+01\"^^<http://www.w3.org/2001/XMLSchema#int>"
+            )
+        );
+        assert_eq!(
+            zero,
+            cardinality(
+                max_cardinality,
+                "\"0\"^^<http://www.w3.org/2001/XMLSchema#unsignedByte>"
+            )
+        );
+        // Other values and datatypes are left alone.
+        assert_ne!(
+            one,
+            cardinality(
+                max_cardinality,
+                "\"2\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+            )
+        );
+        assert_ne!(
+            one,
+            cardinality(
+                max_cardinality,
+                "\"1.0\"^^<http://www.w3.org/2001/XMLSchema#decimal>"
+            )
+        );
+        assert_ne!(
+            one,
+            cardinality(
+                max_cardinality,
+                "\"1\"^^<http://www.w3.org/2001/XMLSchema#boolean>"
+            )
+        );
+        assert_ne!(one, cardinality(max_cardinality, "\"1\"@en"));
+        // So are the literals of every other property.
+        assert_ne!(one, cardinality(name, "\"1\""));
+        assert_ne!(
+            one,
+            cardinality(name, "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>")
+        );
+    }
+}
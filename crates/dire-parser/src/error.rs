@@ -0,0 +1,109 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Io {
+        file: String,
+        reason: String,
+    },
+    MissingTerm {
+        file: String,
+        line: usize,
+    },
+    InvalidTerm {
+        file: String,
+        line: usize,
+        token: String,
+    },
+    Syntax {
+        file: String,
+        line: usize,
+        token: String,
+        reason: &'static str,
+    },
+    Format {
+        file: String,
+        reason: String,
+    },
+}
+
+impl ParseError {
+    /// Attributes an error raised while parsing an in-memory document to the file it came from.
+    pub fn in_file(self, filename: &str) -> Self {
+        let file = filename.to_string();
+        match self {
+            ParseError::Io { reason, .. } => ParseError::Io { file, reason },
+            ParseError::MissingTerm { line, .. } => ParseError::MissingTerm { file, line },
+            ParseError::InvalidTerm { line, token, .. } => {
+                ParseError::InvalidTerm { file, line, token }
+            }
+            ParseError::Syntax {
+                line,
+                token,
+                reason,
+                ..
+            } => ParseError::Syntax {
+                file,
+                line,
+                token,
+                reason,
+            },
+            ParseError::Format { reason, .. } => ParseError::Format { file, reason },
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io { file, reason } => write!(f, "{}: {}", file, reason),
+            ParseError::MissingTerm { file, line } => {
+                write!(f, "{}:{}: expected three terms", file, line)
+            }
+            ParseError::InvalidTerm { file, line, token } => {
+                write!(f, "{}:{}: invalid term `{}`", file, line, token)
+            }
+            ParseError::Syntax {
+                file,
+                line,
+                token,
+                reason,
+            } => write!(f, "{}:{}: {} at `{}`", file, line, reason, token),
+            ParseError::Format { file, reason } => write!(f, "{}: {}", file, reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Strict parsing fails on the first malformed line, whereas lenient parsing skips over it.
+/// Document-based syntaxes, such as Turtle and RDF/XML, can not be resumed past an error, and
+/// therefore always fail on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
+pub struct Parsed {
+    pub triples: Vec<(u32, u32, u32)>,
+    pub skipped: Vec<ParseError>,
+}
+
+pub fn collect_triples(
+    triples: impl Iterator<Item = Result<(u32, u32, u32), ParseError>>,
+    mode: ParseMode,
+) -> Result<Parsed, ParseError> {
+    let mut parsed = Parsed {
+        triples: vec![],
+        skipped: vec![],
+    };
+    for triple in triples {
+        match (triple, mode) {
+            (Ok(triple), _) => parsed.triples.push(triple),
+            (Err(error), ParseMode::Strict) => return Err(error),
+            (Err(error), ParseMode::Lenient) => parsed.skipped.push(error),
+        }
+    }
+    Ok(parsed)
+}
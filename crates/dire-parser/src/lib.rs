@@ -1,32 +1,237 @@
+pub mod binary;
+pub mod dictionary;
+pub mod error;
+pub mod literals;
+pub mod ntriples;
+pub mod rdfxml;
+mod terms;
+pub mod turtle;
+
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-fn read_file(filename: &str) -> Result<impl Iterator<Item = String>, &'static str> {
-    return if let Ok(file) = File::open(filename) {
-        if let buffer = BufReader::new(file) {
-            Ok(buffer.lines().filter_map(|line| line.ok()))
-        } else {
-            Err("fail to make buffer")
-        }
-    } else {
-        Err("fail to open file")
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::iter;
+
+use crate::error::ParseError;
+
+// Yields every line of the file alongside its 1-based line number. A line that can not be read,
+// be it for an I/O error or for not being UTF-8, is yielded as an error and ends the iteration.
+fn read_file(
+    filename: &str,
+) -> Result<impl Iterator<Item = Result<(usize, String), ParseError>>, ParseError> {
+    let file = File::open(filename).map_err(|error| ParseError::Io {
+        file: filename.to_string(),
+        reason: error.to_string(),
+    })?;
+    let file_name = filename.to_string();
+    let mut failed = false;
+    Ok(BufReader::new(file)
+        .lines()
+        .enumerate()
+        .map_while(move |(idx, line)| {
+            if failed {
+                return None;
+            }
+            match line {
+                Ok(line) => Some(Ok((idx + 1, line))),
+                Err(error) => {
+                    failed = true;
+                    Some(Err(read_error(&file_name, idx + 1, error)))
+                }
+            }
+        }))
+}
+
+fn read_error(filename: &str, line_number: usize, error: std::io::Error) -> ParseError {
+    ParseError::Io {
+        file: filename.to_string(),
+        reason: format!("line {}: {}", line_number, error),
+    }
+}
+
+fn parse_3enc_line(
+    line: &str,
+    file: &str,
+    line_number: usize,
+) -> Result<(u32, u32, u32), ParseError> {
+    let mut split_line = line.split_whitespace();
+    let mut next_id = || -> Result<u32, ParseError> {
+        let token = split_line.next().ok_or_else(|| ParseError::MissingTerm {
+            file: file.to_string(),
+            line: line_number,
+        })?;
+        token.parse().map_err(|_| ParseError::InvalidTerm {
+            file: file.to_string(),
+            line: line_number,
+            token: token.to_string(),
+        })
     };
+    let digit_one = next_id()?;
+    let digit_two = next_id()?;
+    let digit_three = next_id()?;
+    if let Some(token) = split_line.next() {
+        return Err(ParseError::InvalidTerm {
+            file: file.to_string(),
+            line: line_number,
+            token: token.to_string(),
+        });
+    }
+    Ok((digit_one, digit_two, digit_three))
 }
 
 pub fn load3enc<'a>(
     filename: &str,
-) -> Result<impl Iterator<Item = (u32, u32, u32)> + 'a, &'static str> {
-    match read_file(filename) {
-        Ok(file) => {
-            let option_map = file.map(move |line| {
-                let mut split_line = line.split(' ');
-                let digit_one: u32 = split_line.next().unwrap().parse().unwrap();
-                let digit_two: u32 = split_line.next().unwrap().parse().unwrap();
-                let digit_three: u32 = split_line.next().unwrap().parse().unwrap();
-                (digit_one, digit_two, digit_three)
-            });
-            Ok(option_map)
+) -> Result<impl Iterator<Item = Result<(u32, u32, u32), ParseError>> + 'a, ParseError> {
+    let file = filename.to_string();
+    let lines = read_file(filename)?;
+    Ok(lines
+        .filter(|line| !matches!(line, Ok((_line_number, line)) if line.trim().is_empty()))
+        .map(move |line| {
+            let (line_number, line) = line?;
+            parse_3enc_line(&line, &file, line_number)
+        }))
+}
+
+/// Reads the `index`-th out of `peers` byte ranges of an `.ntenc` file, without touching the rest
+/// of it. A line belongs to the range its first byte falls in, so the ranges of all peers cover
+/// every line exactly once. Line numbers in errors count from the start of the range.
+pub fn load3enc_partition<'a>(
+    filename: &str,
+    index: usize,
+    peers: usize,
+) -> Result<impl Iterator<Item = Result<(u32, u32, u32), ParseError>> + 'a, ParseError> {
+    let io_error = |error: std::io::Error| ParseError::Io {
+        file: filename.to_string(),
+        reason: error.to_string(),
+    };
+    let file = File::open(filename).map_err(io_error)?;
+    let size = file.metadata().map_err(io_error)?.len();
+    let start = size * index as u64 / peers as u64;
+    let end = size * (index as u64 + 1) / peers as u64;
+    let mut reader = BufReader::new(file);
+    let mut position = start;
+    if start > 0 {
+        // Skips the line straddling the start, which belongs to the previous range, unless the
+        // range starts right after a newline.
+        reader.seek(SeekFrom::Start(start - 1)).map_err(io_error)?;
+        let mut straddling_line = vec![];
+        position = start - 1
+            + reader
+                .read_until(b'\n', &mut straddling_line)
+                .map_err(io_error)? as u64;
+    }
+    let file_name = filename.to_string();
+    let mut line_number = 0;
+    let mut failed = false;
+    let lines = iter::from_fn(move || {
+        if failed || position >= end {
+            return None;
         }
-        Err(msg) => Err(msg),
+        let mut line = String::new();
+        line_number += 1;
+        match reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(read) => {
+                position += read as u64;
+                Some(Ok((line_number, line)))
+            }
+            Err(error) => {
+                failed = true;
+                Some(Err(read_error(&file_name, line_number, error)))
+            }
+        }
+    });
+    let file = filename.to_string();
+    Ok(lines
+        .filter(|line| !matches!(line, Ok((_line_number, line)) if line.trim().is_empty()))
+        .map(move |line| {
+            let (line_number, line) = line?;
+            parse_3enc_line(&line, &file, line_number)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{collect_triples, ParseError, ParseMode};
+    use crate::{load3enc, load3enc_partition};
+    use std::env::temp_dir;
+    use std::fs::write;
+
+    #[test]
+    fn load3enc_reports_malformed_lines() {
+        let path = temp_dir().join("dire_parser_malformed.ntenc");
+        let filename = path.to_str().unwrap();
+        write(&path, "47 4 33\n47 8\n47 x 48\n49 4 33 0\n49 0 47\n").unwrap();
+
+        let strict = collect_triples(load3enc(filename).unwrap(), ParseMode::Strict);
+        assert_eq!(
+            strict.err(),
+            Some(ParseError::MissingTerm {
+                file: filename.to_string(),
+                line: 2
+            })
+        );
+
+        let lenient = collect_triples(load3enc(filename).unwrap(), ParseMode::Lenient).unwrap();
+        assert_eq!(lenient.triples, vec![(47, 4, 33), (49, 0, 47)]);
+        assert_eq!(
+            lenient.skipped,
+            vec![
+                ParseError::MissingTerm {
+                    file: filename.to_string(),
+                    line: 2
+                },
+                ParseError::InvalidTerm {
+                    file: filename.to_string(),
+                    line: 3,
+                    token: "x".to_string()
+                },
+                ParseError::InvalidTerm {
+                    file: filename.to_string(),
+                    line: 4,
+                    token: "0".to_string()
+                },
+            ]
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn load3enc_reports_unreadable_lines() {
+        let path = temp_dir().join("dire_parser_unreadable.ntenc");
+        let filename = path.to_str().unwrap();
+        write(&path, b"47 4 33\n47 \xff 48\n49 0 47\n").unwrap();
+        let expected = ParseError::Io {
+            file: filename.to_string(),
+            reason: "line 2: stream did not contain valid UTF-8".to_string(),
+        };
+
+        let strict = collect_triples(load3enc(filename).unwrap(), ParseMode::Strict);
+        assert_eq!(strict.err(), Some(expected.clone()));
+
+        // Reading does not resume past the error, so the triples after it are not loaded either.
+        let lenient = collect_triples(load3enc(filename).unwrap(), ParseMode::Lenient).unwrap();
+        assert_eq!(lenient.triples, vec![(47, 4, 33)]);
+        assert_eq!(lenient.skipped, vec![expected]);
+
+        let partition = collect_triples(
+            load3enc_partition(filename, 0, 1).unwrap(),
+            ParseMode::Strict,
+        );
+        assert!(matches!(partition, Err(ParseError::Io { .. })));
+    }
+
+    #[test]
+    fn load3enc_partitions_cover_every_line_once() {
+        let filename = "../../data/lubm/1/abox.ntenc";
+        let expected: Vec<(u32, u32, u32)> = load3enc(filename)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        for peers in [1, 2, 3, 7, 16] {
+            let partitioned: Vec<(u32, u32, u32)> = (0..peers)
+                .flat_map(|index| load3enc_partition(filename, index, peers).unwrap())
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(expected, partitioned);
+        }
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dictionary::Dictionary;
+use crate::terms::{RDF_TYPE, XSD};
+
+const RDFS_LITERAL: &str = "<http://www.w3.org/2000/01/rdf-schema#Literal>";
+const RDFS_DATATYPE: &str = "<http://www.w3.org/2000/01/rdf-schema#Datatype>";
+const OWL_SAME_AS: &str = "<http://www.w3.org/2002/07/owl#sameAs>";
+
+/// The local names of the xsd datatypes whose values the reasoner understands.
+pub const DATATYPES: [&str; 19] = [
+    "string",
+    "boolean",
+    "decimal",
+    "integer",
+    "nonNegativeInteger",
+    "nonPositiveInteger",
+    "positiveInteger",
+    "negativeInteger",
+    "long",
+    "int",
+    "short",
+    "byte",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+    "float",
+    "double",
+    "dateTime",
+];
+
+/// The data value of a literal. Two literals denote the same value exactly when their values are
+/// equal, which is what dt-eq and dt-diff are decided on.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    String(String),
+    Boolean(bool),
+    // Decimals, the integers included, as a sign, the digits of the mantissa without leading or
+    // trailing zeros, and a scale. The digits are kept as written, as xsd:decimal and xsd:integer
+    // have no bound on their size. Zero is positive, with no digits.
+    Decimal(bool, String, u32),
+    // Floating point numbers by their bits, with a single NaN, as -0 and +0 are distinct values.
+    Float(u32),
+    Double(u64),
+    // Milliseconds since the epoch, and whether a timezone was given, as values with and without
+    // one are never the same.
+    DateTime(i64, bool),
+}
+
+// The local name of the datatype and the lexical form of a literal, plain literals being strings.
+// Language-tagged literals and literals of other datatypes are left alone.
+fn split_literal(term: &str) -> Option<(&str, &str)> {
+    let rest = term.strip_prefix('"')?;
+    let end = rest.rfind('"')?;
+    let (lexical, suffix) = (&rest[..end], &rest[end + 1..]);
+    if suffix.is_empty() {
+        return Some(("string", lexical));
+    }
+    let datatype = suffix
+        .strip_prefix("^^<")?
+        .strip_suffix('>')?
+        .strip_prefix(XSD)?;
+    DATATYPES
+        .iter()
+        .find(|supported| **supported == datatype)
+        .map(|supported| (*supported, lexical))
+}
+
+/// Parses a literal of a supported datatype into the local name of the datatype and its value,
+/// which is `None` if the lexical form is not valid for the datatype.
+pub fn parse_literal(term: &str) -> Option<(&'static str, Option<Value>)> {
+    let (datatype, lexical) = split_literal(term)?;
+    let value = match datatype {
+        "string" => Some(Value::String(lexical.to_string())),
+        "boolean" => match lexical {
+            "true" | "1" => Some(Value::Boolean(true)),
+            "false" | "0" => Some(Value::Boolean(false)),
+            _ => None,
+        },
+        "float" => parse_floating_point(lexical).map(|value| {
+            let value = value as f32;
+            Value::Float(if value.is_nan() {
+                f32::NAN.to_bits()
+            } else {
+                value.to_bits()
+            })
+        }),
+        "double" => parse_floating_point(lexical).map(|value| {
+            Value::Double(if value.is_nan() {
+                f64::NAN.to_bits()
+            } else {
+                value.to_bits()
+            })
+        }),
+        "dateTime" => parse_date_time(lexical),
+        "decimal" => parse_decimal(lexical),
+        _ => parse_decimal(lexical)
+            .filter(|_| !lexical.contains('.'))
+            .filter(|value| contains(datatype, value)),
+    };
+    // DATATYPES holds the only 'static copy of the name.
+    let datatype = DATATYPES.iter().find(|name| **name == datatype).unwrap();
+    Some((datatype, value))
+}
+
+/// The integer a literal stands for, if it is written with one of the integer datatypes, or as a
+/// plain literal of digits, and fits in an `i128`.
+pub fn integer_value(term: &str) -> Option<i128> {
+    let (datatype, lexical) = split_literal(term)?;
+    let plain = datatype == "string" && term.ends_with('"');
+    let integer = !matches!(
+        datatype,
+        "string" | "boolean" | "decimal" | "float" | "double" | "dateTime"
+    );
+    if !(plain || integer) || lexical.contains('.') {
+        return None;
+    }
+    let value = parse_decimal(lexical)?;
+    if !(plain || contains(datatype, &value)) {
+        return None;
+    }
+    match value {
+        Value::Decimal(negative, digits, 0) => signed_integer(negative, &digits),
+        _ => None,
+    }
+}
+
+// The integer of a sign and digits, or `None` if it does not fit in an `i128`.
+fn signed_integer(negative: bool, digits: &str) -> Option<i128> {
+    if digits.is_empty() {
+        return Some(0);
+    }
+    let magnitude: i128 = digits.parse().ok()?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Whether the value space of the datatype holds the value. The integer datatypes are all subsets
+/// of decimal, whereas the rest are disjoint from each other.
+pub fn contains(datatype: &str, value: &Value) -> bool {
+    // The sign of an integer, zero being neither, for the unbounded integer datatypes.
+    let sign = match value {
+        Value::Decimal(_, digits, 0) if digits.is_empty() => Some(0),
+        Value::Decimal(negative, _, 0) => Some(if *negative { -1 } else { 1 }),
+        _ => None,
+    };
+    // An integer too large for an `i128` is out of range of every bounded datatype.
+    let in_range = |min: i128, max: i128| match value {
+        Value::Decimal(negative, digits, 0) => {
+            signed_integer(*negative, digits).is_some_and(|i| min <= i && i <= max)
+        }
+        _ => false,
+    };
+    match datatype {
+        "string" => matches!(value, Value::String(_)),
+        "boolean" => matches!(value, Value::Boolean(_)),
+        "decimal" => matches!(value, Value::Decimal(_, _, _)),
+        "integer" => sign.is_some(),
+        "nonNegativeInteger" => sign.is_some_and(|sign| sign >= 0),
+        "nonPositiveInteger" => sign.is_some_and(|sign| sign <= 0),
+        "positiveInteger" => sign == Some(1),
+        "negativeInteger" => sign == Some(-1),
+        "long" => in_range(i64::MIN as i128, i64::MAX as i128),
+        "int" => in_range(i32::MIN as i128, i32::MAX as i128),
+        "short" => in_range(i16::MIN as i128, i16::MAX as i128),
+        "byte" => in_range(i8::MIN as i128, i8::MAX as i128),
+        "unsignedLong" => in_range(0, u64::MAX as i128),
+        "unsignedInt" => in_range(0, u32::MAX as i128),
+        "unsignedShort" => in_range(0, u16::MAX as i128),
+        "unsignedByte" => in_range(0, u8::MAX as i128),
+        "float" => matches!(value, Value::Float(_)),
+        "double" => matches!(value, Value::Double(_)),
+        "dateTime" => matches!(value, Value::DateTime(_, _)),
+        _ => false,
+    }
+}
+
+fn parse_decimal(lexical: &str) -> Option<Value> {
+    let (negative, unsigned) = match lexical.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, lexical.strip_prefix('+').unwrap_or(lexical)),
+    };
+    let (integral, fractional) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if integral.is_empty() && fractional.is_empty()
+        || !integral.chars().all(|c| c.is_ascii_digit())
+        || !fractional.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let fractional = fractional.trim_end_matches('0');
+    let digits = format!("{}{}", integral, fractional)
+        .trim_start_matches('0')
+        .to_string();
+    if digits.is_empty() {
+        return Some(Value::Decimal(false, digits, 0));
+    }
+    Some(Value::Decimal(negative, digits, fractional.len() as u32))
+}
+
+fn parse_floating_point(lexical: &str) -> Option<f64> {
+    match lexical {
+        "INF" | "+INF" => Some(f64::INFINITY),
+        "-INF" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        // Rust also accepts spellings such as "inf" that xsd does not.
+        _ if lexical
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')) =>
+        {
+            lexical.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+// Days from 1970-01-01 to the given proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// yyyy-mm-ddThh:mm:ss(.s+)?(Z|(+|-)hh:mm)?
+fn parse_date_time(lexical: &str) -> Option<Value> {
+    let number = |digits: &str| -> Option<i64> {
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    };
+    let (negative_year, unsigned) = match lexical.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, lexical),
+    };
+    let (date, time) = unsigned.split_once('T')?;
+    let mut date_parts = date.splitn(3, '-');
+    let year_digits = date_parts.next()?;
+    if year_digits.len() < 4 {
+        return None;
+    }
+    let year = number(year_digits)? * if negative_year { -1 } else { 1 };
+    let month = number(date_parts.next().filter(|part| part.len() == 2)?)?;
+    let day = number(date_parts.next().filter(|part| part.len() == 2)?)?;
+
+    let (time, offset_minutes) = if let Some(time) = time.strip_suffix('Z') {
+        (time, Some(0))
+    } else if time.len() > 6 && matches!(&time[time.len() - 6..time.len() - 5], "+" | "-") {
+        let (time, offset) = time.split_at(time.len() - 6);
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let minutes = number(hours)? * 60 + number(minutes)?;
+        if minutes > 14 * 60 {
+            return None;
+        }
+        (
+            time,
+            Some(if offset.starts_with('-') {
+                -minutes
+            } else {
+                minutes
+            }),
+        )
+    } else {
+        (time, None)
+    };
+    let mut time_parts = time.splitn(3, ':');
+    let hour = number(time_parts.next().filter(|part| part.len() == 2)?)?;
+    let minute = number(time_parts.next().filter(|part| part.len() == 2)?)?;
+    let seconds = time_parts.next()?;
+    let (whole_seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    if whole_seconds.len() != 2 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let second = number(whole_seconds)?;
+    let millis: i64 = format!("{:0<3}", fraction)[..3].parse().ok()?;
+
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    let end_of_day = hour == 24 && minute == 0 && second == 0 && millis == 0;
+    if day < 1 || day > days_in_month || (hour > 23 && !end_of_day) || minute > 59 || second > 59 {
+        return None;
+    }
+    let minutes =
+        (days_from_civil(year, month, day) * 24 + hour) * 60 + minute - offset_minutes.unwrap_or(0);
+    Some(Value::DateTime(
+        (minutes * 60 + second) * 1000 + millis,
+        offset_minutes.is_some(),
+    ))
+}
+
+/// What the datatype rules entail from some literals, which depends on nothing but the literals
+/// themselves.
+#[derive(Default)]
+pub struct DatatypeEntailments {
+    /// dt-type1, dt-type2 and dt-eq, along with an `rdfs:Literal` typing of every literal of a
+    /// supported datatype, by which the engine tells them apart from other terms.
+    pub triples: Vec<(u32, u32, u32)>,
+    /// Every ill-typed literal, alongside the datatype it claims to have, which dt-not-type
+    /// rejects. They are not typed with it, as they are not in its value space.
+    pub ill_typed: Vec<(u32, u32)>,
+}
+
+/// Works out what the datatype rules entail one literal at a time, so that literals can be
+/// reasoned with as they show up, each of them only against those seen before it.
+pub struct DatatypeRules {
+    r#type: u32,
+    literal: u32,
+    datatype_class: u32,
+    same_as: u32,
+    datatypes: Vec<(&'static str, u32)>,
+    // Every literal seen so far of each value
+    by_value: HashMap<Value, Vec<u32>>,
+    seen: HashSet<u32>,
+}
+
+impl DatatypeRules {
+    pub fn new(dictionary: &mut Dictionary) -> Self {
+        Self {
+            r#type: dictionary.encode(RDF_TYPE),
+            literal: dictionary.encode(RDFS_LITERAL),
+            datatype_class: dictionary.encode(RDFS_DATATYPE),
+            same_as: dictionary.encode(OWL_SAME_AS),
+            datatypes: DATATYPES
+                .iter()
+                .map(|name| (*name, dictionary.encode(&format!("<{}{}>", XSD, name))))
+                .collect(),
+            by_value: HashMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// What the rules entail from the term, which is nothing unless it is a literal of a supported
+    /// datatype that was not seen before. dt-type1 comes along with the first such literal.
+    pub fn entail(&mut self, id: u32, term: &str) -> DatatypeEntailments {
+        let mut entailments = DatatypeEntailments::default();
+        let (declared, value) = match parse_literal(term) {
+            Some(literal) if !self.seen.contains(&id) => literal,
+            _ => return entailments,
+        };
+        // dt-type1
+        if self.seen.is_empty() {
+            entailments.triples.extend(
+                self.datatypes
+                    .iter()
+                    .map(|(_name, datatype)| (*datatype, self.r#type, self.datatype_class)),
+            );
+        }
+        self.seen.insert(id);
+        entailments.triples.push((id, self.r#type, self.literal));
+        match value {
+            Some(value) => {
+                // dt-type2, for every datatype whose value space holds the value
+                for (name, datatype) in &self.datatypes {
+                    if contains(name, &value) {
+                        entailments.triples.push((id, self.r#type, *datatype));
+                    }
+                }
+                // dt-eq, with every literal of the same value seen before, which the engine relies
+                // on to tell apart the sameAs links dt-diff rejects. Distinct lexical forms of a
+                // value are few.
+                let equal = self.by_value.entry(value).or_default();
+                for other in equal.iter() {
+                    entailments.triples.push((id, self.same_as, *other));
+                    entailments.triples.push((*other, self.same_as, id));
+                }
+                equal.push(id);
+            }
+            None => {
+                let (_name, datatype) = self
+                    .datatypes
+                    .iter()
+                    .find(|(name, _)| *name == declared)
+                    .unwrap();
+                entailments.ill_typed.push((id, *datatype));
+            }
+        }
+        entailments
+    }
+}
+
+/// What the datatype rules entail from every literal in a dictionary at once.
+pub fn datatype_entailments(dictionary: &mut Dictionary) -> DatatypeEntailments {
+    let mut literals: Vec<(u32, String)> = dictionary
+        .iter()
+        .filter(|(_id, term)| parse_literal(term).is_some())
+        .map(|(id, term)| (id, term.to_string()))
+        .collect();
+    literals.sort();
+
+    let mut rules = DatatypeRules::new(dictionary);
+    let mut entailments = DatatypeEntailments::default();
+    for (id, term) in literals {
+        let DatatypeEntailments { triples, ill_typed } = rules.entail(id, &term);
+        entailments.triples.extend(triples);
+        entailments.ill_typed.extend(ill_typed);
+    }
+    entailments
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::literals::{datatype_entailments, parse_literal, DatatypeRules, Value};
+
+    #[test]
+    fn parse_literal_normalizes_values() {
+        let integer = "\"010\"^^<http://www.w3.org/2001/XMLSchema#integer>";
+        let decimal = "\"10.0\"^^<http://www.w3.org/2001/XMLSchema#decimal>";
+        assert_eq!(
+            Some(("integer", Some(Value::Decimal(false, "10".to_string(), 0)))),
+            parse_literal(integer)
+        );
+        assert_eq!(
+            parse_literal("\"-0.0\"^^<http://www.w3.org/2001/XMLSchema#decimal>")
+                .unwrap()
+                .1,
+            parse_literal("\"0\"^^<http://www.w3.org/2001/XMLSchema#integer>")
+                .unwrap()
+                .1
+        );
+        assert_eq!(
+            parse_literal(integer).unwrap().1,
+            parse_literal(decimal).unwrap().1
+        );
+        assert_eq!(
+            Some(("byte", None)),
+            parse_literal("\"300\"^^<http://www.w3.org/2001/XMLSchema#byte>")
+        );
+        assert_eq!(
+            parse_literal("\"2002-10-10T17:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime>")
+                .unwrap()
+                .1,
+            parse_literal(
+                "\"2002-10-10T12:00:00-05:00\"^^<http://www.w3.org/2001/XMLSchema#dateTime>"
+            )
+            .unwrap()
+            .1
+        );
+        assert_eq!(
+            Some(("string", Some(Value::String("a".to_string())))),
+            parse_literal("\"a\"")
+        );
+        assert_eq!(None, parse_literal("\"a\"@en"));
+        assert_eq!(None, parse_literal("<http://example.org/a>"));
+    }
+
+    #[test]
+    fn datatype_entailments_types_and_equates_literals() {
+        let mut dictionary = Dictionary::new();
+        let one = dictionary.encode("\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>");
+        let one_again = dictionary.encode("\"1.0\"^^<http://www.w3.org/2001/XMLSchema#decimal>");
+        let ill_typed = dictionary.encode("\"one\"^^<http://www.w3.org/2001/XMLSchema#integer>");
+        let entailments = datatype_entailments(&mut dictionary);
+
+        let r#type = dictionary.encode("<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>");
+        let same_as = dictionary.encode("<http://www.w3.org/2002/07/owl#sameAs>");
+        let integer = dictionary.encode("<http://www.w3.org/2001/XMLSchema#integer>");
+        let unsigned_byte = dictionary.encode("<http://www.w3.org/2001/XMLSchema#unsignedByte>");
+        assert!(entailments.triples.contains(&(one_again, r#type, integer)));
+        assert!(entailments.triples.contains(&(one, r#type, unsigned_byte)));
+        assert!(entailments.triples.contains(&(one, same_as, one_again)));
+        assert!(entailments.triples.contains(&(one_again, same_as, one)));
+        assert_eq!(
+            1,
+            entailments
+                .triples
+                .iter()
+                .filter(|(s, _p, _o)| *s == ill_typed)
+                .count()
+        );
+        assert_eq!(vec![(ill_typed, integer)], entailments.ill_typed);
+    }
+
+    #[test]
+    fn datatype_rules_entail_each_literal_once() {
+        let mut dictionary = Dictionary::new();
+        let one_term = "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>";
+        let one_again_term = "\"01\"^^<http://www.w3.org/2001/XMLSchema#byte>";
+        let one = dictionary.encode(one_term);
+        let one_again = dictionary.encode(one_again_term);
+        let person = dictionary.encode("<http://example.org/Person>");
+        let mut rules = DatatypeRules::new(&mut dictionary);
+        let same_as = dictionary.encode("<http://www.w3.org/2002/07/owl#sameAs>");
+        let datatype = dictionary.encode("<http://www.w3.org/2000/01/rdf-schema#Datatype>");
+
+        let first = rules.entail(one, one_term);
+        // dt-type1, along with the first literal only
+        assert_eq!(
+            19,
+            first
+                .triples
+                .iter()
+                .filter(|(_s, _p, o)| *o == datatype)
+                .count()
+        );
+        assert!(rules.entail(one, one_term).triples.is_empty());
+        assert!(rules
+            .entail(person, "<http://example.org/Person>")
+            .triples
+            .is_empty());
+        let second = rules.entail(one_again, one_again_term);
+        assert!(second.triples.iter().all(|(_s, _p, o)| *o != datatype));
+        assert_eq!(
+            vec![(one_again, same_as, one), (one, same_as, one_again)],
+            second
+                .triples
+                .into_iter()
+                .filter(|(_s, p, _o)| *p == same_as)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn integers_beyond_i128_keep_their_value() {
+        let digits = "1".repeat(45);
+        let integer = format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#integer>", digits);
+        let decimal = format!(
+            "\"{}.0\"^^<http://www.w3.org/2001/XMLSchema#decimal>",
+            digits
+        );
+        let positive = format!(
+            "\"{}\"^^<http://www.w3.org/2001/XMLSchema#positiveInteger>",
+            digits
+        );
+        let negative = format!(
+            "\"-{}\"^^<http://www.w3.org/2001/XMLSchema#negativeInteger>",
+            digits
+        );
+        let long = format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#long>", digits);
+        assert_eq!(
+            Some(("integer", Some(Value::Decimal(false, digits.clone(), 0)))),
+            parse_literal(&integer)
+        );
+        assert_eq!(
+            parse_literal(&integer).unwrap().1,
+            parse_literal(&decimal).unwrap().1
+        );
+        assert!(parse_literal(&positive).unwrap().1.is_some());
+        assert!(parse_literal(&negative).unwrap().1.is_some());
+        assert_eq!(Some(("long", None)), parse_literal(&long));
+        assert_ne!(
+            parse_literal(&integer).unwrap().1,
+            parse_literal(&format!(
+                "\"{}2\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                digits
+            ))
+            .unwrap()
+            .1
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::dictionary::Dictionary;
+use crate::error::ParseError;
+use crate::read_file;
+
+// Splits the next term off the line, returning it in its lexical form alongside the remainder.
+fn next_term(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start();
+    let end = match line.chars().next()? {
+        '<' => line.find('>')? + 1,
+        '_' if line.starts_with("_:") => line.find(char::is_whitespace).unwrap_or(line.len()),
+        '"' => {
+            let mut escaped = false;
+            let mut closing_quote = None;
+            for (idx, character) in line.char_indices().skip(1) {
+                match character {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => {
+                        closing_quote = Some(idx);
+                        break;
+                    }
+                    _ => escaped = false,
+                }
+            }
+            let after_quote = closing_quote? + 1;
+            let suffix = &line[after_quote..];
+            if suffix.starts_with("^^<") {
+                after_quote + suffix.find('>')? + 1
+            } else if suffix.starts_with('@') {
+                after_quote + suffix.find(char::is_whitespace).unwrap_or(suffix.len())
+            } else {
+                after_quote
+            }
+        }
+        _ => return None,
+    };
+    Some((&line[..end], &line[end..]))
+}
+
+// Splits the terms off a line that is neither blank nor a comment, reporting the offending token
+// if it is not a well-formed triple.
+fn split_terms<'l>(
+    line: &'l str,
+    file: &str,
+    line_number: usize,
+) -> Result<(&'l str, &'l str, &'l str), ParseError> {
+    let mut rest = line;
+    let mut terms = [""; 3];
+    for term in terms.iter_mut() {
+        let trimmed = rest.trim_start();
+        match (next_term(trimmed), trimmed.split_whitespace().next()) {
+            (Some((next, remainder)), _) => {
+                *term = next;
+                rest = remainder;
+            }
+            (None, Some(token)) if token != "." => {
+                return Err(ParseError::InvalidTerm {
+                    file: file.to_string(),
+                    line: line_number,
+                    token: token.to_string(),
+                })
+            }
+            (None, _) => {
+                return Err(ParseError::MissingTerm {
+                    file: file.to_string(),
+                    line: line_number,
+                })
+            }
+        }
+    }
+    if rest.trim() != "." {
+        return Err(ParseError::Syntax {
+            file: file.to_string(),
+            line: line_number,
+            token: rest.trim().to_string(),
+            reason: "expected `.` after the object",
+        });
+    }
+    Ok((terms[0], terms[1], terms[2]))
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// Splits an N-Triples line into its subject, predicate and object terms. Blank lines and
+/// comments yield `None`, as do lines that are not a well-formed triple.
+pub fn parse_ntriples_line(line: &str) -> Option<(&str, &str, &str)> {
+    if is_blank_or_comment(line) {
+        return None;
+    }
+    split_terms(line, "", 0).ok()
+}
+
+// Blank node labels are scoped to the file, like Turtle ones, so the same label in another file
+// is another node.
+fn encode_term(
+    term: &str,
+    dictionary: &mut Dictionary,
+    blank_nodes: &mut HashMap<String, u32>,
+) -> u32 {
+    if !term.starts_with("_:") {
+        return dictionary.encode(term);
+    }
+    *blank_nodes
+        .entry(term.to_string())
+        .or_insert_with(|| dictionary.fresh_blank_node())
+}
+
+pub fn loadnt<'a>(
+    filename: &str,
+    dictionary: &'a mut Dictionary,
+) -> Result<impl Iterator<Item = Result<(u32, u32, u32), ParseError>> + 'a, ParseError> {
+    let file = filename.to_string();
+    let lines = read_file(filename)?;
+    let mut blank_nodes = HashMap::new();
+    Ok(lines
+        .filter(|line| !matches!(line, Ok((_line_number, line)) if is_blank_or_comment(line)))
+        .map(move |line| {
+            let (line_number, line) = line?;
+            let (s, p, o) = split_terms(&line, &file, line_number)?;
+            let triple = (
+                encode_term(s, dictionary, &mut blank_nodes),
+                encode_term(p, dictionary, &mut blank_nodes),
+                encode_term(o, dictionary, &mut blank_nodes),
+            );
+            Ok(dictionary.normalize_cardinality(triple))
+        }))
+}
+
+pub fn writent<'a>(
+    filename: &str,
+    triples: impl Iterator<Item = &'a (u32, u32, u32)>,
+    dictionary: &Dictionary,
+) -> Result<(), ParseError> {
+    let io_error = |reason: String| ParseError::Io {
+        file: filename.to_string(),
+        reason,
+    };
+    let file = File::create(filename).map_err(|error| io_error(error.to_string()))?;
+    let mut writer = BufWriter::new(file);
+    for triple in triples {
+        let (s, p, o) = dictionary
+            .decode_triple(*triple)
+            .ok_or_else(|| io_error(format!("{:?} has ids missing from the dictionary", triple)))?;
+        writeln!(writer, "{} {} {} .", s, p, o).map_err(|error| io_error(error.to_string()))?;
+    }
+    writer.flush().map_err(|error| io_error(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::load3enc;
+    use crate::ntriples::{loadnt, parse_ntriples_line, writent};
+    use crate::turtle::parse_turtle;
+    use std::env::temp_dir;
+    use std::fs::read_to_string;
+
+    #[test]
+    fn parse_ntriples_line_works() {
+        assert_eq!(
+            parse_ntriples_line("<http://a> <http://b> \"some \\\"quoted\\\" text\"@en ."),
+            Some((
+                "<http://a>",
+                "<http://b>",
+                "\"some \\\"quoted\\\" text\"@en"
+            ))
+        );
+        assert_eq!(
+            parse_ntriples_line(
+                "_:b0 <http://b> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> ."
+            ),
+            Some((
+                "_:b0",
+                "<http://b>",
+                "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+            ))
+        );
+        assert_eq!(parse_ntriples_line("# a comment"), None);
+        assert_eq!(parse_ntriples_line("<http://a> <http://b> ."), None);
+    }
+
+    #[test]
+    fn loadnt_matches_ntencoder() {
+        let mut dictionary = Dictionary::new();
+        let tbox: Vec<(u32, u32, u32)> = loadnt("../../data/lubm/toy/tbox.nt", &mut dictionary)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let abox: Vec<(u32, u32, u32)> = loadnt("../../data/lubm/toy/abox.nt", &mut dictionary)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let expected_tbox: Vec<(u32, u32, u32)> = load3enc("../../data/lubm/toy/tbox.ntenc")
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let expected_abox: Vec<(u32, u32, u32)> = load3enc("../../data/lubm/toy/abox.ntenc")
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(expected_tbox, tbox);
+        assert_eq!(expected_abox, abox);
+    }
+
+    #[test]
+    fn loadnt_scopes_blank_nodes_to_each_file() {
+        let path = temp_dir().join("dire_parser_blank_nodes.nt");
+        std::fs::write(
+            &path,
+            "_:b0 <http://example.org/knows> _:b1 .\n\
+             _:b1 <http://example.org/knows> _:b0 .\n",
+        )
+        .unwrap();
+        let mut dictionary = Dictionary::new();
+        let first: Vec<(u32, u32, u32)> = loadnt(path.to_str().unwrap(), &mut dictionary)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let second: Vec<(u32, u32, u32)> = loadnt(path.to_str().unwrap(), &mut dictionary)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // The same label is the same node within a file
+        let (b0, knows, b1) = first[0];
+        assert_eq!((b1, knows, b0), first[1]);
+        assert_ne!(b0, b1);
+        // and another one in the next
+        let (other_b0, _knows, other_b1) = second[0];
+        assert!(![b0, b1].contains(&other_b0) && ![b0, b1].contains(&other_b1));
+    }
+
+    #[test]
+    fn dictionary_and_writent_round_trip() {
+        let mut dictionary = Dictionary::new();
+        let abox: Vec<(u32, u32, u32)> = loadnt("../../data/lubm/toy/abox.nt", &mut dictionary)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let dictionary_path = temp_dir().join("dire_parser_round_trip.dict");
+        let abox_path = temp_dir().join("dire_parser_round_trip.nt");
+        dictionary.save(dictionary_path.to_str().unwrap()).unwrap();
+        let mut loaded_dictionary = Dictionary::load(dictionary_path.to_str().unwrap()).unwrap();
+        assert_eq!(dictionary.len(), loaded_dictionary.len());
+
+        writent(abox_path.to_str().unwrap(), abox.iter(), &loaded_dictionary).unwrap();
+        let reloaded_abox: Vec<(u32, u32, u32)> =
+            loadnt(abox_path.to_str().unwrap(), &mut loaded_dictionary)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+        assert_eq!(abox, reloaded_abox);
+        assert_eq!(dictionary.len(), loaded_dictionary.len());
+    }
+
+    #[test]
+    fn writent_leaves_literals_as_they_were_written() {
+        let mut dictionary = Dictionary::new();
+        let document = "@prefix ex: <http://example.org/> .\n\
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .\n\
+            ex:g ex:name \"1\" .\n\
+            ex:r owl:maxCardinality \"1\" .\n";
+        let triples = parse_turtle(document, "", &mut dictionary).unwrap();
+
+        let path = temp_dir().join("dire_parser_literals.nt");
+        writent(path.to_str().unwrap(), triples.iter(), &dictionary).unwrap();
+        assert_eq!(
+            "<http://example.org/g> <http://example.org/name> \"1\" .\n\
+             <http://example.org/r> <http://www.w3.org/2002/07/owl#maxCardinality> \
+             \"1\"^^<http://www.w3.org/2001/XMLSchema#nonNegativeInteger> .\n",
+            read_to_string(path).unwrap()
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+
+use roxmltree::{Document, Node, ParsingOptions, NS_XML_URI};
+
+use crate::dictionary::Dictionary;
+use crate::error::ParseError;
+use crate::terms::{encode_collection, quote_literal, resolve_iri, RDF_TYPE};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+fn is_rdf(node: &Node, name: &str) -> bool {
+    node.tag_name().namespace() == Some(RDF) && node.tag_name().name() == name
+}
+
+fn qualified_name(node: &Node) -> Result<String, ParseError> {
+    match node.tag_name().namespace() {
+        Some(namespace) => Ok(format!("{}{}", namespace, node.tag_name().name())),
+        None => Err(ParseError::Syntax {
+            file: String::new(),
+            line: node.document().text_pos_at(node.range().start).row as usize,
+            token: node.tag_name().name().to_string(),
+            reason: "unqualified element",
+        }),
+    }
+}
+
+fn rdf_attribute<'a>(node: &Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((RDF, name))
+}
+
+// Attributes that encode properties, as opposed to the syntactic rdf:* and xml:* ones.
+fn property_attributes<'a>(node: &Node<'a, '_>) -> Vec<(String, &'a str)> {
+    node.attributes()
+        .iter()
+        .filter_map(|attribute| match attribute.namespace() {
+            Some(NS_XML_URI) | None => None,
+            Some(RDF)
+                if matches!(
+                    attribute.name(),
+                    "about"
+                        | "ID"
+                        | "nodeID"
+                        | "resource"
+                        | "datatype"
+                        | "parseType"
+                        | "aboutEach"
+                        | "aboutEachPrefix"
+                        | "bagID"
+                ) =>
+            {
+                None
+            }
+            Some(namespace) => Some((
+                format!("{}{}", namespace, attribute.name()),
+                attribute.value(),
+            )),
+        })
+        .collect()
+}
+
+struct RdfXmlParser<'a, 'b> {
+    input: &'a str,
+    dictionary: &'b mut Dictionary,
+    // rdf:nodeID labels are scoped to their document, like Turtle blank node labels.
+    blank_nodes: HashMap<String, u32>,
+    triples: Vec<(u32, u32, u32)>,
+}
+
+impl<'a, 'b> RdfXmlParser<'a, 'b> {
+    fn encode_node_id(&mut self, node_id: &str) -> u32 {
+        let dictionary = &mut self.dictionary;
+        *self
+            .blank_nodes
+            .entry(node_id.to_string())
+            .or_insert_with(|| dictionary.fresh_blank_node())
+    }
+
+    // xml:base and xml:lang are inherited by every descendant that does not override them.
+    fn scope(
+        &self,
+        node: &Node,
+        base: &str,
+        language: &Option<String>,
+    ) -> (String, Option<String>) {
+        let base = match node.attribute((NS_XML_URI, "base")) {
+            Some(xml_base) => resolve_iri(base, xml_base),
+            None => base.to_string(),
+        };
+        let language = match node.attribute((NS_XML_URI, "lang")) {
+            Some("") => None,
+            Some(xml_lang) => Some(xml_lang.to_string()),
+            None => language.clone(),
+        };
+        (base, language)
+    }
+
+    fn encode_iri(&mut self, iri: &str) -> u32 {
+        self.dictionary.encode(&format!("<{}>", iri))
+    }
+
+    fn encode_literal(&mut self, text: &str, language: &Option<String>) -> u32 {
+        let literal = match language {
+            Some(language) => format!("{}@{}", quote_literal(text, false), language),
+            None => quote_literal(text, false),
+        };
+        self.dictionary.encode(&literal)
+    }
+
+    fn encode_property_attributes(
+        &mut self,
+        node: &Node,
+        subject: u32,
+        base: &str,
+        language: &Option<String>,
+    ) {
+        for (predicate, value) in property_attributes(node) {
+            let predicate_id = self.encode_iri(&predicate);
+            let object = if predicate == format!("{}type", RDF) {
+                self.encode_iri(&resolve_iri(base, value))
+            } else {
+                self.encode_literal(value, language)
+            };
+            let triple = self
+                .dictionary
+                .normalize_cardinality((subject, predicate_id, object));
+            self.triples.push(triple);
+        }
+    }
+
+    fn parse_node_element(
+        &mut self,
+        node: &Node,
+        base: &str,
+        language: &Option<String>,
+    ) -> Result<u32, ParseError> {
+        let (base, language) = self.scope(node, base, language);
+        let subject = if let Some(about) = rdf_attribute(node, "about") {
+            self.encode_iri(&resolve_iri(&base, about))
+        } else if let Some(id) = rdf_attribute(node, "ID") {
+            self.encode_iri(&resolve_iri(&base, &format!("#{}", id)))
+        } else if let Some(node_id) = rdf_attribute(node, "nodeID") {
+            self.encode_node_id(node_id)
+        } else {
+            self.dictionary.fresh_blank_node()
+        };
+
+        // Typed node elements stand for an rdf:type triple.
+        if !is_rdf(node, "Description") {
+            let r#type = self.dictionary.encode(RDF_TYPE);
+            let class = self.encode_iri(&qualified_name(node)?);
+            self.triples.push((subject, r#type, class));
+        }
+        self.encode_property_attributes(node, subject, &base, &language);
+
+        let mut list_index = 1;
+        for child in node.children().filter(|child| child.is_element()) {
+            self.parse_property_element(&child, subject, &base, &language, &mut list_index)?;
+        }
+        Ok(subject)
+    }
+
+    fn parse_property_element(
+        &mut self,
+        node: &Node,
+        subject: u32,
+        base: &str,
+        language: &Option<String>,
+        list_index: &mut usize,
+    ) -> Result<(), ParseError> {
+        let (base, language) = self.scope(node, base, language);
+        let predicate = if is_rdf(node, "li") {
+            *list_index += 1;
+            self.encode_iri(&format!("{}_{}", RDF, *list_index - 1))
+        } else {
+            self.encode_iri(&qualified_name(node)?)
+        };
+        let mut element_children = node.children().filter(|child| child.is_element());
+
+        let object = match rdf_attribute(node, "parseType") {
+            Some("Resource") => {
+                let object = self.dictionary.fresh_blank_node();
+                let mut nested_list_index = 1;
+                for child in element_children {
+                    self.parse_property_element(
+                        &child,
+                        object,
+                        &base,
+                        &language,
+                        &mut nested_list_index,
+                    )?;
+                }
+                object
+            }
+            Some("Collection") => {
+                let mut elements = vec![];
+                for child in element_children {
+                    elements.push(self.parse_node_element(&child, &base, &language)?);
+                }
+                encode_collection(&elements, self.dictionary, &mut self.triples)
+            }
+            Some(_) => {
+                // parseType="Literal", and any unknown parse type, keeps the markup verbatim.
+                let contents = match (node.first_child(), node.last_child()) {
+                    (Some(first), Some(last)) => &self.input[first.range().start..last.range().end],
+                    _ => "",
+                };
+                let literal = format!("{}^^<{}XMLLiteral>", quote_literal(contents, false), RDF);
+                self.dictionary.encode(&literal)
+            }
+            None => {
+                if let Some(resource) = rdf_attribute(node, "resource") {
+                    let object = self.encode_iri(&resolve_iri(&base, resource));
+                    self.encode_property_attributes(node, object, &base, &language);
+                    object
+                } else if let Some(node_id) = rdf_attribute(node, "nodeID") {
+                    let object = self.encode_node_id(node_id);
+                    self.encode_property_attributes(node, object, &base, &language);
+                    object
+                } else if let Some(child) = element_children.next() {
+                    self.parse_node_element(&child, &base, &language)?
+                } else if !property_attributes(node).is_empty() {
+                    let object = self.dictionary.fresh_blank_node();
+                    self.encode_property_attributes(node, object, &base, &language);
+                    object
+                } else {
+                    let text: String = node
+                        .children()
+                        .filter(|child| child.is_text())
+                        .filter_map(|child| child.text())
+                        .collect();
+                    match rdf_attribute(node, "datatype") {
+                        Some(datatype) => {
+                            let literal = format!(
+                                "{}^^<{}>",
+                                quote_literal(&text, false),
+                                resolve_iri(&base, datatype)
+                            );
+                            self.dictionary.encode(&literal)
+                        }
+                        None => self.encode_literal(&text, &language),
+                    }
+                }
+            }
+        };
+        let triple = self
+            .dictionary
+            .normalize_cardinality((subject, predicate, object));
+        self.triples.push(triple);
+        Ok(())
+    }
+}
+
+pub fn parse_rdfxml(
+    document: &str,
+    base: &str,
+    dictionary: &mut Dictionary,
+) -> Result<Vec<(u32, u32, u32)>, ParseError> {
+    let xml = Document::parse_with_options(document, ParsingOptions { allow_dtd: true }).map_err(
+        |error| ParseError::Syntax {
+            file: String::new(),
+            line: error.pos().row as usize,
+            token: String::new(),
+            reason: "malformed XML",
+        },
+    )?;
+    let mut parser = RdfXmlParser {
+        input: document,
+        dictionary,
+        blank_nodes: HashMap::new(),
+        triples: vec![],
+    };
+    let root = xml.root_element();
+    if is_rdf(&root, "RDF") {
+        let (base, language) = parser.scope(&root, base, &None);
+        for child in root.children().filter(|child| child.is_element()) {
+            parser.parse_node_element(&child, &base, &language)?;
+        }
+    } else {
+        parser.parse_node_element(&root, base, &None)?;
+    }
+    Ok(parser.triples)
+}
+
+pub fn loadrdfxml<'a>(
+    filename: &str,
+    dictionary: &'a mut Dictionary,
+) -> Result<impl Iterator<Item = Result<(u32, u32, u32), ParseError>> + 'a, ParseError> {
+    let document = read_to_string(filename).map_err(|error| ParseError::Io {
+        file: filename.to_string(),
+        reason: error.to_string(),
+    })?;
+    let triples =
+        parse_rdfxml(&document, "", dictionary).map_err(|error| error.in_file(filename))?;
+    Ok(triples.into_iter().map(Ok))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::ntriples::loadnt;
+    use crate::rdfxml::parse_rdfxml;
+
+    #[test]
+    fn parse_rdfxml_matches_ntriples() {
+        let mut dictionary = Dictionary::new();
+        let expected_tbox: Vec<(u32, u32, u32)> =
+            loadnt("../../data/lubm/toy/tbox.nt", &mut dictionary)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        let document = r##"<?xml version="1.0"?>
+            <!DOCTYPE rdf:RDF [
+                <!ENTITY owl "http://www.w3.org/2002/07/owl#">
+            ]>
+            <rdf:RDF
+                xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                xmlns:rdfs="http://www.w3.org/2000/01/rdf-schema#"
+                xmlns:owl="http://www.w3.org/2002/07/owl#"
+                xml:base="http://swat.cse.lehigh.edu/onto/univ-bench.owl">
+                <owl:Class rdf:ID="Employee">
+                    <rdfs:label>Employee</rdfs:label>
+                </owl:Class>
+                <owl:Class rdf:ID="Faculty" rdfs:label="faculty member">
+                    <rdfs:subClassOf rdf:resource="#Employee" />
+                </owl:Class>
+                <owl:Class rdf:ID="FullProfessor">
+                    <rdfs:label>full professor</rdfs:label>
+                    <rdfs:subClassOf rdf:resource="#Professor" />
+                </owl:Class>
+                <owl:Class rdf:about="#Professor">
+                    <rdfs:label>professor</rdfs:label>
+                    <rdfs:subClassOf rdf:resource="#Faculty" />
+                </owl:Class>
+                <owl:ObjectProperty rdf:ID="headOf">
+                    <rdfs:label>is the head of</rdfs:label>
+                    <rdfs:subPropertyOf rdf:resource="#worksFor" />
+                </owl:ObjectProperty>
+                <owl:ObjectProperty rdf:ID="memberOf">
+                    <rdfs:label>member of</rdfs:label>
+                </owl:ObjectProperty>
+                <owl:ObjectProperty rdf:ID="worksFor">
+                    <rdfs:label>Works For</rdfs:label>
+                    <rdfs:subPropertyOf rdf:resource="#memberOf" />
+                    <rdf:type rdf:resource="&owl;TransitiveProperty" />
+                </owl:ObjectProperty>
+                <rdf:Description rdf:about="#employs">
+                    <owl:inverseOf rdf:resource="#worksFor" />
+                </rdf:Description>
+                <owl:ObjectProperty rdf:ID="teacherOf">
+                    <rdfs:label>teaches</rdfs:label>
+                    <rdfs:domain rdf:resource="#Faculty" />
+                    <rdfs:range rdf:resource="#Course" />
+                </owl:ObjectProperty>
+            </rdf:RDF>"##;
+        let tbox = parse_rdfxml(document, "", &mut dictionary).unwrap();
+
+        assert_eq!(expected_tbox, tbox);
+    }
+
+    #[test]
+    fn parse_rdfxml_unrolls_collections() {
+        let mut dictionary = Dictionary::new();
+        let document = r#"<rdf:RDF
+                xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                xmlns:owl="http://www.w3.org/2002/07/owl#">
+                <owl:Class rdf:about="http://example.org/onto#Parent">
+                    <owl:intersectionOf rdf:parseType="Collection">
+                        <owl:Class rdf:about="http://example.org/onto#Person" />
+                        <rdf:Description rdf:about="http://example.org/onto#HasChild" />
+                    </owl:intersectionOf>
+                </owl:Class>
+            </rdf:RDF>"#;
+        let triples = parse_rdfxml(document, "", &mut dictionary).unwrap();
+        let decoded: Vec<(&str, &str, &str)> = triples
+            .iter()
+            .map(|triple| dictionary.decode_triple(*triple).unwrap())
+            .collect();
+
+        let second_cell = decoded[2].0;
+        let first_cell = decoded[4].0;
+        let expected = vec![
+            (
+                "<http://example.org/onto#Parent>",
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>",
+                "<http://www.w3.org/2002/07/owl#Class>",
+            ),
+            (
+                "<http://example.org/onto#Person>",
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>",
+                "<http://www.w3.org/2002/07/owl#Class>",
+            ),
+            (
+                second_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#first>",
+                "<http://example.org/onto#HasChild>",
+            ),
+            (
+                second_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#rest>",
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#nil>",
+            ),
+            (
+                first_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#first>",
+                "<http://example.org/onto#Person>",
+            ),
+            (
+                first_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#rest>",
+                second_cell,
+            ),
+            (
+                "<http://example.org/onto#Parent>",
+                "<http://www.w3.org/2002/07/owl#intersectionOf>",
+                first_cell,
+            ),
+        ];
+
+        assert_eq!(expected, decoded);
+    }
+
+    #[test]
+    fn parse_rdfxml_scopes_node_ids_to_their_document() {
+        let mut dictionary = Dictionary::new();
+        let document = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+                <rdf:Description rdf:nodeID="b">
+                    <p xmlns="http://example.org/" rdf:nodeID="b" />
+                </rdf:Description>
+            </rdf:RDF>"#;
+        let first = parse_rdfxml(document, "", &mut dictionary).unwrap();
+        let second = parse_rdfxml(document, "", &mut dictionary).unwrap();
+
+        assert_eq!(first[0].0, first[0].2);
+        assert_ne!(first[0].0, second[0].0);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::dictionary::Dictionary;
+
+pub(crate) const RDF_TYPE: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>";
+pub(crate) const RDF_FIRST: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#first>";
+pub(crate) const RDF_REST: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#rest>";
+pub(crate) const RDF_NIL: &str = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#nil>";
+pub(crate) const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+// Unrolls a collection into an rdf:first/rdf:rest chain terminated by rdf:nil, with one fresh
+// blank node per element, which is the shape expand_lists in the engine expects. Returns the head.
+pub(crate) fn encode_collection(
+    elements: &[u32],
+    dictionary: &mut Dictionary,
+    triples: &mut Vec<(u32, u32, u32)>,
+) -> u32 {
+    let first = dictionary.encode(RDF_FIRST);
+    let rest = dictionary.encode(RDF_REST);
+    let mut tail = dictionary.encode(RDF_NIL);
+    let heads: Vec<u32> = elements
+        .iter()
+        .map(|_| dictionary.fresh_blank_node())
+        .collect();
+    for (head, element) in heads.iter().zip(elements.iter()).rev() {
+        triples.push((*head, first, *element));
+        triples.push((*head, rest, tail));
+        tail = *head;
+    }
+    tail
+}
+
+// Resolves a possibly relative IRI reference against the base, without the angle brackets.
+pub(crate) fn resolve_iri(base: &str, iri: &str) -> String {
+    let has_scheme = match iri.find(':') {
+        Some(idx) => !iri[..idx].contains(['/', '?', '#']),
+        None => false,
+    };
+    if has_scheme || base.is_empty() {
+        return iri.to_string();
+    }
+    let base_without_fragment = base.split('#').next().unwrap_or(base);
+    if iri.is_empty() {
+        return base_without_fragment.to_string();
+    }
+    if iri.starts_with('#') {
+        return format!("{}{}", base_without_fragment, iri);
+    }
+    if iri.starts_with("//") {
+        let scheme_end = base.find(':').map(|idx| idx + 1).unwrap_or(0);
+        return format!("{}{}", &base[..scheme_end], iri);
+    }
+    if iri.starts_with('/') {
+        let authority_end = match base.find("://") {
+            Some(idx) => base[idx + 3..]
+                .find('/')
+                .map(|end| idx + 3 + end)
+                .unwrap_or(base.len()),
+            None => 0,
+        };
+        return format!("{}{}", &base[..authority_end], iri);
+    }
+    let directory_end = base_without_fragment
+        .rfind('/')
+        .map(|idx| idx + 1)
+        .unwrap_or(base_without_fragment.len());
+    format!("{}{}", &base_without_fragment[..directory_end], iri)
+}
+
+// Renders the contents of a string as a quoted N-Triples string, so that the same literal always
+// interns to the same id regardless of the syntax it was read from. Contents coming from Turtle
+// are still escaped, whereas those coming from XML text are not.
+pub(crate) fn quote_literal(contents: &str, escaped: bool) -> String {
+    let mut quoted = String::with_capacity(contents.len() + 2);
+    quoted.push('"');
+    let mut characters = contents.chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' if !escaped => quoted.push_str("\\\\"),
+            '\\' => match characters.next() {
+                Some('\'') => quoted.push('\''),
+                Some(escaped_character) => {
+                    quoted.push('\\');
+                    quoted.push(escaped_character);
+                }
+                None => quoted.push_str("\\\\"),
+            },
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,612 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+
+use crate::dictionary::Dictionary;
+use crate::error::ParseError;
+use crate::terms::{encode_collection, quote_literal, resolve_iri, RDF_TYPE, XSD};
+
+struct TurtleParser<'a, 'b> {
+    input: &'a str,
+    position: usize,
+    base: String,
+    prefixes: HashMap<String, String>,
+    dictionary: &'b mut Dictionary,
+    // Blank node labels are scoped to their document, so each one is minted a fresh node the
+    // first time it shows up, rather than shared with the same label in other documents.
+    blank_nodes: HashMap<&'a str, u32>,
+    triples: Vec<(u32, u32, u32)>,
+}
+
+fn is_name_char(character: char) -> bool {
+    character.is_alphanumeric()
+        || character == '_'
+        || character == '-'
+        || character == '.'
+        || character == ':'
+        || character == '%'
+        || character == '\u{00B7}'
+}
+
+impl<'a, 'b> TurtleParser<'a, 'b> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.position += bytes;
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.advance(rest.len() - trimmed.len());
+            if !trimmed.starts_with('#') {
+                return;
+            }
+            let comment_length = trimmed.find('\n').unwrap_or(trimmed.len());
+            self.advance(comment_length);
+        }
+    }
+
+    fn error(&self, reason: &'static str) -> ParseError {
+        let line = self.input[..self.position].matches('\n').count() + 1;
+        let token = self
+            .rest()
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(32)
+            .collect();
+        ParseError::Syntax {
+            file: String::new(),
+            line,
+            token,
+            reason,
+        }
+    }
+
+    fn expect(&mut self, character: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() != Some(character) {
+            return Err(self.error("unexpected token"));
+        }
+        self.advance(character.len_utf8());
+        Ok(())
+    }
+
+    // Directive keywords are matched case-insensitively for the SPARQL style, and must be followed
+    // by whitespace so that prefixed names such as `prefix:x` are not mistaken for them.
+    fn at_keyword(&self, keyword: &str, case_sensitive: bool) -> bool {
+        let rest = self.rest();
+        if rest.len() <= keyword.len() || !rest.is_char_boundary(keyword.len()) {
+            return false;
+        }
+        let (head, tail) = rest.split_at(keyword.len());
+        let matches = if case_sensitive {
+            head == keyword
+        } else {
+            head.eq_ignore_ascii_case(keyword)
+        };
+        matches && tail.starts_with(char::is_whitespace)
+    }
+
+    fn parse_document(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                return Ok(());
+            }
+            if self.at_keyword("@prefix", true) {
+                self.advance("@prefix".len());
+                self.parse_prefix()?;
+                self.expect('.')?;
+            } else if self.at_keyword("PREFIX", false) {
+                self.advance("PREFIX".len());
+                self.parse_prefix()?;
+            } else if self.at_keyword("@base", true) {
+                self.advance("@base".len());
+                self.parse_base()?;
+                self.expect('.')?;
+            } else if self.at_keyword("BASE", false) {
+                self.advance("BASE".len());
+                self.parse_base()?;
+            } else {
+                self.parse_triples()?;
+                self.expect('.')?;
+            }
+        }
+    }
+
+    fn parse_prefix(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let colon = rest
+            .find(':')
+            .ok_or_else(|| self.error("expected a prefix declaration"))?;
+        let prefix = rest[..colon].to_string();
+        self.advance(colon + 1);
+        self.skip_whitespace();
+        let namespace = self.parse_iri_reference()?;
+        self.prefixes.insert(prefix, namespace);
+        Ok(())
+    }
+
+    fn parse_base(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        self.base = self.parse_iri_reference()?;
+        Ok(())
+    }
+
+    // Parses `<...>` and returns the resolved IRI without its angle brackets.
+    fn parse_iri_reference(&mut self) -> Result<String, ParseError> {
+        if self.peek() != Some('<') {
+            return Err(self.error("expected an IRI"));
+        }
+        let rest = self.rest();
+        let end = rest
+            .find('>')
+            .ok_or_else(|| self.error("unterminated IRI"))?;
+        let iri = resolve_iri(&self.base, &rest[1..end]);
+        self.advance(end + 1);
+        Ok(iri)
+    }
+
+    fn parse_prefixed_name(&mut self) -> Result<String, ParseError> {
+        let rest = self.rest();
+        let mut end = 0;
+        let mut characters = rest.char_indices().peekable();
+        while let Some((idx, character)) = characters.next() {
+            if character == '\\' {
+                characters.next();
+                end = characters.peek().map(|(idx, _)| *idx).unwrap_or(rest.len());
+            } else if is_name_char(character) {
+                end = idx + character.len_utf8();
+            } else {
+                break;
+            }
+        }
+        // A trailing dot terminates the statement rather than belonging to the name.
+        while rest[..end].ends_with('.') {
+            end -= 1;
+        }
+        let name = &rest[..end];
+        let colon = name
+            .find(':')
+            .ok_or_else(|| self.error("expected a prefixed name"))?;
+        let namespace = self
+            .prefixes
+            .get(&name[..colon])
+            .ok_or_else(|| self.error("undeclared prefix"))?;
+        let local_name: String = name[colon + 1..].chars().filter(|c| *c != '\\').collect();
+        let iri = format!("{}{}", namespace, local_name);
+        self.advance(end);
+        Ok(iri)
+    }
+
+    fn parse_iri(&mut self) -> Result<u32, ParseError> {
+        let iri = if self.peek() == Some('<') {
+            self.parse_iri_reference()?
+        } else {
+            self.parse_prefixed_name()?
+        };
+        Ok(self.dictionary.encode(&format!("<{}>", iri)))
+    }
+
+    fn parse_blank_node_label(&mut self) -> Result<u32, ParseError> {
+        let rest = self.rest();
+        let mut end = 2 + rest[2..]
+            .find(|c: char| !is_name_char(c) || c == ':')
+            .unwrap_or(rest.len() - 2);
+        while rest[..end].ends_with('.') {
+            end -= 1;
+        }
+        if end == 2 {
+            return Err(self.error("empty blank node label"));
+        }
+        let label = &rest[..end];
+        self.advance(end);
+        let dictionary = &mut self.dictionary;
+        Ok(*self
+            .blank_nodes
+            .entry(label)
+            .or_insert_with(|| dictionary.fresh_blank_node()))
+    }
+
+    fn parse_subject(&mut self) -> Result<u32, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('_') if self.rest().starts_with("_:") => self.parse_blank_node_label(),
+            Some('(') => self.parse_collection(),
+            Some(_) => self.parse_iri(),
+            None => Err(self.error("expected a subject")),
+        }
+    }
+
+    fn parse_verb(&mut self) -> Result<u32, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if let Some(after) = rest.strip_prefix('a') {
+            let next = after.chars().next();
+            if next.is_none_or(|c| c.is_whitespace() || c == '<' || c == '[' || c == '"') {
+                self.advance(1);
+                return Ok(self.dictionary.encode(RDF_TYPE));
+            }
+        }
+        self.parse_iri()
+    }
+
+    fn parse_triples(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some('[') {
+            let subject = self.parse_blank_node_property_list()?;
+            self.skip_whitespace();
+            if self.peek() == Some('.') {
+                return Ok(());
+            }
+            return self.parse_predicate_object_list(subject);
+        }
+        let subject = self.parse_subject()?;
+        self.parse_predicate_object_list(subject)
+    }
+
+    fn parse_predicate_object_list(&mut self, subject: u32) -> Result<(), ParseError> {
+        loop {
+            let predicate = self.parse_verb()?;
+            self.parse_object_list(subject, predicate)?;
+            self.skip_whitespace();
+            if self.peek() != Some(';') {
+                return Ok(());
+            }
+            while self.peek() == Some(';') {
+                self.advance(1);
+                self.skip_whitespace();
+            }
+            match self.peek() {
+                Some('.') | Some(']') | None => return Ok(()),
+                _ => continue,
+            }
+        }
+    }
+
+    fn parse_object_list(&mut self, subject: u32, predicate: u32) -> Result<(), ParseError> {
+        loop {
+            let object = self.parse_object()?;
+            let triple = self
+                .dictionary
+                .normalize_cardinality((subject, predicate, object));
+            self.triples.push(triple);
+            self.skip_whitespace();
+            if self.peek() != Some(',') {
+                return Ok(());
+            }
+            self.advance(1);
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<u32, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        match self.peek() {
+            Some('<') => self.parse_iri(),
+            Some('_') if rest.starts_with("_:") => self.parse_blank_node_label(),
+            Some('[') => self.parse_blank_node_property_list(),
+            Some('(') => self.parse_collection(),
+            Some('"') | Some('\'') => self.parse_literal(),
+            Some(c) if c.is_ascii_digit() || c == '+' || c == '-' || c == '.' => {
+                self.parse_numeric_literal()
+            }
+            Some(_) if self.at_boolean("true") => self.parse_boolean_literal("true"),
+            Some(_) if self.at_boolean("false") => self.parse_boolean_literal("false"),
+            Some(_) => self.parse_iri(),
+            None => Err(self.error("expected an object")),
+        }
+    }
+
+    fn parse_blank_node_property_list(&mut self) -> Result<u32, ParseError> {
+        self.expect('[')?;
+        let blank_node = self.dictionary.fresh_blank_node();
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            self.parse_predicate_object_list(blank_node)?;
+        }
+        self.expect(']')?;
+        Ok(blank_node)
+    }
+
+    fn parse_collection(&mut self) -> Result<u32, ParseError> {
+        self.expect('(')?;
+        let mut elements = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(')') => break,
+                Some(_) => elements.push(self.parse_object()?),
+                None => return Err(self.error("unterminated collection")),
+            }
+        }
+        self.advance(1);
+        Ok(encode_collection(
+            &elements,
+            self.dictionary,
+            &mut self.triples,
+        ))
+    }
+
+    fn parse_literal(&mut self) -> Result<u32, ParseError> {
+        let rest = self.rest();
+        let quote = rest
+            .chars()
+            .next()
+            .ok_or_else(|| self.error("expected a string literal"))?;
+        let long_quote: String = quote.to_string().repeat(3);
+        let delimiter = if rest.starts_with(&long_quote) {
+            long_quote.as_str()
+        } else {
+            &long_quote[..1]
+        };
+        let body = &rest[delimiter.len()..];
+        let mut escaped = false;
+        let mut closing = None;
+        for (idx, character) in body.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if character == '\\' {
+                escaped = true;
+            } else if body[idx..].starts_with(delimiter) {
+                closing = Some(idx);
+                break;
+            } else if delimiter.len() == 1 && (character == '\n' || character == '\r') {
+                break;
+            }
+        }
+        let closing = closing.ok_or_else(|| self.error("unterminated string literal"))?;
+        let mut literal = quote_literal(&body[..closing], true);
+        self.advance(delimiter.len() + closing + delimiter.len());
+
+        let rest = self.rest();
+        if let Some(language_tag) = rest.strip_prefix('@') {
+            let end = 1 + language_tag
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(language_tag.len());
+            literal.push_str(&rest[..end]);
+            self.advance(end);
+        } else if rest.starts_with("^^") {
+            self.advance(2);
+            let datatype = if self.peek() == Some('<') {
+                self.parse_iri_reference()?
+            } else {
+                self.parse_prefixed_name()?
+            };
+            literal.push_str(&format!("^^<{}>", datatype));
+        }
+        Ok(self.dictionary.encode(&literal))
+    }
+
+    fn parse_numeric_literal(&mut self) -> Result<u32, ParseError> {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let mut end = 0;
+        if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+            end += 1;
+        }
+        while end < bytes.len() && bytes[end].is_ascii_digit() {
+            end += 1;
+        }
+        let mut datatype = "integer";
+        if end + 1 < bytes.len() && bytes[end] == b'.' && bytes[end + 1].is_ascii_digit() {
+            datatype = "decimal";
+            end += 1;
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+        }
+        if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+            datatype = "double";
+            end += 1;
+            if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+                end += 1;
+            }
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+        }
+        if !rest[..end].contains(|c: char| c.is_ascii_digit()) {
+            return Err(self.error("malformed numeric literal"));
+        }
+        let literal = format!("\"{}\"^^<{}{}>", &rest[..end], XSD, datatype);
+        self.advance(end);
+        Ok(self.dictionary.encode(&literal))
+    }
+
+    fn at_boolean(&self, keyword: &str) -> bool {
+        let rest = self.rest();
+        match rest.strip_prefix(keyword) {
+            Some(after) => !after
+                .chars()
+                .next()
+                .is_some_and(|c| is_name_char(c) && c != '.'),
+            None => false,
+        }
+    }
+
+    fn parse_boolean_literal(&mut self, keyword: &str) -> Result<u32, ParseError> {
+        self.advance(keyword.len());
+        let literal = format!("\"{}\"^^<{}boolean>", keyword, XSD);
+        Ok(self.dictionary.encode(&literal))
+    }
+}
+
+pub fn parse_turtle(
+    document: &str,
+    base: &str,
+    dictionary: &mut Dictionary,
+) -> Result<Vec<(u32, u32, u32)>, ParseError> {
+    let mut parser = TurtleParser {
+        input: document,
+        position: 0,
+        base: base.to_string(),
+        prefixes: HashMap::new(),
+        dictionary,
+        blank_nodes: HashMap::new(),
+        triples: vec![],
+    };
+    parser.parse_document()?;
+    Ok(parser.triples)
+}
+
+pub fn loadttl<'a>(
+    filename: &str,
+    dictionary: &'a mut Dictionary,
+) -> Result<impl Iterator<Item = Result<(u32, u32, u32), ParseError>> + 'a, ParseError> {
+    let document = read_to_string(filename).map_err(|error| ParseError::Io {
+        file: filename.to_string(),
+        reason: error.to_string(),
+    })?;
+    let triples =
+        parse_turtle(&document, "", dictionary).map_err(|error| error.in_file(filename))?;
+    Ok(triples.into_iter().map(Ok))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::ntriples::loadnt;
+    use crate::turtle::parse_turtle;
+
+    #[test]
+    fn parse_turtle_matches_ntriples() {
+        let mut dictionary = Dictionary::new();
+        let expected_tbox: Vec<(u32, u32, u32)> =
+            loadnt("../../data/lubm/toy/tbox.nt", &mut dictionary)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        let document = r#"
+            @prefix ub: <http://swat.cse.lehigh.edu/onto/univ-bench.owl#> .
+            PREFIX owl: <http://www.w3.org/2002/07/owl#>
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+            ub:Employee a owl:Class ; rdfs:label "Employee" .
+            ub:Faculty a owl:Class ;
+                rdfs:label 'faculty member' ;
+                rdfs:subClassOf ub:Employee .
+            ub:FullProfessor a owl:Class ; rdfs:label """full professor""" ;
+                rdfs:subClassOf ub:Professor .
+            ub:Professor a owl:Class ; rdfs:label "professor" ; rdfs:subClassOf ub:Faculty .
+            ub:headOf a owl:ObjectProperty ;
+                rdfs:label "is the head of" ;
+                rdfs:subPropertyOf ub:worksFor .
+            ub:memberOf a owl:ObjectProperty ; rdfs:label "member of" .
+            # Comments are ignored
+            ub:worksFor a owl:ObjectProperty ;
+                rdfs:label "Works For" ;
+                rdfs:subPropertyOf ub:memberOf ;
+                a owl:TransitiveProperty .
+            ub:employs owl:inverseOf ub:worksFor .
+            ub:teacherOf a owl:ObjectProperty ; rdfs:label "teaches" ;
+                rdfs:domain ub:Faculty ; rdfs:range ub:Course ; .
+        "#;
+        let tbox = parse_turtle(document, "", &mut dictionary).unwrap();
+
+        assert_eq!(expected_tbox, tbox);
+    }
+
+    #[test]
+    fn parse_turtle_unrolls_collections_and_blank_nodes() {
+        let mut dictionary = Dictionary::new();
+        let document = r#"
+            @base <http://example.org/onto> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            <#Parent> owl:equivalentClass [
+                owl:intersectionOf ( <#Person> <#HasChild> )
+            ] .
+            <#n> <#value> 42, -1.5, 1e3, true .
+        "#;
+        let triples = parse_turtle(document, "", &mut dictionary).unwrap();
+        let decoded: Vec<(&str, &str, &str)> = triples
+            .iter()
+            .map(|triple| dictionary.decode_triple(*triple).unwrap())
+            .collect();
+
+        let second_cell = decoded[0].0;
+        let first_cell = decoded[2].0;
+        let restriction = decoded[4].0;
+        let expected = vec![
+            (
+                second_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#first>",
+                "<http://example.org/onto#HasChild>",
+            ),
+            (
+                second_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#rest>",
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#nil>",
+            ),
+            (
+                first_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#first>",
+                "<http://example.org/onto#Person>",
+            ),
+            (
+                first_cell,
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#rest>",
+                second_cell,
+            ),
+            (
+                restriction,
+                "<http://www.w3.org/2002/07/owl#intersectionOf>",
+                first_cell,
+            ),
+            (
+                "<http://example.org/onto#Parent>",
+                "<http://www.w3.org/2002/07/owl#equivalentClass>",
+                restriction,
+            ),
+            (
+                "<http://example.org/onto#n>",
+                "<http://example.org/onto#value>",
+                "\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+            ),
+            (
+                "<http://example.org/onto#n>",
+                "<http://example.org/onto#value>",
+                "\"-1.5\"^^<http://www.w3.org/2001/XMLSchema#decimal>",
+            ),
+            (
+                "<http://example.org/onto#n>",
+                "<http://example.org/onto#value>",
+                "\"1e3\"^^<http://www.w3.org/2001/XMLSchema#double>",
+            ),
+            (
+                "<http://example.org/onto#n>",
+                "<http://example.org/onto#value>",
+                "\"true\"^^<http://www.w3.org/2001/XMLSchema#boolean>",
+            ),
+        ];
+
+        assert_eq!(expected, decoded);
+    }
+
+    #[test]
+    fn parse_turtle_scopes_blank_node_labels_to_their_document() {
+        let mut dictionary = Dictionary::new();
+        let document = r#"
+            _:b <http://example.org/p> _:c .
+            _:b <http://example.org/q> _:b .
+        "#;
+        let first = parse_turtle(document, "", &mut dictionary).unwrap();
+        let second = parse_turtle(document, "", &mut dictionary).unwrap();
+
+        assert_eq!(first[0].0, first[1].0);
+        assert_eq!(first[1].0, first[1].2);
+        assert_ne!(first[0].0, first[0].2);
+        assert_ne!(first[0].0, second[0].0);
+        assert_ne!(first[0].2, second[0].2);
+    }
+}
@@ -1,17 +1,26 @@
 use clap::{Arg, Command};
-use dire_engine::entrypoint::{entrypoint, Engine};
-use dire_engine::model::types::Triple;
-use dire_parser::load3enc;
+use dire_engine::entrypoint::Engine;
+use dire_engine::model::types::{Inconsistency, Provenance, RdfsEntailment, ShardLoader, Triple};
+use dire_engine::reasoner::Reasoner;
+use dire_parser::binary::BinaryTriples;
+use dire_parser::dictionary::Dictionary;
+use dire_parser::error::{collect_triples, ParseError, ParseMode, Parsed};
+use dire_parser::literals::datatype_entailments;
+use dire_parser::ntriples::{loadnt, writent};
+use dire_parser::rdfxml::loadrdfxml;
+use dire_parser::turtle::loadttl;
+use dire_parser::{load3enc, load3enc_partition};
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::hash::Hash;
 use std::io::Write;
 use std::io::{BufWriter, Read};
-use std::mem::transmute;
 use std::path::Path;
-use std::process::id;
+use std::process::exit;
 use std::string::String;
-use std::thread;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use timely::CommunicationConfig::{Cluster, Process};
 use timely::{Config, WorkerConfig};
 
@@ -29,25 +38,183 @@ fn parse_hosts_file(filename: &str) -> Cfg {
     return config;
 }
 
+// Binary inputs stay memory-mapped, and their triples are decoded from the mapped pages as they
+// are sent to the engine, rather than collected up front.
+enum Input {
+    Parsed(Vec<Triple>),
+    Mapped(BinaryTriples),
+}
+
+impl Input {
+    fn len(&self) -> usize {
+        match self {
+            Input::Parsed(triples) => triples.len(),
+            Input::Mapped(triples) => triples.len(),
+        }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = Triple> + '_> {
+        match self {
+            Input::Parsed(triples) => Box::new(triples[start..end].iter().copied()),
+            Input::Mapped(triples) => Box::new(triples.as_slice().range(start, end).iter()),
+        }
+    }
+}
+
+fn load_triples(filename: &str, dictionary: &mut Dictionary, mode: ParseMode) -> Input {
+    if filename.ends_with(".ntbin") {
+        return match BinaryTriples::open(filename) {
+            Ok(triples) => Input::Mapped(triples),
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1);
+            }
+        };
+    }
+    let parsed = if filename.ends_with(".nt") {
+        loadnt(filename, dictionary).and_then(|triples| collect_triples(triples, mode))
+    } else if filename.ends_with(".ttl") {
+        loadttl(filename, dictionary).and_then(|triples| collect_triples(triples, mode))
+    } else if filename.ends_with(".owl") || filename.ends_with(".rdf") {
+        loadrdfxml(filename, dictionary).and_then(|triples| collect_triples(triples, mode))
+    } else {
+        load3enc(filename).and_then(|triples| collect_triples(triples, mode))
+    };
+    Input::Parsed(unwrap_parsed(filename, parsed))
+}
+
+fn unwrap_parsed(filename: &str, parsed: Result<Parsed, ParseError>) -> Vec<Triple> {
+    match parsed {
+        Ok(parsed) => report_skipped(filename, parsed),
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1);
+        }
+    }
+}
+
+fn report_skipped(filename: &str, parsed: Parsed) -> Vec<Triple> {
+    if !parsed.skipped.is_empty() {
+        println!(
+            "Skipped {} malformed lines in {}",
+            parsed.skipped.len(),
+            filename
+        );
+    }
+    parsed.triples
+}
+
+// The capacity of the input channels in cluster mode, which only carry the triples entailed outside
+// of the engine.
+const SHARDED_CHANNEL_CAPACITY: usize = 1024;
+
+// Encoded inputs can be split without a shared dictionary, so that each worker reads only its
+// own share of the file: a byte range of an .ntenc file, or a slice of a memory-mapped .ntbin one.
+// The workers read them on their own threads, so failures are handed back to the engine, which
+// reports them once the first commit is through.
+fn shard_loader(filename: &str, mode: ParseMode) -> Option<ShardLoader> {
+    let filename = filename.to_string();
+    if filename.ends_with(".ntbin") {
+        Some(Arc::new(move |index, peers| {
+            BinaryTriples::open(&filename)
+                .map(|triples| triples.partition(index, peers).iter().collect())
+                .map_err(|error| error.to_string())
+        }))
+    } else if filename.ends_with(".ntenc") {
+        Some(Arc::new(move |index, peers| {
+            load3enc_partition(&filename, index, peers)
+                .and_then(|triples| collect_triples(triples, mode))
+                .map(|parsed| report_skipped(&filename, parsed))
+                .map_err(|error| error.to_string())
+        }))
+    } else {
+        None
+    }
+}
+
+// Stops before anything is written out, if part of the input was left out.
+fn exit_on_errors(reasoner: &Reasoner) {
+    if !reasoner.errors().is_empty() {
+        for error in reasoner.errors() {
+            eprintln!("{}", error);
+        }
+        exit(1);
+    }
+}
+
+// Sums up the diffs of an output channel, keeping what is still present at the end.
+fn consolidate_diffs<T: Hash + Eq + Ord>(diffs: impl Iterator<Item = (T, u64, isize)>) -> Vec<T> {
+    let mut multiplicities: HashMap<T, isize> = HashMap::new();
+    for (item, _time, diff) in diffs {
+        *multiplicities.entry(item).or_insert(0) += diff;
+    }
+    let mut consolidated: Vec<T> = multiplicities
+        .into_iter()
+        .filter(|(_item, multiplicity)| *multiplicity > 0)
+        .map(|(item, _multiplicity)| item)
+        .collect();
+    consolidated.sort_unstable();
+    consolidated
+}
+
+fn write_inconsistencies(
+    filename: &str,
+    inconsistencies: &[Inconsistency],
+    dictionary: &Dictionary,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    for (rule, witnesses) in inconsistencies {
+        writeln!(writer, "{}", rule)?;
+        for witness in witnesses {
+            match dictionary.decode_triple(*witness) {
+                Some((s, p, o)) => writeln!(writer, "    {} {} {} .", s, p, o)?,
+                None => writeln!(writer, "    {:?}", witness)?,
+            }
+        }
+    }
+    writer.flush()
+}
+
+fn write_provenance(
+    filename: &str,
+    provenance: &[Provenance],
+    dictionary: &Dictionary,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    for (triple, rule, premises) in provenance {
+        match dictionary.decode_triple(*triple) {
+            Some((s, p, o)) => writeln!(writer, "{} {} {} . {}", s, p, o, rule)?,
+            None => writeln!(writer, "{:?} {}", triple, rule)?,
+        }
+        for premise in premises {
+            match dictionary.decode_triple(*premise) {
+                Some((s, p, o)) => writeln!(writer, "    {} {} {} .", s, p, o)?,
+                None => writeln!(writer, "    {:?}", premise)?,
+            }
+        }
+    }
+    writer.flush()
+}
+
 fn main() {
     let matches = Command::new("differential-reasoner")
         .version("1.3.1")
         .about("Reasons in a differential manner 😎")
         .arg(
             Arg::new("TBOX_PATH")
-                .help("Sets the tbox file path")
+                .help("Sets the tbox file path, either .ntenc, .ntbin, .nt, .ttl or .owl/.rdf")
                 .required(true)
                 .index(1),
         )
         .arg(
             Arg::new("ABOX_PATH")
-                .help("Sets the abox file path")
+                .help("Sets the abox file path, either .ntenc, .ntbin, .nt, .ttl or .owl/.rdf")
                 .required(true)
                 .index(2),
         )
         .arg(
             Arg::new("EXPRESSIVITY")
-                .help("Sets the expressivity")
+                .help("Sets the expressivity, either rdfs, rdfsfull, rdfspp or owl2rl")
                 .required(true)
                 .index(3),
         )
@@ -59,16 +226,63 @@ fn main() {
         )
         .arg(
             Arg::new("BATCH_SIZE")
-                .help("Sets the batch size")
+                .help("Sets the share of the abox inserted by the first commit, the rest being inserted and then retracted by two more. Cluster mode takes 1.0 only, as every worker loads its share of the abox at once")
                 .required(true)
                 .index(5),
         )
         .arg(
             Arg::new("HOSTFILE")
-                .help("Sets the hostfile")
+                .help("Sets the hostfile, for cluster mode, which takes .ntenc or .ntbin inputs and a batch size of 1.0 only")
                 .required(false)
                 .index(6),
         )
+        .arg(
+            Arg::new("RDFS_DISABLE")
+                .long("rdfs-disable")
+                .takes_value(true)
+                .use_value_delimiter(true)
+                .possible_values([
+                    "axiomatic-triples",
+                    "property-typing",
+                    "resource-typing",
+                    "reflexivity",
+                    "container-membership",
+                    "datatypes",
+                ])
+                .help("Turns off parts of the rdfsfull entailment, separated by commas"),
+        )
+        .arg(
+            Arg::new("SAMEAS")
+                .long("sameas")
+                .takes_value(true)
+                .possible_values(["expansion", "representatives"])
+                .default_value("expansion")
+                .help("Sets how owl2rl handles sameAs, either copying triples onto every peer, or rewriting them to one representative per clique"),
+        )
+        .arg(
+            Arg::new("LENIENT")
+                .long("lenient")
+                .help("Skips and counts malformed input lines, instead of failing on the first one"),
+        )
+        .arg(
+            Arg::new("DICTIONARY")
+                .long("dictionary")
+                .takes_value(true)
+                .help("Sets the dictionary file, loaded if it exists and extended with the terms of non-encoded inputs"),
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .long("output")
+                .takes_value(true)
+                .requires("DICTIONARY")
+                .help("Sets the directory the materialized tbox and abox are written to, as N-Triples"),
+        )
+        .arg(
+            Arg::new("PROVENANCE")
+                .long("provenance")
+                .requires("OUTPUT")
+                .help("Also writes the rule and premises of every one-step derivation of the abox materialization"),
+        )
         .get_matches();
 
     let t_path: String = matches.value_of("TBOX_PATH").unwrap().to_string();
@@ -84,13 +298,29 @@ fn main() {
         .unwrap()
         .parse::<f64>()
         .unwrap();
+    let mut rdfs_entailment = RdfsEntailment::default();
+    for disabled in matches.values_of("RDFS_DISABLE").into_iter().flatten() {
+        match disabled {
+            "axiomatic-triples" => rdfs_entailment.axiomatic_triples = false,
+            "property-typing" => rdfs_entailment.property_typing = false,
+            "resource-typing" => rdfs_entailment.resource_typing = false,
+            "reflexivity" => rdfs_entailment.reflexivity = false,
+            "container-membership" => rdfs_entailment.container_membership = false,
+            _ => rdfs_entailment.datatypes = false,
+        }
+    }
     let logic = match expressivity.as_str() {
         "rdfspp" => Engine::RDFSpp,
         "rdfs" => Engine::RDFS,
-        "owl2rl" => Engine::OWL2RL,
+        "rdfsfull" => Engine::RDFSFull(rdfs_entailment),
+        "owl2rl" => match matches.value_of("SAMEAS") {
+            Some("representatives") => Engine::OWL2RLRepresentatives,
+            _ => Engine::OWL2RL,
+        },
         _ => Engine::Dummy,
     };
     let distributed: bool = matches.is_present("HOSTFILE");
+    let mut process_index = 0;
     let mut cfg: Config = Config {
         communication: Process(workers),
         worker: WorkerConfig::default(),
@@ -98,6 +328,7 @@ fn main() {
     if distributed {
         let hostfile = matches.value_of("HOSTFILE").unwrap().to_string();
         let parsed_hostfile = parse_hosts_file(&hostfile);
+        process_index = parsed_hostfile.index;
         cfg = Config {
             worker: WorkerConfig::default(),
             communication: Cluster {
@@ -110,82 +341,198 @@ fn main() {
         };
     }
 
-    let abox_iter = load3enc(&a_path);
-    let abox_vec: Vec<Triple> = abox_iter.unwrap().collect();
-    let cutoff: usize = (abox_vec.len() as f64 * batch_size) as usize;
+    let is_encoded = |path: &str| path.ends_with(".ntenc") || path.ends_with(".ntbin");
+    let encoded_inputs = is_encoded(&t_path) && is_encoded(&a_path);
+    let dictionary_path: Option<String> = matches.value_of("DICTIONARY").map(|p| p.to_string());
+    let mut dictionary = match &dictionary_path {
+        Some(path) if Path::new(path).exists() => Dictionary::load(path).unwrap(),
+        _ => Dictionary::new(),
+    };
+    let parse_mode = if matches.is_present("LENIENT") {
+        ParseMode::Lenient
+    } else {
+        ParseMode::Strict
+    };
+    // In cluster mode every worker loads its own share of the encoded inputs, rather than having
+    // each process read and send everything, which would take every triple in once per process.
+    // The shards all go into the first commit, so there is no cutoff to split them by either.
+    let (tbox_shard, abox_shard) = if distributed {
+        match (
+            shard_loader(&t_path, parse_mode),
+            shard_loader(&a_path, parse_mode),
+        ) {
+            (Some(tbox_shard), Some(abox_shard)) => (Some(tbox_shard), Some(abox_shard)),
+            _ => {
+                eprintln!("Cluster mode takes .ntenc or .ntbin inputs only, which every worker loads a share of");
+                exit(1);
+            }
+        }
+    } else {
+        (None, None)
+    };
+    if distributed && batch_size != 1.0 {
+        eprintln!("Cluster mode takes a batch size of 1.0 only, as the sharded inputs all go into the first commit");
+        exit(1);
+    }
+    let tbox_input = if tbox_shard.is_some() {
+        Input::Parsed(vec![])
+    } else {
+        load_triples(&t_path, &mut dictionary, parse_mode)
+    };
+    let abox_input = if abox_shard.is_some() {
+        Input::Parsed(vec![])
+    } else {
+        load_triples(&a_path, &mut dictionary, parse_mode)
+    };
+    // Triples entailed outside of the engine go along with the inputs, and ill-typed literals are
+    // reported on their own.
+    let mut tbox_entailed: Vec<Triple> = vec![];
+    let mut ill_typed: Vec<(u32, u32)> = vec![];
+    // Whatever the datatype rules entail depends on the literals alone, so it is worked out from the
+    // dictionary rather than by the engine, which only sees ids.
+    if let Engine::OWL2RL | Engine::OWL2RLRepresentatives = logic {
+        // Encoded inputs only hold ids, so their literals are only known from the dictionary they
+        // were encoded with.
+        let dictionary_loaded = dictionary_path
+            .as_ref()
+            .is_some_and(|path| Path::new(path).exists());
+        if (is_encoded(&t_path) || is_encoded(&a_path)) && !dictionary_loaded {
+            eprintln!("Warning: encoded inputs without --dictionary leave their literals unknown, so the datatype rules entail nothing and no ill-typed literal is reported");
+        }
+        let entailments = datatype_entailments(&mut dictionary);
+        tbox_entailed.extend(entailments.triples);
+        ill_typed.extend(entailments.ill_typed);
+    }
+    // Every process of a cluster runs its own driver, so the triples entailed outside of the engine
+    // are only sent by the first one, rather than once per process.
+    if process_index != 0 {
+        tbox_entailed.clear();
+        ill_typed.clear();
+    }
+    if let Some(path) = &dictionary_path {
+        if !encoded_inputs {
+            dictionary.save(path).unwrap();
+        }
+    }
+    let cutoff: usize = (abox_input.len() as f64 * batch_size) as usize;
 
     let mut batch_size: usize = 0;
-    if cutoff == 0 {
-        batch_size = abox_vec.len();
+    if distributed {
+        // The shards do not go through the input channels.
+        batch_size = SHARDED_CHANNEL_CAPACITY;
+    } else if cutoff == 0 {
+        batch_size = abox_input.len().max(1);
     } else {
         batch_size = cutoff
     }
 
-    let (
-        tbox_input_sink,
-        abox_input_sink,
-        tbox_output_source,
-        abox_output_source,
-        done_source,
-        terminator_sink,
-        logs,
-        joinhandle,
-    ) = entrypoint(cfg, batch_size, logic);
-
-    let tbox_iter = load3enc(&t_path);
-    if let Ok(parsed_nt) = tbox_iter {
-        parsed_nt.for_each(|triple| {
-            tbox_input_sink.send((triple, 1)).unwrap();
-        })
-    }
-
-    if cutoff != 0 && cutoff != abox_vec.len() {
-        let addition_vec: &[Triple];
-        let deletion_vec: &[Triple];
-        addition_vec = &abox_vec[cutoff..];
-        println!("Addition update size: {}", addition_vec.len());
-        deletion_vec = &abox_vec[cutoff..];
-        println!("Deletion update size: {}", deletion_vec.len());
-
-        for (idx, triple) in abox_vec.iter().enumerate() {
-            if idx == cutoff {
-                break;
-            }
-            abox_input_sink.send((*triple, 1)).unwrap();
-        }
+    let mut builder = Reasoner::builder()
+        .config(cfg)
+        .engine(logic)
+        .batch_size(batch_size)
+        .provenance(matches.is_present("PROVENANCE"));
+    if let Some(tbox_shard) = tbox_shard {
+        builder = builder.tbox_shard(tbox_shard);
+    }
+    if let Some(abox_shard) = abox_shard {
+        builder = builder.abox_shard(abox_shard);
+    }
+    // The reasoner adds the axiomatic triples of the rdf:_n properties that come in, which only the
+    // dictionary tells apart.
+    let container_membership_properties: HashSet<u32> = dictionary
+        .container_membership_properties()
+        .into_iter()
+        .collect();
+    builder = builder.container_membership(Box::new(move |id| {
+        container_membership_properties.contains(&id)
+    }));
+    let mut reasoner = builder.build();
 
-        for _ in 0..workers {
-            done_source.recv();
-            terminator_sink.send("CONTINUE".to_string());
-        }
+    tbox_input
+        .range(0, tbox_input.len())
+        .chain(tbox_entailed)
+        .for_each(|triple| {
+            reasoner.insert_tbox(triple);
+        });
+    ill_typed.into_iter().for_each(|literal| {
+        reasoner.insert_ill_typed(literal);
+    });
+
+    if cutoff != 0 && cutoff != abox_input.len() {
+        println!("Addition update size: {}", abox_input.len() - cutoff);
+        println!("Deletion update size: {}", abox_input.len() - cutoff);
 
-        addition_vec.iter().for_each(|triple| {
-            abox_input_sink.send((*triple, 1)).unwrap();
+        abox_input.range(0, cutoff).for_each(|triple| {
+            reasoner.insert_abox(triple);
         });
 
-        for _ in 0..workers {
-            done_source.recv();
-            terminator_sink.send("CONTINUE".to_string());
-        }
+        reasoner.commit();
+        exit_on_errors(&reasoner);
+
+        abox_input
+            .range(cutoff, abox_input.len())
+            .for_each(|triple| {
+                reasoner.insert_abox(triple);
+            });
+
+        reasoner.commit();
 
-        deletion_vec.iter().for_each(|triple| {
-            abox_input_sink.send((*triple, -1)).unwrap();
-        })
+        abox_input
+            .range(cutoff, abox_input.len())
+            .for_each(|triple| {
+                reasoner.retract_abox(triple);
+            })
     } else {
-        abox_vec.iter().for_each(|triple| {
-            abox_input_sink.send((*triple, 1)).unwrap();
+        abox_input.range(0, abox_input.len()).for_each(|triple| {
+            reasoner.insert_abox(triple);
         });
     }
 
-    for _ in 0..workers {
-        done_source.recv();
-        terminator_sink.send("STOP".to_string());
-    }
+    reasoner.shutdown();
+    exit_on_errors(&reasoner);
 
-    joinhandle.join().unwrap();
+    println!(
+        "materialized tbox triples: {}",
+        reasoner.tbox_changes().len()
+    );
+    println!(
+        "materialized abox triples: {}",
+        reasoner.abox_changes().len()
+    );
+    let inconsistencies = consolidate_diffs(reasoner.inconsistencies().try_iter());
+    println!("inconsistencies: {}", inconsistencies.len());
 
-    println!("materialized tbox triples: {}", tbox_output_source.len());
-    println!("materialized abox triples: {}", abox_output_source.len());
+    if let Some(output_dir) = matches.value_of("OUTPUT") {
+        let output_dir = Path::new(output_dir);
+        let tbox_materialization = consolidate_diffs(reasoner.tbox_changes().try_iter());
+        let abox_materialization = consolidate_diffs(reasoner.abox_changes().try_iter());
+        writent(
+            output_dir.join("tbox.nt").to_str().unwrap(),
+            tbox_materialization.iter(),
+            &dictionary,
+        )
+        .unwrap();
+        writent(
+            output_dir.join("abox.nt").to_str().unwrap(),
+            abox_materialization.iter(),
+            &dictionary,
+        )
+        .unwrap();
+        write_inconsistencies(
+            output_dir.join("inconsistencies.txt").to_str().unwrap(),
+            &inconsistencies,
+            &dictionary,
+        )
+        .unwrap();
+        if matches.is_present("PROVENANCE") {
+            write_provenance(
+                output_dir.join("provenance.txt").to_str().unwrap(),
+                &consolidate_diffs(reasoner.provenance().try_iter()),
+                &dictionary,
+            )
+            .unwrap();
+        }
+    }
 
     let a_filename = match Path::new(&a_path).file_stem() {
         Some(file_name) => file_name,
@@ -206,7 +553,7 @@ fn main() {
     .unwrap();
     let mut log_writer = BufWriter::new(log_file);
     writeln!(&mut log_writer, "{}", "file,latency,added,removed,worker");
-    while let Ok(log) = logs.try_recv() {
+    while let Ok(log) = reasoner.logs().try_recv() {
         writeln!(&mut log_writer, "{}", log);
     }
 }